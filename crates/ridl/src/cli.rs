@@ -0,0 +1,347 @@
+use ridl_codegen::{
+  CodegenOptions, CodegenTargetType, Int64Json, MockOptions, PythonStyle, TsValidator,
+};
+use ridl_ir::dump::DumpFilter;
+use ridl_utils::fs::FilesFilter;
+use ridl_utils::glob::Glob;
+use ridl_utils::types::IdlProtocolType;
+
+pub const USAGE: &str = "usage:
+  ridl                 generate the ts code of the demo in the playground
+  ridl gen <folder> [--protocol <protocol>] [--target <target>] [--out <folder>]
+           [--i64 string|number] [--go-module <module>]
+           [--python dataclass|typeddict|pydantic] [--ts-guards]
+           [--validator zod|io-ts|valibot]
+                       generate the code of the idl files into the out folder (default `output`),
+                       the 64-bit integers are json strings by default, the go packages are
+                       imported under the go module, the python classes are dataclasses by default,
+                       the ts types are followed by their type guards with `--ts-guards`, the
+                       validator target declares zod schemas by default
+  ridl dump <folder> [--protocol <protocol>] [--namespace <namespace>] [--type <name>]
+                       print the parsed idl files as json
+  ridl dump --schema   print the json schema of the dump format
+  ridl mock <type> [--in <folder>] [--protocol <protocol>] [--count <n>] [--seed <n>]
+           [--list-size <n>] [--optional-probability <p>] [--i64 string|number]
+                       print json samples of the type found in the folder (default `.`), the
+                       type may be qualified by its namespace, e.g. `ek.course.Course`
+
+the idl files of the folder are selected by [--include <glob>] [--exclude <glob>] [--max-depth <n>]
+of every command, the globs may be given more than once and `.gitignore` / `.ridlignore` apply
+
+protocols: thrift (default), thrift-json, protobuf, avro, graphql, openapi, flatbuffers, capnp
+targets: ts (default), proto, thrift, jsonschema, openapi, rust, go, python,
+         kotlin, swift, dart, validator, mock";
+
+pub struct GenArgs {
+  pub protocol_type: IdlProtocolType,
+  pub target_type: CodegenTargetType,
+  pub options: CodegenOptions,
+  pub folder_path: String,
+  pub files_filter: FilesFilter,
+  pub output_path: String,
+}
+
+pub struct DumpArgs {
+  pub protocol_type: IdlProtocolType,
+  pub folder_path: String,
+  pub files_filter: FilesFilter,
+  pub filter: DumpFilter,
+}
+
+pub struct MockArgs {
+  pub protocol_type: IdlProtocolType,
+  pub folder_path: String,
+  pub files_filter: FilesFilter,
+  pub type_name: String,
+  pub count: usize,
+  pub mock_options: MockOptions,
+  pub options: CodegenOptions,
+}
+
+pub enum Command {
+  Demo,
+  Gen(GenArgs),
+  Dump(DumpArgs),
+  DumpSchema,
+  Mock(MockArgs),
+}
+
+/// Parse the number of an option, e.g. the `3` of `--count 3`.
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+  value
+    .parse()
+    .map_err(|_| format!("invalid number `{}` of `{}`", value, option))
+}
+
+/// Parse the probability of an option in `[0, 1]`, e.g. the `0.2` of `--optional-probability 0.2`.
+fn parse_probability(option: &str, value: &str) -> Result<f64, String> {
+  match value.parse() {
+    Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+    _ => Err(format!("invalid probability `{}` of `{}`", value, option)),
+  }
+}
+
+/// Check the glob of an option, e.g. the `idl/**` of `--include idl/**`.
+fn parse_glob(option: &str, value: String) -> Result<String, String> {
+  match Glob::new(&value) {
+    Ok(_) => Ok(value),
+    Err(_) => Err(format!("invalid glob `{}` of `{}`", value, option)),
+  }
+}
+
+/// Parse the arguments following the program name.
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+  let Some((command, args)) = args.split_first() else {
+    return Ok(Command::Demo);
+  };
+  let (is_gen, is_dump, is_mock) = match command.as_str() {
+    "gen" => (true, false, false),
+    "dump" => (false, true, false),
+    "mock" => (false, false, true),
+    _ => return Err(format!("unknown command `{}`", command)),
+  };
+
+  let mut protocol_type = IdlProtocolType::Thrift;
+  let mut target_type = CodegenTargetType::Ts;
+  let mut options = CodegenOptions::default();
+  let mut output_path = "output".to_string();
+  let mut folder_path = None;
+  let mut files_filter = FilesFilter {
+    use_ignore_files: true,
+    ..FilesFilter::default()
+  };
+  let mut filter = DumpFilter::default();
+  let mut type_name = None;
+  let mut count = 1;
+  let mut mock_options = MockOptions::default();
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let mut value_of = |option: &str| {
+      args
+        .next()
+        .cloned()
+        .ok_or(format!("missing the value of `{}`", option))
+    };
+    match arg.as_str() {
+      "--protocol" => {
+        let name = value_of(arg)?;
+        protocol_type =
+          IdlProtocolType::from_name(&name).ok_or(format!("unknown protocol `{}`", name))?;
+      }
+      "--target" if is_gen => {
+        let name = value_of(arg)?;
+        target_type =
+          CodegenTargetType::from_name(&name).ok_or(format!("unknown target `{}`", name))?;
+      }
+      "--out" if is_gen => output_path = value_of(arg)?,
+      "--i64" if is_gen || is_mock => {
+        let name = value_of(arg)?;
+        options.int64_json =
+          Int64Json::from_name(&name).ok_or(format!("unknown i64 json `{}`", name))?;
+      }
+      "--go-module" if is_gen => options.go_module = value_of(arg)?,
+      "--ts-guards" if is_gen => options.ts_guards = true,
+      "--python" if is_gen => {
+        let name = value_of(arg)?;
+        options.python_style =
+          PythonStyle::from_name(&name).ok_or(format!("unknown python style `{}`", name))?;
+      }
+      "--validator" if is_gen => {
+        let name = value_of(arg)?;
+        options.validator =
+          TsValidator::from_name(&name).ok_or(format!("unknown validator `{}`", name))?;
+      }
+      "--include" => files_filter.include.push(parse_glob(arg, value_of(arg)?)?),
+      "--exclude" => files_filter.exclude.push(parse_glob(arg, value_of(arg)?)?),
+      "--max-depth" => files_filter.max_depth = Some(parse_number(arg, &value_of(arg)?)?),
+      "--schema" if is_dump => return Ok(Command::DumpSchema),
+      "--namespace" if is_dump => filter.namespace = Some(value_of(arg)?),
+      "--type" if is_dump => filter.r#type = Some(value_of(arg)?),
+      "--in" if is_mock => folder_path = Some(value_of(arg)?),
+      "--count" if is_mock => count = parse_number(arg, &value_of(arg)?)?,
+      "--seed" if is_mock => mock_options.seed = parse_number(arg, &value_of(arg)?)?,
+      "--list-size" if is_mock => mock_options.list_size = parse_number(arg, &value_of(arg)?)?,
+      "--optional-probability" if is_mock => {
+        mock_options.optional_probability = parse_probability(arg, &value_of(arg)?)?
+      }
+      _ if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg)),
+      _ if is_mock && type_name.is_none() => type_name = Some(arg.clone()),
+      _ if is_mock => return Err(format!("unexpected argument `{}`", arg)),
+      _ if folder_path.is_none() => folder_path = Some(arg.clone()),
+      _ => return Err(format!("unexpected argument `{}`", arg)),
+    }
+  }
+  if is_mock {
+    return Ok(Command::Mock(MockArgs {
+      protocol_type,
+      folder_path: folder_path.unwrap_or(".".to_string()),
+      files_filter,
+      type_name: type_name.ok_or("missing the type to mock")?,
+      count,
+      mock_options,
+      options,
+    }));
+  }
+  if is_gen {
+    return Ok(Command::Gen(GenArgs {
+      protocol_type,
+      target_type,
+      options,
+      folder_path: folder_path.ok_or("missing the folder to generate")?,
+      files_filter,
+      output_path,
+    }));
+  }
+  Ok(Command::Dump(DumpArgs {
+    protocol_type,
+    folder_path: folder_path.ok_or("missing the folder to dump")?,
+    files_filter,
+    filter,
+  }))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{parse_args, Command};
+  use ridl_codegen::{CodegenTargetType, Int64Json, PythonStyle, TsValidator};
+  use ridl_utils::types::IdlProtocolType;
+
+  fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|a| a.to_string()).collect()
+  }
+
+  fn err(s: &str) -> String {
+    parse_args(&args(s)).err().unwrap()
+  }
+
+  #[test]
+  fn test_parse_demo() {
+    assert!(matches!(parse_args(&[]), Ok(Command::Demo)));
+  }
+
+  #[test]
+  fn test_parse_dump() {
+    assert!(matches!(
+      parse_args(&args("dump --schema")),
+      Ok(Command::DumpSchema)
+    ));
+    match parse_args(&args(
+      "dump idl --protocol proto --type Course --namespace foo.bar",
+    )) {
+      Ok(Command::Dump(dump)) => {
+        assert!(matches!(dump.protocol_type, IdlProtocolType::Protobuf));
+        assert_eq!(dump.folder_path, "idl");
+        assert_eq!(dump.filter.namespace.as_deref(), Some("foo.bar"));
+        assert_eq!(dump.filter.r#type.as_deref(), Some("Course"));
+      }
+      _ => panic!("should be a dump"),
+    }
+  }
+
+  #[test]
+  fn test_parse_gen() {
+    match parse_args(&args(
+      "gen idl --target proto --out protos --i64 number --go-module x --python pydantic --ts-guards --validator io-ts",
+    )) {
+      Ok(Command::Gen(gen)) => {
+        assert!(matches!(gen.protocol_type, IdlProtocolType::Thrift));
+        assert!(matches!(gen.target_type, CodegenTargetType::Proto));
+        assert_eq!(gen.folder_path, "idl");
+        assert_eq!(gen.output_path, "protos");
+        assert_eq!(gen.options.int64_json, Int64Json::Number);
+        assert_eq!(gen.options.go_module, "x");
+        assert_eq!(gen.options.python_style, PythonStyle::Pydantic);
+        assert!(gen.options.ts_guards);
+        assert_eq!(gen.options.validator, TsValidator::IoTs);
+      }
+      _ => panic!("should be a gen"),
+    }
+  }
+
+  #[test]
+  fn test_parse_mock() {
+    match parse_args(&args(
+      "mock ek.course.Course --in idl --count 3 --seed 7 --list-size 1 --optional-probability 0.2",
+    )) {
+      Ok(Command::Mock(mock)) => {
+        assert_eq!(mock.type_name, "ek.course.Course");
+        assert_eq!(mock.folder_path, "idl");
+        assert_eq!(mock.count, 3);
+        assert_eq!(mock.mock_options.seed, 7);
+        assert_eq!(mock.mock_options.list_size, 1);
+        assert_eq!(mock.mock_options.optional_probability, 0.2);
+      }
+      _ => panic!("should be a mock"),
+    }
+    match parse_args(&args("mock Course")) {
+      Ok(Command::Mock(mock)) => {
+        assert_eq!(mock.folder_path, ".");
+        assert_eq!(mock.count, 1);
+      }
+      _ => panic!("should be a mock"),
+    }
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert_eq!(err("build"), "unknown command `build`");
+    assert_eq!(err("gen"), "missing the folder to generate");
+    assert_eq!(err("gen idl --target x"), "unknown target `x`");
+    assert_eq!(err("gen idl --i64 bigint"), "unknown i64 json `bigint`");
+    assert_eq!(
+      err("gen idl --python attrs"),
+      "unknown python style `attrs`"
+    );
+    assert_eq!(err("gen idl --validator yup"), "unknown validator `yup`");
+    assert_eq!(err("mock"), "missing the type to mock");
+    assert_eq!(
+      err("mock Course --count x"),
+      "invalid number `x` of `--count`"
+    );
+    for p in ["7", "-1", "NaN", "x"] {
+      assert_eq!(
+        err(&format!("mock Course --optional-probability {}", p)),
+        format!("invalid probability `{}` of `--optional-probability`", p)
+      );
+    }
+    assert_eq!(err("mock Course Lesson"), "unexpected argument `Lesson`");
+    assert_eq!(err("gen idl --type Course"), "unknown option `--type`");
+    assert_eq!(err("dump"), "missing the folder to dump");
+    assert_eq!(err("dump idl --type"), "missing the value of `--type`");
+    assert_eq!(err("dump idl --protocol x"), "unknown protocol `x`");
+    assert_eq!(err("dump idl --x"), "unknown option `--x`");
+    assert_eq!(err("dump a b"), "unexpected argument `b`");
+  }
+
+  #[test]
+  fn test_parse_files_filter() {
+    match parse_args(&args(
+      "gen idl --include course/** --include *.thrift --exclude vendor --max-depth 2",
+    )) {
+      Ok(Command::Gen(gen)) => {
+        assert_eq!(gen.files_filter.include, ["course/**", "*.thrift"]);
+        assert_eq!(gen.files_filter.exclude, ["vendor"]);
+        assert_eq!(gen.files_filter.max_depth, Some(2));
+        assert!(gen.files_filter.use_ignore_files);
+      }
+      _ => panic!("should be a gen"),
+    }
+    match parse_args(&args("dump idl --exclude legacy/**")) {
+      Ok(Command::Dump(dump)) => assert_eq!(dump.files_filter.exclude, ["legacy/**"]),
+      _ => panic!("should be a dump"),
+    }
+    match parse_args(&args("mock Course --max-depth 0")) {
+      Ok(Command::Mock(mock)) => assert_eq!(mock.files_filter.max_depth, Some(0)),
+      _ => panic!("should be a mock"),
+    }
+
+    assert_eq!(
+      err("gen idl --include [z-a]"),
+      "invalid glob `[z-a]` of `--include`"
+    );
+    assert_eq!(
+      err("dump idl --max-depth x"),
+      "invalid number `x` of `--max-depth`"
+    );
+  }
+}
@@ -1,16 +1,274 @@
+use std::{env, process};
+
 use ridl_block::iter_and_collect_all_idl_blocks;
-use ridl_parser::parse_idl_metas_from_blocks;
-use ridl_utils::types::{IdlProtocolType};
+use ridl_codegen::{
+  gen_code_from_schema, mock_samples, CodegenFilesMap, CodegenOptions, CodegenTargetType,
+};
+use ridl_ir::dump::{dump, DUMP_JSON_SCHEMA};
+use ridl_ir::schema::Schema;
+use ridl_parser::parse_schema_from_blocks;
+use ridl_utils::error::RidlError;
+use ridl_utils::fs::{write_file_str_content, DiskFs, FileSystem, FilesFilter};
+use ridl_utils::types::IdlProtocolType;
 
-fn main() {
-  let protocol_type = IdlProtocolType::Thrift;
+mod cli;
+use cli::{parse_args, Command, USAGE};
+
+fn parse(
+  fs: &dyn FileSystem,
+  protocol_type: &IdlProtocolType,
+  folder_path: &str,
+  files_filter: &FilesFilter,
+) -> Result<Schema, RidlError> {
   // Block
   let namespace_blocks_map =
-    iter_and_collect_all_idl_blocks(&protocol_type, "./playground/0-parser/demo");
+    iter_and_collect_all_idl_blocks(fs, protocol_type, folder_path, files_filter)?;
   // println!("idl blocks: {:?}", namespace_blocks_map);
 
   // Parser
-  parse_idl_metas_from_blocks(&protocol_type, &namespace_blocks_map);
+  Ok(parse_schema_from_blocks(
+    protocol_type,
+    &namespace_blocks_map,
+  ))
+}
+
+fn run(
+  fs: &dyn FileSystem,
+  protocol_type: &IdlProtocolType,
+  target_type: &CodegenTargetType,
+  options: &CodegenOptions,
+  folder_path: &str,
+  files_filter: &FilesFilter,
+) -> Result<CodegenFilesMap, RidlError> {
+  let schema = parse(fs, protocol_type, folder_path, files_filter)?;
 
   // Codegen
+  let (files_map, warnings) = gen_code_from_schema(target_type, &schema, options);
+  for warning in warnings {
+    eprintln!("warning: {}", warning);
+  }
+  Ok(files_map)
+}
+
+/// Generate the code of the folder and write the files into the output folder.
+fn run_and_write(
+  fs: &dyn FileSystem,
+  protocol_type: &IdlProtocolType,
+  target_type: &CodegenTargetType,
+  options: &CodegenOptions,
+  folder_path: &str,
+  files_filter: &FilesFilter,
+  output_path: &str,
+) -> Result<(), RidlError> {
+  let files_map = run(
+    fs,
+    protocol_type,
+    target_type,
+    options,
+    folder_path,
+    files_filter,
+  )?;
+  for (file_path, code) in files_map {
+    write_file_str_content(fs, &format!("{}/{}", output_path, file_path), &code)?;
+  }
+  Ok(())
+}
+
+/// Print the error and exit with 1, e.g. when an idl file can not be read or the code can not be
+/// written.
+fn exit_on_error(result: Result<(), RidlError>) {
+  if let Err(err) = result {
+    eprintln!("{}", err);
+    process::exit(1);
+  }
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let command = parse_args(&args).unwrap_or_else(|err| {
+    eprintln!("{}\n\n{}", err, USAGE);
+    process::exit(2);
+  });
+  let fs = DiskFs;
+  match command {
+    Command::Demo => exit_on_error(run_and_write(
+      &fs,
+      &IdlProtocolType::Thrift,
+      &CodegenTargetType::Ts,
+      &CodegenOptions::default(),
+      "./playground/0-parser/demo",
+      &FilesFilter {
+        use_ignore_files: true,
+        ..FilesFilter::default()
+      },
+      "./playground/0-parser/output",
+    )),
+    Command::Gen(args) => exit_on_error(run_and_write(
+      &fs,
+      &args.protocol_type,
+      &args.target_type,
+      &args.options,
+      &args.folder_path,
+      &args.files_filter,
+      &args.output_path,
+    )),
+    Command::DumpSchema => println!("{}", DUMP_JSON_SCHEMA.trim_end()),
+    Command::Dump(args) => {
+      match parse(
+        &fs,
+        &args.protocol_type,
+        &args.folder_path,
+        &args.files_filter,
+      ) {
+        Ok(schema) => println!("{}", dump(&schema, &args.filter)),
+        Err(err) => {
+          eprintln!("{}", err);
+          process::exit(1);
+        }
+      }
+    }
+    Command::Mock(args) => {
+      let samples = parse(
+        &fs,
+        &args.protocol_type,
+        &args.folder_path,
+        &args.files_filter,
+      )
+      .map_err(|err| err.to_string())
+      .and_then(|schema| {
+        mock_samples(
+          &schema,
+          &args.type_name,
+          args.count,
+          &args.mock_options,
+          &args.options,
+        )
+      });
+      match samples {
+        // a sample is printed as it is, more are printed as an array
+        Ok(mut samples) if samples.len() == 1 => {
+          println!(
+            "{}",
+            serde_json::to_string_pretty(&samples.remove(0)).unwrap()
+          )
+        }
+        Ok(samples) => println!("{}", serde_json::to_string_pretty(&samples).unwrap()),
+        Err(err) => {
+          eprintln!("{}", err);
+          process::exit(1);
+        }
+      }
+    }
+  }
+}
+
+#[test]
+fn test_codegen_is_reproducible() {
+  let folder_path = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../playground/0-parser/demo"
+  );
+  let files_filter = FilesFilter::default();
+  let files_map = run(
+    &DiskFs,
+    &IdlProtocolType::Thrift,
+    &CodegenTargetType::Ts,
+    &CodegenOptions::default(),
+    folder_path,
+    &files_filter,
+  )
+  .unwrap();
+  assert_eq!(
+    files_map.keys().collect::<Vec<&String>>(),
+    ["base.ts", "ek.ecp.after_school.ts"]
+  );
+  // every run, with its own hash seeds and thread scheduling, generates exactly the same bytes.
+  for _ in 0..8 {
+    assert_eq!(
+      run(
+        &DiskFs,
+        &IdlProtocolType::Thrift,
+        &CodegenTargetType::Ts,
+        &CodegenOptions::default(),
+        folder_path,
+        &files_filter,
+      )
+      .unwrap(),
+      files_map
+    );
+  }
+}
+
+#[test]
+fn test_run_in_memory() {
+  use ridl_utils::memory_fs::MemoryFs;
+
+  let fs = MemoryFs::from_files(&[
+    (
+      "idl/base.thrift",
+      "namespace go base\n\nstruct Base {\n  1: string LogID\n}\n",
+    ),
+    (
+      "idl/course.thrift",
+      "include \"base.thrift\"\nnamespace go course\n\nenum Grade {\n  One = 1\n}\n\nstruct Course {\n  1: i64 id\n  2: optional list<Grade> grades\n  255: base.Base Base\n}\n",
+    ),
+  ]);
+  let files_map = run(
+    &fs,
+    &IdlProtocolType::Thrift,
+    &CodegenTargetType::Ts,
+    &CodegenOptions::default(),
+    "idl",
+    &FilesFilter::default(),
+  )
+  .unwrap();
+  for (file_path, code) in &files_map {
+    write_file_str_content(&fs, &format!("output/{}", file_path), code).unwrap();
+  }
+  assert_eq!(
+    fs.read_to_string(std::path::Path::new("output/course.ts"))
+      .unwrap(),
+    r#"// namespace course
+
+import type { Base } from "./base";
+
+export enum Grade {
+  One = 1,
+}
+
+export interface Course {
+  id: string;
+  grades?: Grade[];
+  Base: Base;
+}
+"#
+  );
+  assert!(fs.is_file(std::path::Path::new("output/base.ts")));
+}
+
+#[test]
+fn test_run_with_io_errors() {
+  use ridl_utils::memory_fs::MemoryFs;
+
+  let fs = MemoryFs::from_files(&[
+    (
+      "idl/course.thrift",
+      "namespace go course\n\nstruct Course {\n  1: i64 id\n}\n",
+    ),
+    // the file to generate is a folder
+    ("output/course.ts/README.md", ""),
+  ]);
+  let write = |folder_path: &str| {
+    run_and_write(
+      &fs,
+      &IdlProtocolType::Thrift,
+      &CodegenTargetType::Ts,
+      &CodegenOptions::default(),
+      folder_path,
+      &FilesFilter::default(),
+      "output",
+    )
+  };
+  assert!(matches!(write("idl"), Err(RidlError::Io(_))));
+  assert!(matches!(write("missing"), Err(RidlError::Io(_))));
 }
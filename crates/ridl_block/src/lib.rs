@@ -1,49 +1,753 @@
-use ridl_utils::fs::{get_all_files_path_of_folder, read_file_str_content};
-use ridl_utils::types::{IdlBlocksMap, IdlProtocolType, NamespaceIdlBlocksMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
 
+use regex::Regex;
+use ridl_utils::error::{DuplicateType, RidlError};
+use ridl_utils::fs::{
+  get_all_files_path_of_folder, read_file_str_content, FileSystem, FilesFilter,
+};
+use ridl_utils::pool::par_map;
+use ridl_utils::types::{IdlBlocksMap, IdlProtocolType, IdlType, NamespaceIdlBlocksMap};
+use serde_json::Value as Json;
+
+mod searcher_avro;
+mod searcher_capnp;
+mod searcher_flatbuffers;
+mod searcher_graphql;
+mod searcher_openapi;
+mod searcher_protobuf;
 mod searcher_thrift;
-use searcher_thrift::searcher as searcher_thrift;
+mod searcher_thrift_json;
+use searcher_avro::{includes_searcher as includes_searcher_avro, searcher as searcher_avro};
+use searcher_capnp::{includes_searcher as includes_searcher_capnp, searcher as searcher_capnp};
+use searcher_flatbuffers::{
+  includes_searcher as includes_searcher_flatbuffers, searcher as searcher_flatbuffers,
+};
+use searcher_graphql::{
+  includes_searcher as includes_searcher_graphql, searcher as searcher_graphql,
+};
+use searcher_openapi::{
+  includes_searcher as includes_searcher_openapi, searcher as searcher_openapi,
+};
+use searcher_protobuf::{
+  includes_searcher as includes_searcher_protobuf, searcher as searcher_protobuf,
+};
+use searcher_thrift::{includes_searcher as includes_searcher_thrift, searcher as searcher_thrift};
+use searcher_thrift_json::{
+  includes_searcher as includes_searcher_thrift_json, resolve_classes,
+  searcher as searcher_thrift_json,
+};
 
-type BlockSearcher = fn(source_str: &str) -> (String, IdlBlocksMap);
+/// The blocks of a file by their namespaces, a file of avro may declare types of other namespaces
+/// and the namespace of an openapi document is named after its file.
+type BlockSearcher = fn(file_path: &str, source_str: &str) -> Vec<(String, IdlBlocksMap)>;
+type IncludesSearcher = fn(source_str: &str) -> Vec<String>;
 
 pub fn iter_and_collect_all_idl_blocks(
+  fs: &dyn FileSystem,
   idl_protocol_type: &IdlProtocolType,
   folder_path: &str,
-) -> NamespaceIdlBlocksMap {
+  files_filter: &FilesFilter,
+) -> Result<NamespaceIdlBlocksMap, RidlError> {
   // 1. get path of all idl files
-  let idl_files_path = get_all_files_path_of_folder(folder_path, idl_protocol_type.as_str());
-  let idl_files_path = idl_files_path.lock().unwrap();
-  println!("all idl files path: {:?}", idl_files_path);
+  let idl_files_path: Vec<String> =
+    get_all_files_path_of_folder(fs, folder_path, idl_protocol_type.postfixes(), files_filter)?
+      .iter()
+      .map(|p| p.to_string_lossy().to_string())
+      .collect();
 
-  // 2. read all these files and find all idl blocks
-  // TODO: multi threads to speed up
-  let idl_files_content: Vec<String> = idl_files_path
-    .iter()
-    .map(|s| read_file_str_content(s.as_str()))
-    .collect();
   // select the corresponding block runner by idl type
-  let searcher: BlockSearcher = match idl_protocol_type {
-    IdlProtocolType::Thrift => searcher_thrift,
-    // TODO: other idl types.
+  let (searcher, includes_searcher): (BlockSearcher, IncludesSearcher) = match idl_protocol_type {
+    IdlProtocolType::Thrift => (|_, s| vec![searcher_thrift(s)], includes_searcher_thrift),
+    IdlProtocolType::Protobuf => (
+      |_, s| vec![searcher_protobuf(s)],
+      includes_searcher_protobuf,
+    ),
+    IdlProtocolType::Avro => (|_, s| searcher_avro(s), includes_searcher_avro),
+    IdlProtocolType::GraphQL => (|_, s| vec![searcher_graphql(s)], includes_searcher_graphql),
+    IdlProtocolType::OpenApi => (
+      |path, s| vec![searcher_openapi(path, s)],
+      includes_searcher_openapi,
+    ),
+    IdlProtocolType::ThriftJson => (
+      |_, s| vec![searcher_thrift_json(s)],
+      includes_searcher_thrift_json,
+    ),
+    IdlProtocolType::FlatBuffers => (
+      |_, s| vec![searcher_flatbuffers(s)],
+      includes_searcher_flatbuffers,
+    ),
+    IdlProtocolType::CapnProto => (|_, s| vec![searcher_capnp(s)], includes_searcher_capnp),
+  };
+
+  // 2. read all these files and the files included by them
+  let idl_files = read_files_with_includes(fs, folder_path, idl_files_path, includes_searcher)?;
+
+  // 3. find all idl blocks in parallel, the results keep the order of the files.
+  let files_blocks = par_map(idl_files.iter().collect(), |(file_path, source_str)| {
+    searcher(file_path.as_str(), source_str.as_str())
+      .into_iter()
+      .map(|(namespace, idl_blocks_map)| (file_path.clone(), namespace, idl_blocks_map))
+      .collect::<Vec<_>>()
+  });
+  let mut files_blocks: Vec<_> = files_blocks.into_iter().flatten().collect();
+
+  // 4. the imports of capnp are named by `using`, which refer to the namespaces of the files
+  if matches!(idl_protocol_type, IdlProtocolType::CapnProto) {
+    declare_imports_capnp(fs, folder_path, &mut files_blocks);
+  }
+  // the refs of thrift are named by the programs of the included files, not their namespaces
+  if matches!(idl_protocol_type, IdlProtocolType::Thrift) {
+    resolve_refs_thrift(fs, folder_path, &idl_files, &mut files_blocks);
+  }
+  if matches!(idl_protocol_type, IdlProtocolType::ThriftJson) {
+    resolve_refs_thrift_json(&idl_files, &mut files_blocks);
+  }
+
+  // 5. combine all blocks into namespace_map
+  merge_files_blocks(files_blocks)
+}
+
+fn canonicalize(fs: &dyn FileSystem, file_path: &str) -> PathBuf {
+  fs.canonicalize(Path::new(file_path))
+    .unwrap_or_else(|_| PathBuf::from(file_path))
+}
+
+/// Resolve a file included by `file_path` relatively to it, or else to the root folder.
+fn resolve_include(
+  fs: &dyn FileSystem,
+  folder_path: &str,
+  file_path: &str,
+  include: &str,
+) -> String {
+  let folder = Path::new(file_path).parent().unwrap_or(Path::new(""));
+  let path = folder.join(include);
+  let path = match fs.is_file(&path) {
+    true => path,
+    false => Path::new(folder_path).join(include),
   };
+  path.to_string_lossy().to_string()
+}
+
+/// Declare the namespace of the file imported by every `using Base = import "base.capnp";`, as the
+/// file declares it, e.g. `$Cxx.namespace("common");` is appended to the block of the import. The
+/// imports of the files not found are left undeclared.
+fn declare_imports_capnp(
+  fs: &dyn FileSystem,
+  folder_path: &str,
+  files_blocks: &mut [(String, String, IdlBlocksMap)],
+) {
+  let namespaces: HashMap<PathBuf, String> = files_blocks
+    .iter()
+    .map(|(file_path, namespace, _)| (canonicalize(fs, file_path), namespace.clone()))
+    .collect();
+  for (file_path, _, idl_blocks_map) in files_blocks.iter_mut() {
+    for block in idl_blocks_map
+      .get_mut(&IdlType::Alias)
+      .into_iter()
+      .flatten()
+    {
+      let Some(include) = includes_searcher_capnp(&block.lines[0]).pop() else {
+        continue;
+      };
+      let path = resolve_include(fs, folder_path, file_path, &include);
+      if let Some(namespace) = namespaces.get(&canonicalize(fs, &path)) {
+        block.lines.push(format!(
+          "$Cxx.namespace(\"{}\");",
+          namespace.replace('.', "::")
+        ));
+      }
+    }
+  }
+}
+
+/// Rewrite the refs to the types of the included files from the names of their programs to the
+/// namespaces the files declare, e.g. `base_types.Base` of `include "common/base_types.thrift"` is
+/// rewritten to `ek.common.Base` if `base_types.thrift` declares `namespace go ek.common`. The refs
+/// to the files not found or declaring no namespace are left as they are.
+fn resolve_refs_thrift(
+  fs: &dyn FileSystem,
+  folder_path: &str,
+  idl_files: &[(String, String)],
+  files_blocks: &mut [(String, String, IdlBlocksMap)],
+) {
+  let namespaces: HashMap<PathBuf, String> = files_blocks
+    .iter()
+    .map(|(file_path, namespace, _)| (canonicalize(fs, file_path), namespace.clone()))
+    .collect();
+  let programs_map: HashMap<&str, HashMap<String, &String>> = idl_files
+    .iter()
+    .map(|(file_path, source_str)| {
+      let programs = includes_searcher_thrift(source_str)
+        .into_iter()
+        .filter_map(|include| {
+          let path = resolve_include(fs, folder_path, file_path, &include);
+          let namespace = namespaces.get(&canonicalize(fs, &path))?;
+          let program = Path::new(&include).file_stem()?.to_string_lossy();
+          (!namespace.is_empty()).then(|| (program.to_string(), namespace))
+        })
+        .collect();
+      (file_path.as_str(), programs)
+    })
+    .collect();
+
+  // the dotted names not following another name or dot, e.g. `base.Base` but not `a.base.Base`
+  let re_ref = Regex::new(r"(^|[^\w.])(\w+)\.(\w+)").unwrap();
+  // the parentheses of annotations hold keys, e.g. `(api.get = "/x")`, instead of fields
+  let re_annotations = Regex::new(r"^\s*[\w.]+\s*[=,)]").unwrap();
+  for (file_path, _, idl_blocks_map) in files_blocks.iter_mut() {
+    let Some(programs) = programs_map.get(file_path.as_str()) else {
+      continue;
+    };
+    if programs.is_empty() {
+      continue;
+    }
+    for block in idl_blocks_map.values_mut().flatten() {
+      let mut in_comment = false;
+      for line in block.lines.iter_mut() {
+        // the refs are searched in the code only, but rewritten in the line at the same offsets
+        let code = blank_non_code_thrift(line, &mut in_comment, &re_annotations);
+        let mut resolved = String::new();
+        let mut last = 0;
+        for c in re_ref.captures_iter(&code) {
+          let (Some(program), Some(name)) = (c.get(2), c.get(3)) else {
+            continue;
+          };
+          if let Some(namespace) = programs.get(program.as_str()) {
+            resolved.push_str(&line[last..program.start()]);
+            resolved.push_str(&format!("{}.{}", namespace, name.as_str()));
+            last = name.end();
+          }
+        }
+        if last > 0 {
+          resolved.push_str(&line[last..]);
+          *line = resolved;
+        }
+      }
+    }
+  }
+}
+
+/// The end of the text quoted from `start`, i.e. after its closing quote.
+fn quoted_end(bytes: &[u8], start: usize) -> usize {
+  let mut i = start + 1;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' => i += 1,
+      b if b == bytes[start] => return i + 1,
+      _ => {}
+    }
+    i += 1;
+  }
+  bytes.len()
+}
+
+/// The end of the annotations or the default value from `start`, i.e. the closing parenthesis or
+/// the separator of the field, skipping the quoted text and the nested brackets.
+fn bracketed_end(bytes: &[u8], start: usize, closing: &[u8]) -> usize {
+  let mut depth = 0;
+  let mut i = start;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'"' | b'\'' => {
+        i = quoted_end(bytes, i);
+        continue;
+      }
+      b if depth == 0 && closing.contains(&b) => return i,
+      b'(' | b'[' | b'{' => depth += 1,
+      b')' | b']' | b'}' => depth -= 1,
+      _ => {}
+    }
+    i += 1;
+  }
+  bytes.len()
+}
+
+/// The line of thrift with its comments, quoted text, annotations and default values blanked, so
+/// that the dotted names left are the types, e.g. of the fields, the typedefs, the methods and the
+/// services they extend. `in_comment` tells whether the line starts in a block comment, and is
+/// updated for the next line.
+fn blank_non_code_thrift(line: &str, in_comment: &mut bool, re_annotations: &Regex) -> String {
+  let bytes = line.as_bytes();
+  let mut code = bytes.to_vec();
+  let mut i = 0;
+  while i < bytes.len() {
+    let rest = &bytes[i..];
+    let end = if *in_comment || rest.starts_with(b"/*") {
+      let from = if *in_comment { i } else { i + 2 };
+      *in_comment = true;
+      match bytes[from..].windows(2).position(|w| w == b"*/") {
+        Some(e) => {
+          *in_comment = false;
+          from + e + 2
+        }
+        None => bytes.len(),
+      }
+    } else if rest.starts_with(b"//") || rest.starts_with(b"#") {
+      bytes.len()
+    } else if rest.starts_with(b"\"") || rest.starts_with(b"'") {
+      quoted_end(bytes, i)
+    } else if rest.starts_with(b"(") && re_annotations.is_match(&line[i + 1..]) {
+      (bracketed_end(bytes, i + 1, b")") + 1).min(bytes.len())
+    } else if rest.starts_with(b"=") {
+      bracketed_end(bytes, i + 1, b",;)}")
+    } else {
+      i += 1;
+      continue;
+    };
+    code[i..end].fill(b' ');
+    i = end;
+  }
+  // the blanked spans start and end at ascii bytes, so the code is still utf-8
+  String::from_utf8(code).unwrap_or_default()
+}
+
+/// Rewrite the classes of the json generated by `thrift --gen json` from the programs of the
+/// included files to the namespaces they declare, e.g. `base.Base` is rewritten to
+/// `ek.common.Base` if the json of the program `base` declares `namespace go ek.common`. The
+/// programs are the `name`s of the jsons, which are listed by the `includes` of the ones including
+/// them.
+fn resolve_refs_thrift_json(
+  idl_files: &[(String, String)],
+  files_blocks: &mut [(String, String, IdlBlocksMap)],
+) {
+  let programs: HashMap<&str, Json> = idl_files
+    .iter()
+    .filter_map(|(file_path, source_str)| {
+      Some((file_path.as_str(), serde_json::from_str(source_str).ok()?))
+    })
+    .collect();
+  let namespaces: HashMap<&str, &str> = programs
+    .values()
+    .filter_map(|p| Some((p["name"].as_str()?, p["namespaces"]["go"].as_str()?)))
+    .filter(|(_, namespace)| !namespace.is_empty())
+    .collect();
+
+  for (file_path, _, idl_blocks_map) in files_blocks.iter_mut() {
+    let Some(program) = programs.get(file_path.as_str()) else {
+      continue;
+    };
+    let included: HashMap<&str, &str> = program["includes"]
+      .as_array()
+      .into_iter()
+      .flatten()
+      .filter_map(|include| {
+        let include = include.as_str()?;
+        Some((include, *namespaces.get(include)?))
+      })
+      .collect();
+    if included.is_empty() {
+      continue;
+    }
+    for line in idl_blocks_map
+      .values_mut()
+      .flatten()
+      .flat_map(|b| b.lines.iter_mut())
+    {
+      let Ok(mut definition) = serde_json::from_str::<Json>(line) else {
+        continue;
+      };
+      resolve_classes(&mut definition, &|class| {
+        let (program, name) = class.split_once('.')?;
+        Some(format!("{}.{}", included.get(program)?, name))
+      });
+      *line = definition.to_string();
+    }
+  }
+}
+
+/// Read the files in parallel, then the files included by them that are not read yet, until all the
+/// included files are read. An included file is resolved relatively to the file including it, or
+/// else to the root folder, and it is read even if it is outside the folder or skipped by the files
+/// filter, since the types it declares are referred. Returns the (path, content) of the files, the
+/// files of `files_path` come first and then the included files in the order they are found. Fails
+/// if any of the files can not be read.
+fn read_files_with_includes(
+  fs: &dyn FileSystem,
+  folder_path: &str,
+  files_path: Vec<String>,
+  includes_searcher: IncludesSearcher,
+) -> io::Result<Vec<(String, String)>> {
+  let mut read_files: HashSet<PathBuf> = files_path.iter().map(|p| canonicalize(fs, p)).collect();
+
+  let mut files = Vec::new();
+  let mut unread_files_path = files_path;
+  while !unread_files_path.is_empty() {
+    let read = par_map(unread_files_path, |file_path| -> io::Result<_> {
+      let source_str = read_file_str_content(fs, file_path.as_str())?;
+      let includes: Vec<String> = includes_searcher(source_str.as_str())
+        .iter()
+        .map(|include| resolve_include(fs, folder_path, &file_path, include))
+        .collect();
+      Ok((file_path, source_str, includes))
+    });
+
+    unread_files_path = Vec::new();
+    for read_file in read {
+      let (file_path, source_str, includes) = read_file?;
+      for include in includes {
+        if !fs.is_file(Path::new(&include)) {
+          eprintln!("included file {} of {} is not found", include, file_path);
+        } else if read_files.insert(canonicalize(fs, &include)) {
+          unread_files_path.push(include);
+        }
+      }
+      files.push((file_path, source_str));
+    }
+  }
+  Ok(files)
+}
 
-  // // 3. combine all blocks into namespace_map
+/// Merge the blocks of all files into their namespaces, the blocks of the same namespace are
+/// concatenated and ordered in the order of the files. Types declared more than once in a namespace
+/// are reported as an error instead of being merged.
+fn merge_files_blocks(
+  files_blocks: Vec<(String, String, IdlBlocksMap)>,
+) -> Result<NamespaceIdlBlocksMap, RidlError> {
   let mut namespace_map = NamespaceIdlBlocksMap::new();
-  for source_str in idl_files_content {
-    let (namespace, idl_blocks_map) = searcher(source_str.as_str());
-    let ori_map = IdlBlocksMap::new();
-    let ori_map = namespace_map.get(namespace.as_str()).unwrap_or(&ori_map);
-
-    let mut merged_map = IdlBlocksMap::new();
-    let keys = idl_blocks_map.keys();
-    for k in keys {
-      // merge two vectors
-      let v1 = ori_map.get(k).unwrap_or(&[].to_vec()).clone();
-      let v2 = idl_blocks_map.get(k).unwrap_or(&[].to_vec()).clone();
-      let v_merged = merged_map.entry(k.clone()).or_insert(v1);
-      v_merged.extend(v2);
-    }
-    namespace_map.insert(namespace, merged_map);
-  }
-  namespace_map
+  // the files declaring each type: (namespace, type name) -> files path
+  let mut declared_map: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+  // the order the blocks of the next file of each namespace start from
+  let mut orders: HashMap<String, usize> = HashMap::new();
+  for (file_path, namespace, mut idl_blocks_map) in files_blocks {
+    let start = orders.entry(namespace.clone()).or_default();
+    let mut end = *start;
+    for block in idl_blocks_map.values_mut().flatten() {
+      block.order += *start;
+      end = end.max(block.order + 1);
+    }
+    *start = end;
+
+    for blocks in idl_blocks_map.values() {
+      // the extensions of graphql, e.g. `extend type X`, are named after the types they extend, and
+      // the imports of capnp, e.g. `using Base = import "base.capnp";`, are named in every file
+      for block in blocks.iter().filter(|b| {
+        !b.lines
+          .first()
+          .is_some_and(|l| l.starts_with("extend ") || l.starts_with("using "))
+      }) {
+        declared_map
+          .entry((namespace.clone(), block.name.clone()))
+          .or_default()
+          .push(file_path.clone());
+      }
+    }
+
+    let merged_map = namespace_map.entry(namespace).or_default();
+    for (k, blocks) in idl_blocks_map {
+      merged_map.entry(k).or_default().extend(blocks);
+    }
+  }
+
+  let duplicates: Vec<DuplicateType> = declared_map
+    .into_iter()
+    .filter(|(_, files_path)| files_path.len() > 1)
+    .map(|((namespace, name), files_path)| DuplicateType {
+      namespace,
+      name,
+      files_path,
+    })
+    .collect();
+  if !duplicates.is_empty() {
+    return Err(RidlError::DuplicateTypes(duplicates));
+  }
+  Ok(namespace_map)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{iter_and_collect_all_idl_blocks, merge_files_blocks, Json};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    error::{DuplicateType, RidlError},
+    fs::FilesFilter,
+    memory_fs::MemoryFs,
+    types::{blocks_in_order, IdlBlocksMap, IdlProtocolType, IdlType},
+  };
+
+  fn blocks_map(interfaces: &[&str], enums: &[&str]) -> IdlBlocksMap {
+    let to_blocks = |names: &[&str]| -> Vec<CodeBlock> {
+      names
+        .iter()
+        .map(|name| CodeBlock {
+          name: name.to_string(),
+          lines: Vec::new(),
+          order: 0,
+        })
+        .collect()
+    };
+    let mut map = IdlBlocksMap::new();
+    map.insert(IdlType::Interface, to_blocks(interfaces));
+    map.insert(IdlType::Enum, to_blocks(enums));
+    map
+  }
+
+  fn names(map: &IdlBlocksMap, idl_type: IdlType) -> Vec<String> {
+    map[&idl_type].iter().map(|b| b.name.clone()).collect()
+  }
+
+  #[test]
+  fn test_merge_same_namespace() {
+    let mut second = blocks_map(&["S3"], &[]);
+    // the second file has no enum at all
+    second.remove(&IdlType::Enum);
+    let namespace_map = merge_files_blocks(
+      [
+        ("a.thrift", "foo", blocks_map(&["S1", "S2"], &["E1"])),
+        ("b.thrift", "foo", second),
+        ("c.thrift", "bar", blocks_map(&["S1"], &[])),
+      ]
+      .map(|(f, n, m)| (f.to_string(), n.to_string(), m))
+      .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(namespace_map.len(), 2);
+    let foo = &namespace_map["foo"];
+    assert_eq!(names(foo, IdlType::Interface), ["S1", "S2", "S3"]);
+    assert_eq!(names(foo, IdlType::Enum), ["E1"]);
+    assert_eq!(names(&namespace_map["bar"], IdlType::Interface), ["S1"]);
+    // the blocks of the later file follow the ones of the earlier
+    let ordered: Vec<&str> = blocks_in_order(foo)
+      .iter()
+      .map(|(_, b)| b.name.as_str())
+      .collect();
+    assert_eq!(ordered, ["S1", "S2", "E1", "S3"]);
+  }
+
+  #[test]
+  fn test_merge_duplicate_types() {
+    let res = merge_files_blocks(
+      [
+        ("a.thrift", "foo", blocks_map(&["S1"], &["E1"])),
+        ("b.thrift", "foo", blocks_map(&["S2"], &["S1"])),
+        ("c.thrift", "foo", blocks_map(&["S1"], &[])),
+      ]
+      .map(|(f, n, m)| (f.to_string(), n.to_string(), m))
+      .to_vec(),
+    );
+    assert_eq!(
+      res.unwrap_err(),
+      RidlError::DuplicateTypes(
+        [DuplicateType {
+          namespace: "foo".to_string(),
+          name: "S1".to_string(),
+          files_path: ["a.thrift", "b.thrift", "c.thrift"]
+            .map(|s| s.to_string())
+            .to_vec(),
+        }]
+        .to_vec()
+      )
+    );
+  }
+
+  #[test]
+  fn test_collect_with_includes() {
+    let fs = MemoryFs::from_files(&[
+      (
+        "idl/service/a.thrift",
+        "include \"../common/base.thrift\"\ninclude \"missing.thrift\"\nnamespace go foo\n\nstruct A {\n  1: base.Base base\n}\n",
+      ),
+      (
+        "idl/common/base.thrift",
+        "include \"../service/a.thrift\"\nnamespace go base\n\nstruct Base {\n  1: string id\n}\n",
+      ),
+    ]);
+    let namespace_map = iter_and_collect_all_idl_blocks(
+      &fs,
+      &IdlProtocolType::Thrift,
+      "idl/service",
+      &FilesFilter::default(),
+    )
+    .unwrap();
+    assert_eq!(
+      namespace_map.keys().collect::<Vec<&String>>(),
+      ["base", "foo"]
+    );
+    assert_eq!(names(&namespace_map["base"], IdlType::Interface), ["Base"]);
+    assert_eq!(names(&namespace_map["foo"], IdlType::Interface), ["A"]);
+  }
+
+  #[test]
+  fn test_collect_thrift_refs() {
+    let fs = MemoryFs::from_files(&[
+      (
+        "idl/course.thrift",
+        "include \"common/base_types.thrift\"\nnamespace go ek.course\n\nstruct Course {\n  1: base_types.Base base\n  2: map<string, list<base_types.Base>> bases\n  3: other.base_types.Base other\n}\n\nservice CourseService extends base_types.BaseService {\n  base_types.Base Get(1: base_types.Id id = base_types.ZERO) throws (1: base_types.Error err) (base_types.get = \"/base_types.x\", api.get = \"/x\") // base_types.Base\n  void Ping(1: base_types.Id id /* base_types.Base */)\n}\n\ntypedef base_types.Base Base\n",
+      ),
+      (
+        "idl/common/base_types.thrift",
+        "namespace go ek.common\n\nstruct Base {\n  1: string id\n}\n",
+      ),
+    ]);
+    let namespace_map = iter_and_collect_all_idl_blocks(
+      &fs,
+      &IdlProtocolType::Thrift,
+      "idl",
+      &FilesFilter::default(),
+    )
+    .unwrap();
+    // the refs name the namespace of the included file instead of its program
+    assert_eq!(
+      namespace_map["ek.course"][&IdlType::Interface][0].lines[1..4],
+      [
+        "  1: ek.common.Base base",
+        "  2: map<string, list<ek.common.Base>> bases",
+        "  3: other.base_types.Base other",
+      ]
+    );
+    // the comments, the quoted text, the annotations and the default values are left as they are
+    assert_eq!(
+      namespace_map["ek.course"][&IdlType::Service][0].lines[..],
+      [
+        "service CourseService extends ek.common.BaseService {",
+        "  ek.common.Base Get(1: ek.common.Id id = base_types.ZERO) throws (1: ek.common.Error err) (base_types.get = \"/base_types.x\", api.get = \"/x\") // base_types.Base",
+        "  void Ping(1: ek.common.Id id /* base_types.Base */)",
+        "}",
+      ]
+    );
+    assert_eq!(
+      namespace_map["ek.course"][&IdlType::Alias][0].lines,
+      ["typedef ek.common.Base Base"]
+    );
+  }
+
+  #[test]
+  fn test_collect_thrift_json_refs() {
+    // the programs are named after the files, which differ from their namespaces
+    let fs = MemoryFs::from_files(&[
+      (
+        "json/course.json",
+        r#"{"name": "course", "namespaces": {"go": "ek.course"}, "includes": ["base"], "structs": [
+  {"name": "Error", "fields": []},
+  {"name": "Course", "fields": [
+    {"key": 1, "name": "base", "typeId": "struct", "type": {"typeId": "struct", "class": "base.Error"}},
+    {"key": 2, "name": "error", "typeId": "struct", "type": {"typeId": "struct", "class": "course.Error"}}
+  ]}
+]}"#,
+      ),
+      (
+        "json/base.json",
+        r#"{"name": "base", "namespaces": {"go": "ek.common"}, "includes": [], "structs": [{"name": "Error", "fields": []}]}"#,
+      ),
+    ]);
+    let namespace_map = iter_and_collect_all_idl_blocks(
+      &fs,
+      &IdlProtocolType::ThriftJson,
+      "json",
+      &FilesFilter::default(),
+    )
+    .unwrap();
+    assert_eq!(
+      namespace_map.keys().collect::<Vec<&String>>(),
+      ["ek.common", "ek.course"]
+    );
+    let course: Json =
+      serde_json::from_str(&namespace_map["ek.course"][&IdlType::Interface][1].lines[0]).unwrap();
+    let classes: Vec<&str> = course["fields"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|f| f["type"]["class"].as_str().unwrap())
+      .collect();
+    // `base.Error` is not the local `Error`, which is bare
+    assert_eq!(classes, ["ek.common.Error", "Error"]);
+  }
+
+  #[test]
+  fn test_collect_includes_outside_filter() {
+    let fs = MemoryFs::from_files(&[
+      (
+        "proto/common/base.proto",
+        "syntax = \"proto3\";\npackage base;\n\nmessage Base {\n  string log_id = 1;\n}\n",
+      ),
+      (
+        "proto/course/course.proto",
+        "syntax = \"proto3\";\npackage course;\n\nimport \"common/base.proto\";\nimport \"google/protobuf/timestamp.proto\";\n\nmessage Course {\n  base.Base base = 1;\n}\n",
+      ),
+    ]);
+    // the imported `base.proto` is resolved from the root folder and read though it is not included
+    let namespace_map = iter_and_collect_all_idl_blocks(
+      &fs,
+      &IdlProtocolType::Protobuf,
+      "proto",
+      &FilesFilter {
+        include: vec!["course/**".to_string()],
+        ..FilesFilter::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      namespace_map.keys().collect::<Vec<&String>>(),
+      ["base", "course"]
+    );
+    assert_eq!(names(&namespace_map["base"], IdlType::Interface), ["Base"]);
+  }
+
+  #[test]
+  fn test_collect_capnp_imports() {
+    let fs = MemoryFs::from_files(&[
+      (
+        "capnp/person.capnp",
+        "@0xdbb9ad1f14bf0b36;\nusing Cxx = import \"/capnp/c++.capnp\";\n$Cxx.namespace(\"ek::person\");\nusing Base = import \"common/base.capnp\";\nusing Missing = import \"missing.capnp\";\n\nstruct Person {\n  id @0 :Base.Id;\n}\n",
+      ),
+      (
+        "capnp/common/base.capnp",
+        "@0x8e0594c8abeb307c;\nusing Cxx = import \"/capnp/c++.capnp\";\n$Cxx.namespace(\"ek::common\");\n\nstruct Id {\n  value @0 :UInt64;\n}\n",
+      ),
+    ]);
+    let namespace_map = iter_and_collect_all_idl_blocks(
+      &fs,
+      &IdlProtocolType::CapnProto,
+      "capnp",
+      &FilesFilter::default(),
+    )
+    .unwrap();
+    let imports: Vec<Vec<String>> = namespace_map["ek.person"][&IdlType::Alias]
+      .iter()
+      .map(|b| b.lines.clone())
+      .collect();
+    assert_eq!(
+      imports,
+      [
+        vec![
+          r#"using Base = import "common/base.capnp";"#.to_string(),
+          r#"$Cxx.namespace("ek::common");"#.to_string(),
+        ],
+        vec![r#"using Missing = import "missing.capnp";"#.to_string()],
+      ]
+    );
+  }
+
+  #[test]
+  fn test_collect_openapi_documents() {
+    let error =
+      "    Error:\n      type: object\n      properties:\n        code: { type: integer }\n";
+    let fs = MemoryFs::from_files(&[
+      (
+        "api/a.yaml",
+        format!(
+          "openapi: 3.0.3\ncomponents:\n  schemas:\n{}        cause:\n          $ref: 'b.yaml#/components/schemas/Error'\n",
+          error
+        )
+        .as_str(),
+      ),
+      (
+        "api/b.yaml",
+        format!("openapi: 3.0.3\ncomponents:\n  schemas:\n{}", error).as_str(),
+      ),
+    ]);
+    // the schemas of the same name in different documents do not collide
+    let namespace_map = iter_and_collect_all_idl_blocks(
+      &fs,
+      &IdlProtocolType::OpenApi,
+      "api",
+      &FilesFilter::default(),
+    )
+    .unwrap();
+    assert_eq!(namespace_map.keys().collect::<Vec<&String>>(), ["a", "b"]);
+    assert_eq!(names(&namespace_map["a"], IdlType::Interface), ["Error"]);
+    assert!(namespace_map["a"][&IdlType::Interface][0].lines[0].contains(r#"{"$ref":"b.Error"}"#));
+    assert_eq!(names(&namespace_map["b"], IdlType::Interface), ["Error"]);
+  }
 }
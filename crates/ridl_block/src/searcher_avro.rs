@@ -0,0 +1,505 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use ridl_utils::code_block::CodeBlock;
+use ridl_utils::lexer::{tokenize, Token, Tokens};
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+/// The path of the imported files of avro idl, e.g. `base.avdl` of `import idl "base.avdl";`.
+pub fn includes_searcher(source_str: &str) -> Vec<String> {
+  let re = Regex::new(r#"(?m)^\s*import\s+(?:idl|protocol|schema)\s+"([^"]+)"\s*;"#).unwrap();
+  re.captures_iter(source_str)
+    .map(|c| c.get(1).unwrap().as_str().to_string())
+    .collect()
+}
+
+/// Search the named types of an avro schema (`.avsc`), protocol (`.avpr`) or idl (`.avdl`), the
+/// idl is translated into the json protocol first. Every record and enum becomes a block of a
+/// single line, which is the json of its schema:
+/// - the named types defined inside it are replaced by their names, they are blocks as well
+/// - the references to `fixed` types are replaced by their definitions, as they are not blocks
+///
+/// The types are searched into their own namespaces, e.g. `@namespace("other") record Base` is a
+/// block of `other`, and the namespace of the file comes first.
+pub fn searcher(source_str: &str) -> Vec<(String, IdlBlocksMap)> {
+  let new_blocks_map = || {
+    let mut cbs_map = IdlBlocksMap::new();
+    cbs_map.insert(IdlType::Interface, Vec::new());
+    cbs_map.insert(IdlType::Enum, Vec::new());
+    cbs_map
+  };
+
+  let trimmed = source_str.trim_start();
+  let schema = if trimmed.starts_with('{') || trimmed.starts_with('[') {
+    match serde_json::from_str::<Value>(source_str) {
+      Ok(schema) => schema,
+      Err(err) => {
+        eprintln!("parsing avro schema with error: {}", err);
+        return vec![(String::new(), new_blocks_map())];
+      }
+    }
+  } else {
+    idl_to_protocol(source_str)
+  };
+
+  let namespace = schema
+    .get("namespace")
+    .and_then(|n| n.as_str())
+    .unwrap_or("")
+    .to_string();
+  let types = match schema.get("protocol") {
+    Some(_) => schema.get("types").cloned().unwrap_or(json!([])),
+    None => schema,
+  };
+
+  let mut named = Vec::new();
+  collect_named_types(types, &namespace, &mut named);
+
+  let fixed: HashMap<String, Value> = named
+    .iter()
+    .filter(|(_, _, t)| t["type"] == "fixed")
+    .flat_map(|(name, ns, t)| {
+      [
+        (name.clone(), t.clone()),
+        (format!("{}.{}", ns, name), t.clone()),
+      ]
+    })
+    .collect();
+  let mut namespaces = vec![(namespace, new_blocks_map())];
+  for (order, (name, ns, mut t)) in named.into_iter().enumerate() {
+    let idl_type = match t["type"].as_str() {
+      Some("record") | Some("error") => IdlType::Interface,
+      Some("enum") => IdlType::Enum,
+      _ => continue,
+    };
+    if let Some(fields) = t.get_mut("fields").and_then(|f| f.as_array_mut()) {
+      for field in fields {
+        if let Some(field_type) = field.get_mut("type") {
+          inline_fixed_types(field_type, &fixed);
+        }
+      }
+    }
+    let idx = match namespaces.iter().position(|(n, _)| *n == ns) {
+      Some(idx) => idx,
+      None => {
+        namespaces.push((ns, new_blocks_map()));
+        namespaces.len() - 1
+      }
+    };
+    namespaces[idx]
+      .1
+      .get_mut(&idl_type)
+      .unwrap()
+      .push(CodeBlock {
+        name,
+        lines: vec![t.to_string()],
+        order,
+      });
+  }
+  namespaces
+}
+
+/// Collect the (name, namespace, schema) of the named types in the order they are defined, the
+/// definitions nested inside a record are replaced by the names.
+fn collect_named_types(schema: Value, namespace: &str, named: &mut Vec<(String, String, Value)>) {
+  match schema {
+    Value::Array(union) => {
+      for s in union {
+        collect_named_types(s, namespace, named);
+      }
+    }
+    Value::Object(_) => {
+      replace_named_type(schema, namespace, named);
+    }
+    _ => {}
+  }
+}
+
+/// Returns the schema itself if it is not a named type, otherwise collects it and returns its name.
+fn replace_named_type(
+  mut schema: Value,
+  namespace: &str,
+  named: &mut Vec<(String, String, Value)>,
+) -> Value {
+  match schema {
+    Value::Array(union) => Value::Array(
+      union
+        .into_iter()
+        .map(|s| replace_named_type(s, namespace, named))
+        .collect(),
+    ),
+    Value::Object(_) => {
+      let r#type = schema["type"].as_str().unwrap_or("").to_string();
+      match r#type.as_str() {
+        "record" | "error" | "enum" | "fixed" => {
+          let full_name = schema["name"].as_str().unwrap_or("").to_string();
+          let (ns, name) = match full_name.rsplit_once('.') {
+            Some((ns, name)) => (ns.to_string(), name.to_string()),
+            None => (
+              schema["namespace"]
+                .as_str()
+                .unwrap_or(namespace)
+                .to_string(),
+              full_name,
+            ),
+          };
+          // keep the position of the type before the nested ones
+          let idx = named.len();
+          named.push((name.clone(), ns.clone(), Value::Null));
+          if let Some(fields) = schema.get_mut("fields").and_then(|f| f.as_array_mut()) {
+            for field in fields {
+              if let Some(field_type) = field.get_mut("type") {
+                *field_type = replace_named_type(field_type.take(), &ns, named);
+              }
+            }
+          }
+          named[idx].2 = schema;
+          if ns == namespace || ns.is_empty() {
+            Value::String(name)
+          } else {
+            Value::String(format!("{}.{}", ns, name))
+          }
+        }
+        "array" => {
+          let items = schema["items"].take();
+          schema["items"] = replace_named_type(items, namespace, named);
+          schema
+        }
+        "map" => {
+          let values = schema["values"].take();
+          schema["values"] = replace_named_type(values, namespace, named);
+          schema
+        }
+        _ => schema,
+      }
+    }
+    _ => schema,
+  }
+}
+
+fn inline_fixed_types(schema: &mut Value, fixed: &HashMap<String, Value>) {
+  match schema {
+    Value::String(name) => {
+      if let Some(f) = fixed.get(name.as_str()) {
+        *schema = f.clone();
+      }
+    }
+    Value::Array(union) => union.iter_mut().for_each(|s| inline_fixed_types(s, fixed)),
+    Value::Object(o) => {
+      for key in ["items", "values"] {
+        if let Some(s) = o.get_mut(key) {
+          inline_fixed_types(s, fixed);
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Translate the avro idl into the json protocol, only the parts describing types are kept.
+fn idl_to_protocol(source_str: &str) -> Value {
+  let mut tokens = Tokens::new(tokenize(source_str, &["//"]));
+  let mut protocol = Map::new();
+  protocol.insert("protocol".to_string(), json!(""));
+  let mut types = Vec::new();
+  let mut annotations = parse_annotations(&mut tokens);
+  while !tokens.is_end() {
+    if tokens.eat_ident("protocol") {
+      protocol.insert("protocol".to_string(), json!(tokens.ident()));
+      if let Some(ns) = annotations.remove("namespace") {
+        protocol.insert("namespace".to_string(), ns);
+      }
+      tokens.eat_symbol('{');
+    } else if tokens.eat_ident("namespace") {
+      // the schema syntax: `namespace foo.bar;`
+      protocol.insert("namespace".to_string(), json!(tokens.dotted_ident()));
+      tokens.eat_symbol(';');
+    } else if tokens.peek().is_some_and(|t| {
+      ["record", "error", "enum", "fixed"]
+        .iter()
+        .any(|k| t.is_ident(k))
+    }) && matches!(tokens.peek_nth(1), Some(Token::Ident(_)))
+    {
+      types.push(parse_named_type(&mut tokens, annotations));
+    } else if tokens.eat_symbol('}') {
+    } else {
+      // imports, messages and other statements
+      tokens.skip_statement();
+    }
+    annotations = parse_annotations(&mut tokens);
+  }
+  protocol.insert("types".to_string(), Value::Array(types));
+  Value::Object(protocol)
+}
+
+/// Parse the annotations like `@namespace("foo")` or `@java-class("java.util.List")`.
+fn parse_annotations(tokens: &mut Tokens) -> Map<String, Value> {
+  let mut annotations = Map::new();
+  while tokens.eat_symbol('@') {
+    let mut name = tokens.ident().unwrap_or_default();
+    while tokens.peek().is_some_and(|t| t.is_symbol('-'))
+      && matches!(tokens.peek_nth(1), Some(Token::Ident(_)))
+    {
+      tokens.next_token();
+      name = format!("{}-{}", name, tokens.ident().unwrap());
+    }
+    if !tokens.eat_symbol('(') {
+      continue;
+    }
+    match tokens.peek() {
+      Some(Token::Str(s)) if tokens.peek_nth(1).is_some_and(|t| t.is_symbol(')')) => {
+        annotations.insert(name, json!(s));
+        tokens.next_token();
+        tokens.next_token();
+      }
+      _ => tokens.skip_until(')'),
+    }
+  }
+  annotations
+}
+
+fn parse_named_type(tokens: &mut Tokens, annotations: Map<String, Value>) -> Value {
+  let kind = tokens.ident().unwrap();
+  let name = tokens.ident().unwrap();
+  let mut schema = Map::new();
+  schema.insert("type".to_string(), json!(kind));
+  schema.insert("name".to_string(), json!(name));
+  if let Some(ns) = annotations.get("namespace") {
+    schema.insert("namespace".to_string(), ns.clone());
+  }
+  match kind.as_str() {
+    "fixed" => {
+      tokens.eat_symbol('(');
+      if let Some(Token::Number(size)) = tokens.next_token() {
+        schema.insert("size".to_string(), json!(size.parse::<u64>().unwrap_or(0)));
+      }
+      tokens.skip_statement();
+    }
+    "enum" => {
+      tokens.eat_symbol('{');
+      let mut symbols = Vec::new();
+      while let Some(token) = tokens.next_token() {
+        match token {
+          Token::Ident(symbol) => symbols.push(json!(symbol)),
+          Token::Symbol('}') => break,
+          _ => {}
+        }
+      }
+      schema.insert("symbols".to_string(), Value::Array(symbols));
+      // the default symbol: `= FOO;`
+      if tokens.eat_symbol('=') {
+        tokens.skip_statement();
+      }
+    }
+    _ => {
+      tokens.eat_symbol('{');
+      let mut fields = Vec::new();
+      while !tokens.is_end() && !tokens.eat_symbol('}') {
+        let annotations = parse_annotations(tokens);
+        let Some(field_type) = parse_type(tokens, annotations) else {
+          tokens.skip_statement();
+          continue;
+        };
+        // a type may be followed by many variables: `string a, b = "b";`
+        loop {
+          parse_annotations(tokens);
+          let Some(field_name) = tokens.ident() else {
+            tokens.skip_statement();
+            break;
+          };
+          fields.push(json!({ "name": field_name, "type": field_type }));
+          skip_default_value(tokens);
+          if !tokens.eat_symbol(',') {
+            tokens.eat_symbol(';');
+            break;
+          }
+        }
+      }
+      schema.insert("fields".to_string(), Value::Array(fields));
+    }
+  }
+  Value::Object(schema)
+}
+
+fn skip_default_value(tokens: &mut Tokens) {
+  if !tokens.eat_symbol('=') {
+    return;
+  }
+  while let Some(token) = tokens.peek() {
+    match token {
+      Token::Symbol(',') | Token::Symbol(';') => return,
+      Token::Symbol('{') | Token::Symbol('[') => {
+        let close = if token.is_symbol('{') { '}' } else { ']' };
+        tokens.next_token();
+        tokens.skip_until(close);
+      }
+      _ => {
+        tokens.next_token();
+      }
+    }
+  }
+}
+
+/// Parse a type of avro idl into the json schema.
+fn parse_type(tokens: &mut Tokens, annotations: Map<String, Value>) -> Option<Value> {
+  let name = tokens.dotted_ident()?;
+  let mut schema = match name.as_str() {
+    "array" | "map" => {
+      tokens.eat_symbol('<');
+      let inner = parse_type(tokens, Map::new())?;
+      tokens.eat_symbol('>');
+      let key = if name == "array" { "items" } else { "values" };
+      json!({ "type": name, key: inner })
+    }
+    "union" => {
+      tokens.eat_symbol('{');
+      let mut union = Vec::new();
+      while !tokens.is_end() && !tokens.eat_symbol('}') {
+        if !tokens.eat_symbol(',') {
+          union.push(parse_type(tokens, Map::new())?);
+        }
+      }
+      Value::Array(union)
+    }
+    "decimal" => {
+      tokens.eat_symbol('(');
+      tokens.skip_until(')');
+      json!({ "type": "bytes", "logicalType": "decimal" })
+    }
+    "date" | "time_ms" | "timestamp_ms" | "local_timestamp_ms" | "uuid" => {
+      let (r#type, logical_type) = match name.as_str() {
+        "date" => ("int", "date"),
+        "time_ms" => ("int", "time-millis"),
+        "timestamp_ms" => ("long", "timestamp-millis"),
+        "local_timestamp_ms" => ("long", "local-timestamp-millis"),
+        _ => ("string", "uuid"),
+      };
+      json!({ "type": r#type, "logicalType": logical_type })
+    }
+    _ => match annotations.get("logicalType") {
+      Some(logical_type) => json!({ "type": name, "logicalType": logical_type }),
+      None => json!(name),
+    },
+  };
+  // the nullable shorthand: `string? name;`
+  if tokens.eat_symbol('?') {
+    schema = json!(["null", schema]);
+  }
+  Some(schema)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{includes_searcher, searcher};
+  use ridl_utils::types::IdlType;
+  use serde_json::{json, Value};
+
+  type NamedSchemas = Vec<(String, Value)>;
+
+  /// The (namespace, records, enums) of the namespaces searched.
+  fn blocks(source: &str) -> Vec<(String, NamedSchemas, NamedSchemas)> {
+    searcher(source)
+      .into_iter()
+      .map(|(namespace, cbs_map)| {
+        let to_json = |idl_type: IdlType| {
+          cbs_map[&idl_type]
+            .iter()
+            .map(|b| (b.name.clone(), serde_json::from_str(&b.lines[0]).unwrap()))
+            .collect()
+        };
+        (
+          namespace,
+          to_json(IdlType::Interface),
+          to_json(IdlType::Enum),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_searcher_schema() {
+    let mut namespaces = blocks(
+      r#"{
+  "type": "record",
+  "name": "Lesson",
+  "namespace": "foo.bar",
+  "fields": [
+    {"name": "id", "type": "long"},
+    {"name": "time", "type": {"type": "record", "name": "LessonTime", "fields": [
+      {"name": "start", "type": {"type": "long", "logicalType": "timestamp-millis"}}
+    ]}},
+    {"name": "status", "type": ["null", {"type": "enum", "name": "Status", "symbols": ["A", "B"]}]},
+    {"name": "hash", "type": {"type": "fixed", "name": "MD5", "size": 16}},
+    {"name": "hashes", "type": {"type": "array", "items": "MD5"}}
+  ]
+}"#,
+    );
+    assert_eq!(namespaces.len(), 1);
+    let (namespace, records, enums) = namespaces.remove(0);
+    assert_eq!(namespace, "foo.bar");
+    let names: Vec<&str> = records.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["Lesson", "LessonTime"]);
+    assert_eq!(records[0].1["fields"][1]["type"], "LessonTime");
+    assert_eq!(records[0].1["fields"][2]["type"], json!(["null", "Status"]));
+    assert_eq!(records[0].1["fields"][3]["type"]["type"], "fixed");
+    assert_eq!(records[0].1["fields"][4]["type"]["items"]["type"], "fixed");
+    assert_eq!(enums.len(), 1);
+    assert_eq!(enums[0].1["symbols"], json!(["A", "B"]));
+  }
+
+  #[test]
+  fn test_searcher_idl() {
+    let source = r#"/** The lessons */
+@namespace("foo.bar")
+protocol Lessons {
+  import idl "base.avdl";
+  import schema "time.avsc";
+
+  enum Status { A, B } = A;
+  fixed MD5(16);
+
+  @namespace("other") record Base { string id; }
+
+  record Lesson {
+    long id = 0;
+    string? name;
+    union { null, Status } status = null;
+    array<map<MD5>> hashes;
+    @logicalType("timestamp-millis") long start, end;
+    date day;
+    decimal(9, 2) price = "0";
+    other.Base base;
+  }
+
+  Lesson get(long id) throws Error;
+}"#;
+    assert_eq!(includes_searcher(source), ["base.avdl", "time.avsc"]);
+    let mut namespaces = blocks(source);
+    // `Base` is declared into its own namespace, which `other.Base` refers to
+    let (other, bases, other_enums) = namespaces.pop().unwrap();
+    assert_eq!(other, "other");
+    assert_eq!(bases[0].0, "Base");
+    assert!(other_enums.is_empty());
+    let (namespace, records, enums) = namespaces.pop().unwrap();
+    assert_eq!(namespace, "foo.bar");
+    let names: Vec<&str> = records.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["Lesson"]);
+    assert_eq!(enums[0].0, "Status");
+    assert_eq!(
+      records[0].1["fields"],
+      json!([
+        {"name": "id", "type": "long"},
+        {"name": "name", "type": ["null", "string"]},
+        {"name": "status", "type": ["null", "Status"]},
+        {"name": "hashes", "type": {"type": "array", "items": {"type": "map", "values": {"type": "fixed", "name": "MD5", "size": 16}}}},
+        {"name": "start", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "end", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "day", "type": {"type": "int", "logicalType": "date"}},
+        {"name": "price", "type": {"type": "bytes", "logicalType": "decimal"}},
+        {"name": "base", "type": "other.Base"},
+      ])
+    );
+  }
+}
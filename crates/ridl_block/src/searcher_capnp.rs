@@ -0,0 +1,111 @@
+use regex::Regex;
+
+use ridl_utils::code_block::{search_blocks_from_source, CodeBlock};
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+/// Cap'n Proto has no packages, so the namespace is taken from `$Cxx.namespace("foo::bar");`.
+fn parse_namespace_by_re(source_str: &str) -> String {
+  let re = Regex::new(r#"(?m)^\s*\$Cxx\.namespace\(\s*"([\w:]+)"\s*\)\s*;"#).unwrap();
+  match re.captures(source_str) {
+    Some(c) => c.get(1).unwrap().as_str().replace("::", "."),
+    None => String::from(""),
+  }
+}
+
+/// The path of the imported files, e.g. `base.capnp` of `using Base = import "base.capnp";`. The
+/// paths starting with `/` are relative to the root folder, and the schemas shipped with capnp
+/// (`/capnp/*`) are built in, so they are not imported.
+pub fn includes_searcher(source_str: &str) -> Vec<String> {
+  let re = Regex::new(r#"\bimport\s+"([^"]+)""#).unwrap();
+  re.captures_iter(source_str)
+    .map(|c| c.get(1).unwrap().as_str())
+    .filter(|path| !path.starts_with("/capnp/"))
+    .map(|path| path.trim_start_matches('/').to_string())
+    .collect()
+}
+
+/// The imports named by `using`, e.g. `using Base = import "base.capnp";` is a block named `Base`,
+/// whose namespace is declared once the imported file is read. The built in schemas are skipped.
+fn search_imports(source_str: &str) -> Vec<CodeBlock> {
+  let re = Regex::new(r#"(?m)^\s*using\s+(\w+)\s*=\s*import\s+"([^"]+)"\s*;"#).unwrap();
+  re.captures_iter(source_str)
+    .filter(|c| !c[2].starts_with("/capnp/"))
+    .map(|c| CodeBlock {
+      name: c[1].to_string(),
+      lines: vec![c[0].trim().to_string()],
+      order: source_str[..c.get(0).unwrap().start()]
+        .matches('\n')
+        .count(),
+    })
+    .collect()
+}
+
+/// Search the top level `struct` and `enum` blocks, the nested structs and enums are kept inside
+/// the blocks of their parents. The imports named by `using` are kept as aliases.
+pub fn searcher(source_str: &str) -> (String, IdlBlocksMap) {
+  let mut cbs_map = IdlBlocksMap::new();
+
+  let namespace = parse_namespace_by_re(source_str);
+
+  let re_head = Regex::new(r"^(struct|enum)\s").unwrap();
+  let blocks = search_blocks_from_source(source_str, re_head, None, None, None);
+  let (enum_blocks, struct_blocks): (Vec<CodeBlock>, Vec<CodeBlock>) = blocks
+    .into_iter()
+    .map(|mut b| {
+      // the name is followed by the id or the generic parameters, e.g. `Map(Key, Value)`
+      b.name = b
+        .name
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or("")
+        .to_string();
+      b
+    })
+    .partition(|b| b.lines[0].trim_start().starts_with("enum"));
+  cbs_map.insert(IdlType::Interface, struct_blocks);
+  cbs_map.insert(IdlType::Enum, enum_blocks);
+  cbs_map.insert(IdlType::Alias, search_imports(source_str));
+
+  (namespace, cbs_map)
+}
+
+#[test]
+fn test_searcher() {
+  let source = r#"@0xdbb9ad1f14bf0b36;
+
+using Cxx = import "/capnp/c++.capnp";
+$Cxx.namespace("storage::v1");
+using Base = import "/common/base.capnp";
+
+struct Person @0x8e0594c8abeb307c {
+  name @0 :Text;
+  struct Phone {
+    number @0 :Text;
+  }
+  enum Kind { user @0; admin @1; }
+}
+
+enum Status { open @0; closed @1; }
+
+struct Map(Key, Value) {
+  entries @0 :List(Entry);
+}
+
+interface Store {
+  get @0 (key :Text) -> (value :Text);
+}
+"#;
+  let (namespace, cbs_map) = searcher(source);
+  assert_eq!(namespace, "storage.v1");
+  assert_eq!(includes_searcher(source), ["common/base.capnp"]);
+  let names = |idl_type: IdlType| -> Vec<String> {
+    cbs_map[&idl_type].iter().map(|b| b.name.clone()).collect()
+  };
+  assert_eq!(names(IdlType::Interface), ["Person", "Map"]);
+  assert_eq!(names(IdlType::Enum), ["Status"]);
+  assert_eq!(names(IdlType::Alias), ["Base"]);
+  assert_eq!(
+    cbs_map[&IdlType::Alias][0].lines,
+    [r#"using Base = import "/common/base.capnp";"#]
+  );
+}
@@ -0,0 +1,92 @@
+use regex::Regex;
+
+use ridl_utils::code_block::{search_blocks_from_source, CodeBlock};
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+fn parse_namespace_by_re(source_str: &str) -> String {
+  let re = Regex::new(r"(?m)^\s*namespace\s+([\w\.]+)\s*;").unwrap();
+  match re.captures(source_str) {
+    Some(c) => c.get(1).unwrap().as_str().to_string(),
+    None => String::from(""),
+  }
+}
+
+/// The path of the included files, e.g. `base.fbs` of `include "base.fbs";`.
+pub fn includes_searcher(source_str: &str) -> Vec<String> {
+  let re = Regex::new(r#"(?m)^\s*include\s+"([^"]+)"\s*;"#).unwrap();
+  re.captures_iter(source_str)
+    .map(|c| c.get(1).unwrap().as_str().to_string())
+    .collect()
+}
+
+/// Search the `table`, `struct`, `enum` and `union` blocks, tables and structs are interfaces and
+/// unions are aliases. The namespace of the file is used as the namespace.
+pub fn searcher(source_str: &str) -> (String, IdlBlocksMap) {
+  let mut cbs_map = IdlBlocksMap::new();
+
+  let namespace = parse_namespace_by_re(source_str);
+
+  let re_head = Regex::new(r"^(table|struct|enum|union)\s").unwrap();
+  let blocks = search_blocks_from_source(source_str, re_head, None, None, None);
+  let keyword_of = |b: &CodeBlock| {
+    b.lines[0]
+      .split_whitespace()
+      .next()
+      .unwrap_or("")
+      .to_string()
+  };
+  let mut interface_blocks = Vec::new();
+  let mut enum_blocks = Vec::new();
+  let mut alias_blocks = Vec::new();
+  for mut b in blocks {
+    // the name is followed by the underlying type of enums, e.g. `Color : byte`
+    b.name = b
+      .name
+      .split(|c: char| !c.is_alphanumeric() && c != '_')
+      .next()
+      .unwrap_or("")
+      .to_string();
+    match keyword_of(&b).as_str() {
+      "enum" => enum_blocks.push(b),
+      "union" => alias_blocks.push(b),
+      _ => interface_blocks.push(b),
+    }
+  }
+  cbs_map.insert(IdlType::Interface, interface_blocks);
+  cbs_map.insert(IdlType::Enum, enum_blocks);
+  cbs_map.insert(IdlType::Alias, alias_blocks);
+
+  (namespace, cbs_map)
+}
+
+#[test]
+fn test_searcher() {
+  let source = r#"include "base.fbs";
+namespace game.telemetry;
+
+/// a monster
+table Monster {
+  pos:Vec3;
+  hp:short = 100;
+}
+
+struct Vec3 { x:float; y:float; z:float; }
+
+enum Color : byte { Red = 0, Green, Blue = 2 }
+
+union Equipment { Weapon, Shield }
+
+root_type Monster;
+"#;
+  let (namespace, cbs_map) = searcher(source);
+  assert_eq!(namespace, "game.telemetry");
+  assert_eq!(includes_searcher(source), ["base.fbs"]);
+  let names = |idl_type: IdlType| -> Vec<String> {
+    cbs_map[&idl_type].iter().map(|b| b.name.clone()).collect()
+  };
+  assert_eq!(names(IdlType::Interface), ["Monster", "Vec3"]);
+  assert_eq!(names(IdlType::Enum), ["Color"]);
+  assert_eq!(cbs_map[&IdlType::Enum][0].lines.len(), 1);
+  assert_eq!(names(IdlType::Alias), ["Equipment"]);
+  assert_eq!(cbs_map[&IdlType::Interface][0].lines.len(), 4);
+}
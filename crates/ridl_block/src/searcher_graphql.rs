@@ -0,0 +1,182 @@
+use ridl_utils::code_block::CodeBlock;
+use ridl_utils::lexer::{tokenize, Token};
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+/// The keywords starting a top level definition.
+const DEFINITION_KEYWORDS: [&str; 9] = [
+  "type",
+  "input",
+  "interface",
+  "enum",
+  "union",
+  "scalar",
+  "extend",
+  "schema",
+  "directive",
+];
+
+/// Render the token back to the source, the strings are quoted again.
+fn token_to_source(token: &Token) -> String {
+  match token {
+    Token::Ident(s) | Token::Number(s) => s.clone(),
+    Token::Str(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+    Token::Symbol(c) => c.to_string(),
+  }
+}
+
+/// Split the tokens into the top level definitions, a definition lasts until the next definition
+/// keyword or description out of any brackets.
+fn split_definitions(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+  let mut definitions: Vec<Vec<Token>> = Vec::new();
+  let mut depth = 0;
+  for token in tokens {
+    if depth == 0 {
+      match &token {
+        // the keyword after `extend` is a part of the extension
+        Token::Ident(i)
+          if DEFINITION_KEYWORDS.contains(&i.as_str())
+            && !definitions
+              .last()
+              .is_some_and(|d| d.len() == 1 && d[0].is_ident("extend")) =>
+        {
+          definitions.push(Vec::new());
+        }
+        // the descriptions are dropped
+        Token::Str(_) => continue,
+        _ => {}
+      }
+    }
+    match token {
+      Token::Symbol('{') | Token::Symbol('(') | Token::Symbol('[') => depth += 1,
+      Token::Symbol('}') | Token::Symbol(')') | Token::Symbol(']') => depth -= 1,
+      _ => {}
+    }
+    if let Some(definition) = definitions.last_mut() {
+      definition.push(token);
+    }
+  }
+  definitions
+}
+
+/// GraphQL has no imports, all the files share a single schema.
+pub fn includes_searcher(_source_str: &str) -> Vec<String> {
+  Vec::new()
+}
+
+/// Search the definitions of the schema, `type`, `input` and `interface` are interfaces, `union`
+/// and `scalar` are aliases. The schema is global so the namespace is empty. An extension, e.g.
+/// `extend type Course { ... }`, is a block named after the type it extends, which the parser
+/// merges into the type. The `schema` and `directive` definitions are skipped. The lines of a
+/// block is the definition in a single line without the comments and the descriptions of
+/// definitions.
+pub fn searcher(source_str: &str) -> (String, IdlBlocksMap) {
+  let mut cbs_map = IdlBlocksMap::new();
+  cbs_map.insert(IdlType::Interface, Vec::new());
+  cbs_map.insert(IdlType::Enum, Vec::new());
+  cbs_map.insert(IdlType::Alias, Vec::new());
+
+  for (order, definition) in split_definitions(tokenize(source_str, &["#"]))
+    .into_iter()
+    .enumerate()
+  {
+    // the keyword and the name follow `extend` in the extensions
+    let head = match definition[0].is_ident("extend") {
+      true => &definition[1..],
+      false => &definition[..],
+    };
+    let idl_type = match head.first() {
+      Some(Token::Ident(i)) if ["type", "input", "interface"].contains(&i.as_str()) => {
+        IdlType::Interface
+      }
+      Some(Token::Ident(i)) if i == "enum" => IdlType::Enum,
+      Some(Token::Ident(i)) if i == "union" => IdlType::Alias,
+      // the extensions of scalars only add directives
+      Some(Token::Ident(i)) if i == "scalar" && head.len() == definition.len() => IdlType::Alias,
+      _ => continue,
+    };
+    let Some(Token::Ident(name)) = head.get(1) else {
+      continue;
+    };
+    let line: Vec<String> = definition.iter().map(token_to_source).collect();
+    cbs_map.get_mut(&idl_type).unwrap().push(CodeBlock {
+      name: name.clone(),
+      lines: vec![line.join(" ")],
+      order,
+    });
+  }
+
+  (String::new(), cbs_map)
+}
+
+#[test]
+fn test_searcher() {
+  let source = r#"
+"""
+A course, with `{` in the description
+"""
+type Course implements Node & Entity @key(fields: "id") {
+  id: ID!
+  "the lessons"
+  lessons(first: Int = 10): [Lesson!]! # comment
+}
+
+input CourseInput { name: String = "a \"b\"" }
+
+enum Status { OPEN CLOSED }
+
+union SearchResult =
+  | Course
+  | Lesson
+
+scalar DateTime @specifiedBy(url: "https://tools.ietf.org/html/rfc3339")
+
+extend type Course { extra: Int }
+
+extend union SearchResult = Teacher
+
+extend scalar DateTime @a
+
+schema { query: Query }
+
+directive @key(fields: String!) on OBJECT | INTERFACE
+
+interface Node { id: ID! }
+"#;
+  let (namespace, cbs_map) = searcher(source);
+  assert_eq!(namespace, "");
+  let blocks = |idl_type: IdlType| -> Vec<(String, String)> {
+    cbs_map[&idl_type]
+      .iter()
+      .map(|b| (b.name.clone(), b.lines.join("\n")))
+      .collect()
+  };
+  assert_eq!(
+    blocks(IdlType::Interface),
+    [
+      ("Course", "type Course implements Node & Entity @ key ( fields : \"id\" ) { id : ID ! \"the lessons\" lessons ( first : Int = 10 ) : [ Lesson ! ] ! }"),
+      ("CourseInput", "input CourseInput { name : String = \"a \\\"b\\\"\" }"),
+      ("Course", "extend type Course { extra : Int }"),
+      ("Node", "interface Node { id : ID ! }"),
+    ]
+    .map(|(n, l)| (n.to_string(), l.to_string()))
+  );
+  assert_eq!(
+    blocks(IdlType::Enum),
+    [(
+      "Status".to_string(),
+      "enum Status { OPEN CLOSED }".to_string()
+    )]
+  );
+  assert_eq!(
+    blocks(IdlType::Alias),
+    [
+      ("SearchResult", "union SearchResult = | Course | Lesson"),
+      (
+        "DateTime",
+        "scalar DateTime @ specifiedBy ( url : \"https://tools.ietf.org/html/rfc3339\" )"
+      ),
+      ("SearchResult", "extend union SearchResult = Teacher"),
+    ]
+    .map(|(n, l)| (n.to_string(), l.to_string()))
+  );
+}
@@ -0,0 +1,455 @@
+use serde_json::{json, Value};
+
+use ridl_parser::openapi::parser::union_members;
+use ridl_utils::code_block::CodeBlock;
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+/// Parse the yaml or json document, the non string keys (e.g. the status codes of responses) are
+/// turned into strings.
+fn parse_document(source_str: &str) -> Option<Value> {
+  let yaml: serde_norway::Value = match serde_norway::from_str(source_str) {
+    Ok(yaml) => yaml,
+    Err(err) => {
+      eprintln!("parsing openapi document with error: {}", err);
+      return None;
+    }
+  };
+  serde_json::to_value(yaml).ok()
+}
+
+/// Turn a schema name into an identifier, e.g. `foo.Bar-Baz` into `foo_Bar_Baz`.
+fn sanitize_name(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| if c.is_alphanumeric() { c } else { '_' })
+    .collect()
+}
+
+/// The namespace of a document is the stem of its file, e.g. `common` of `../common.schema.json`.
+fn file_namespace(file_path: &str) -> String {
+  let file_name = file_path.rsplit(['/', '\\']).next().unwrap_or(file_path);
+  sanitize_name(file_name.split('.').next().unwrap_or(file_name))
+}
+
+/// The name of the type that a `$ref` points to:
+/// - `#/components/schemas/Course` and `common.yaml#/$defs/Course` are `Course`
+/// - `course-plan.json` is the root schema of the file, which is named after the file as
+///   `CoursePlan`, so the root schemas referred by other files should be titled the same
+/// - `#` is the root schema of the current file, named by its `title`
+fn ref_name(r#ref: &str, root_name: &str) -> String {
+  let (file, fragment) = r#ref.split_once('#').unwrap_or((r#ref, ""));
+  let fragment = fragment.trim_end_matches('/');
+  if !fragment.is_empty() {
+    let last = fragment.rsplit('/').next().unwrap_or("");
+    return sanitize_name(&last.replace("~1", "/").replace("~0", "~"));
+  }
+  if file.is_empty() {
+    return root_name.to_string();
+  }
+  let file_name = file.rsplit('/').next().unwrap_or(file);
+  let stem = file_name.split('.').next().unwrap_or(file_name);
+  stem
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|p| !p.is_empty())
+    .map(|p| {
+      let mut chars = p.chars();
+      match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+      }
+    })
+    .collect()
+}
+
+/// The path of the local files referred by `$ref`, e.g. `common.yaml` of
+/// `$ref: common.yaml#/components/schemas/Error`. The remote references are not supported.
+pub fn includes_searcher(source_str: &str) -> Vec<String> {
+  fn collect(value: &Value, files_path: &mut Vec<String>) {
+    match value {
+      Value::Object(o) => {
+        if let Some(Value::String(r#ref)) = o.get("$ref") {
+          let file = r#ref.split('#').next().unwrap_or("");
+          if !file.is_empty() && !file.contains("://") && !files_path.iter().any(|p| p == file) {
+            files_path.push(file.to_string());
+          }
+        }
+        o.values().for_each(|v| collect(v, files_path));
+      }
+      Value::Array(a) => a.iter().for_each(|v| collect(v, files_path)),
+      _ => {}
+    }
+  }
+  let mut files_path = Vec::new();
+  if let Some(document) = parse_document(source_str) {
+    collect(&document, &mut files_path);
+  }
+  files_path
+}
+
+/// The (name, schema) of the named schemas in the document:
+/// - the `components/schemas` of openapi 3, or the `definitions` of swagger 2
+/// - the root schema of json schema if it is titled, and its `$defs` or `definitions`
+fn named_schemas(document: &Value) -> (String, Vec<(String, Value)>) {
+  let entries = |v: Option<&Value>| -> Vec<(String, Value)> {
+    v.and_then(|v| v.as_object())
+      .map(|o| {
+        o.iter()
+          .map(|(k, v)| (sanitize_name(k), v.clone()))
+          .collect()
+      })
+      .unwrap_or_default()
+  };
+  if document.get("openapi").is_some() || document.get("swagger").is_some() {
+    let schemas = match document.get("openapi") {
+      Some(_) => entries(document.pointer("/components/schemas")),
+      None => entries(document.get("definitions")),
+    };
+    return (String::new(), schemas);
+  }
+
+  let is_json_schema = [
+    "$schema",
+    "$defs",
+    "definitions",
+    "properties",
+    "enum",
+    "oneOf",
+  ]
+  .iter()
+  .any(|k| document.get(k).is_some());
+  if !is_json_schema {
+    return (String::new(), Vec::new());
+  }
+  let root_name = document
+    .get("title")
+    .and_then(|t| t.as_str())
+    .map(sanitize_name)
+    .unwrap_or_default();
+  let mut schemas = Vec::new();
+  let mut root = document.clone();
+  if let Some(o) = root.as_object_mut() {
+    schemas.extend(entries(o.remove("$defs").as_ref()));
+    schemas.extend(entries(o.remove("definitions").as_ref()));
+  }
+  let has_root_type = ["type", "properties", "enum", "oneOf", "anyOf", "allOf"]
+    .iter()
+    .any(|k| root.get(k).is_some());
+  if !root_name.is_empty() && has_root_type {
+    schemas.insert(0, (root_name.clone(), root));
+  }
+  (root_name, schemas)
+}
+
+/// Whether an inline schema needs a name to be presented, which are the objects with properties,
+/// the enums and the unions of many types.
+fn needs_name(schema: &Value) -> bool {
+  let Some(o) = schema.as_object() else {
+    return false;
+  };
+  if o.contains_key("$ref") {
+    return false;
+  }
+  let all_of = o.get("allOf").and_then(|a| a.as_array());
+  o.contains_key("properties")
+    || o.contains_key("enum")
+    || all_of.is_some_and(|a| a.len() > 1 || a.iter().any(|m| m.get("$ref").is_none()))
+    || union_members(schema).len() > 1
+}
+
+struct Collector<'a> {
+  namespace: &'a str,
+  root_name: &'a str,
+  schemas: Vec<(String, Value)>,
+}
+
+impl Collector<'_> {
+  /// Collect the schema as a named one, the parents come before the schemas defined inside them.
+  fn collect(&mut self, name: String, mut schema: Value) {
+    let index = self.schemas.len();
+    self.schemas.push((name.clone(), Value::Null));
+    self.rewrite(&mut schema, &name, true);
+    self.schemas[index].1 = schema;
+  }
+
+  /// Collect the schema as `name` and replace it by the reference if it needs a name, otherwise
+  /// rewrite the schemas inside it.
+  fn replace(&mut self, schema: &mut Value, name: String) {
+    if needs_name(schema) {
+      self.collect(name.clone(), schema.take());
+      *schema = json!({ "$ref": name });
+    } else {
+      self.rewrite(schema, &name, false);
+    }
+  }
+
+  /// Normalize the `$ref` into the name of the referred type, which is qualified by the namespace
+  /// of the document if it is another one, e.g. `common.Error`. And replace the inline schemas that
+  /// need names by the references, e.g. the object of the property `plan` of `Course` is named as
+  /// `Course_plan`. `named` is whether `path` is the name of `schema` itself.
+  fn rewrite(&mut self, schema: &mut Value, path: &str, named: bool) {
+    let Some(o) = schema.as_object_mut() else {
+      return;
+    };
+    if let Some(Value::String(r#ref)) = o.get("$ref") {
+      let name = ref_name(r#ref, self.root_name);
+      let file = r#ref.split('#').next().unwrap_or("");
+      let namespace = file_namespace(file);
+      let name = match file.is_empty() || namespace == self.namespace {
+        true => name,
+        false => format!("{}.{}", namespace, name),
+      };
+      o.insert("$ref".to_string(), Value::String(name));
+    }
+    if let Some(Value::Object(properties)) = o.get_mut("properties") {
+      for (k, v) in properties.iter_mut() {
+        self.replace(v, format!("{}_{}", path, sanitize_name(k)));
+      }
+    }
+    for (key, postfix) in [("items", "item"), ("additionalProperties", "value")] {
+      if let Some(v) = o.get_mut(key) {
+        let name = match named {
+          true => format!("{}_{}", path, postfix),
+          false => path.to_string(),
+        };
+        self.replace(v, name);
+      }
+    }
+    for key in ["oneOf", "anyOf"] {
+      if let Some(Value::Array(members)) = o.get_mut(key) {
+        for (i, m) in members.iter_mut().enumerate() {
+          self.replace(m, format!("{}_{}", path, i));
+        }
+      }
+    }
+    // the members of `allOf` are merged, so they are parts of the schema
+    if let Some(Value::Array(members)) = o.get_mut("allOf") {
+      for m in members.iter_mut() {
+        self.rewrite(m, path, named);
+      }
+    }
+  }
+}
+
+/// The idl type of a named schema, the objects are interfaces and the others are aliases.
+fn idl_type_of(schema: &Value) -> IdlType {
+  let has = |k: &str| schema.get(k).is_some();
+  if has("enum") {
+    IdlType::Enum
+  } else if has("properties") || has("allOf") {
+    IdlType::Interface
+  } else {
+    IdlType::Alias
+  }
+}
+
+/// Search the schemas of an openapi 3 (or swagger 2) document or a json schema, in yaml or json.
+/// Every named schema becomes a block of a single line, which is the json of the schema:
+/// - the inline objects, enums and unions are named after their parents and become blocks as well
+/// - the `$ref` are replaced by the names of the referred types
+///
+/// Every document has its own namespace named after its file, so the schemas of the same name in
+/// different documents do not collide.
+pub fn searcher(file_path: &str, source_str: &str) -> (String, IdlBlocksMap) {
+  let mut cbs_map = IdlBlocksMap::new();
+  cbs_map.insert(IdlType::Interface, Vec::new());
+  cbs_map.insert(IdlType::Enum, Vec::new());
+  cbs_map.insert(IdlType::Alias, Vec::new());
+
+  let namespace = file_namespace(file_path);
+  let Some(document) = parse_document(source_str) else {
+    return (namespace, cbs_map);
+  };
+  let (root_name, schemas) = named_schemas(&document);
+  let mut collector = Collector {
+    namespace: namespace.as_str(),
+    root_name: root_name.as_str(),
+    schemas: Vec::new(),
+  };
+  for (name, schema) in schemas {
+    collector.collect(name, schema);
+  }
+  for (order, (name, schema)) in collector.schemas.into_iter().enumerate() {
+    cbs_map
+      .get_mut(&idl_type_of(&schema))
+      .unwrap()
+      .push(CodeBlock {
+        name,
+        lines: vec![schema.to_string()],
+        order,
+      });
+  }
+
+  (namespace, cbs_map)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{file_namespace, includes_searcher, ref_name, searcher};
+  use ridl_utils::types::IdlType;
+  use serde_json::{json, Value};
+
+  type NamedSchemas = Vec<(String, Value)>;
+
+  fn blocks(file_path: &str, source: &str) -> (NamedSchemas, NamedSchemas, NamedSchemas) {
+    let (_, cbs_map) = searcher(file_path, source);
+    let to_json = |idl_type: IdlType| {
+      cbs_map[&idl_type]
+        .iter()
+        .map(|b| (b.name.clone(), serde_json::from_str(&b.lines[0]).unwrap()))
+        .collect()
+    };
+    (
+      to_json(IdlType::Interface),
+      to_json(IdlType::Enum),
+      to_json(IdlType::Alias),
+    )
+  }
+
+  #[test]
+  fn test_ref_name() {
+    assert_eq!(ref_name("#/components/schemas/Course", "Root"), "Course");
+    assert_eq!(ref_name("common.yaml#/$defs/a.Error", "Root"), "a_Error");
+    assert_eq!(ref_name("../course-plan.schema.json", "Root"), "CoursePlan");
+    assert_eq!(ref_name("#", "Root"), "Root");
+  }
+
+  #[test]
+  fn test_file_namespace() {
+    assert_eq!(file_namespace("api/course.yaml"), "course");
+    assert_eq!(file_namespace("../course-plan.schema.json"), "course_plan");
+    assert_eq!(searcher("api/common.yaml", "").0, "common");
+  }
+
+  #[test]
+  fn test_searcher_openapi() {
+    let (interfaces, enums, aliases) = blocks(
+      "api/course.yaml",
+      r#"openapi: 3.0.3
+info: { title: Course, version: "1" }
+paths:
+  /courses:
+    get:
+      responses:
+        200:
+          description: ok
+components:
+  schemas:
+    Course:
+      type: object
+      required: [id]
+      properties:
+        id: { type: integer, format: int64 }
+        status:
+          type: string
+          enum: [open, closed]
+        plan:
+          type: object
+          properties:
+            weeks: { type: integer }
+        lessons:
+          type: array
+          items:
+            $ref: '#/components/schemas/Lesson'
+        teacher:
+          $ref: 'common.yaml#/components/schemas/Teacher'
+        next:
+          $ref: 'course.yaml#/components/schemas/Course'
+    Lesson:
+      allOf:
+        - $ref: '#/components/schemas/Base'
+        - type: object
+          properties:
+            name: { type: string, nullable: true }
+    Id:
+      oneOf:
+        - type: string
+        - type: integer
+"#,
+    );
+    assert_eq!(
+      interfaces,
+      [
+        (
+          "Course",
+          json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+              "id": {"type": "integer", "format": "int64"},
+              "status": {"$ref": "Course_status"},
+              "plan": {"$ref": "Course_plan"},
+              "lessons": {"type": "array", "items": {"$ref": "Lesson"}},
+              "teacher": {"$ref": "common.Teacher"},
+              "next": {"$ref": "Course"},
+            }
+          })
+        ),
+        (
+          "Course_plan",
+          json!({"type": "object", "properties": {"weeks": {"type": "integer"}}})
+        ),
+        (
+          "Lesson",
+          json!({"allOf": [
+            {"$ref": "Base"},
+            {"type": "object", "properties": {"name": {"type": "string", "nullable": true}}}
+          ]})
+        ),
+      ]
+      .map(|(n, s)| (n.to_string(), s))
+    );
+    assert_eq!(
+      enums,
+      [(
+        "Course_status".to_string(),
+        json!({"type": "string", "enum": ["open", "closed"]})
+      )]
+    );
+    assert_eq!(
+      aliases,
+      [(
+        "Id".to_string(),
+        json!({"oneOf": [{"type": "string"}, {"type": "integer"}]})
+      )]
+    );
+  }
+
+  #[test]
+  fn test_searcher_json_schema() {
+    let source = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "Course",
+  "type": "object",
+  "properties": {
+    "parent": {"$ref": "#"},
+    "tags": {"type": "array", "items": {"$ref": "#/$defs/Tag"}},
+    "owner": {"$ref": "user.schema.json"}
+  },
+  "$defs": {
+    "Tag": {"type": "object", "properties": {"name": {"type": "string"}}}
+  }
+}"##;
+    let (interfaces, _, _) = blocks("course.schema.json", source);
+    assert_eq!(
+      interfaces
+        .iter()
+        .map(|(n, _)| n.as_str())
+        .collect::<Vec<&str>>(),
+      ["Course", "Tag"]
+    );
+    assert_eq!(
+      interfaces[0].1["properties"],
+      json!({
+        "parent": {"$ref": "Course"},
+        "tags": {"type": "array", "items": {"$ref": "Tag"}},
+        "owner": {"$ref": "user.User"},
+      })
+    );
+    assert_eq!(includes_searcher(source), ["user.schema.json"]);
+
+    // the documents of other kinds are skipped
+    let (interfaces, enums, aliases) =
+      blocks("package.json", r#"{"name": "ridl", "version": "0.1.0"}"#);
+    assert!(interfaces.is_empty() && enums.is_empty() && aliases.is_empty());
+  }
+}
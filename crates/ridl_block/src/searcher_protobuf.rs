@@ -0,0 +1,73 @@
+use regex::Regex;
+
+use ridl_utils::code_block::{search_blocks_from_source, CodeBlock};
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+fn parse_package_by_re(source_str: &str) -> String {
+  let re = Regex::new(r"(?m)^\s*package\s+([\w\.]+)\s*;").unwrap();
+  match re.captures(source_str) {
+    Some(c) => c.get(1).unwrap().as_str().to_string(),
+    None => String::from(""),
+  }
+}
+
+/// The path of the imported files, e.g. `foo/bar.proto` of `import public "foo/bar.proto";`. The
+/// well-known types of `google/protobuf` are built in, so they are not imported.
+pub fn includes_searcher(source_str: &str) -> Vec<String> {
+  let re = Regex::new(r#"(?m)^\s*import\s+(?:(?:public|weak)\s+)?"([^"]+)"\s*;"#).unwrap();
+  re.captures_iter(source_str)
+    .map(|c| c.get(1).unwrap().as_str().to_string())
+    .filter(|path| !path.starts_with("google/protobuf/"))
+    .collect()
+}
+
+/// Search the top level `message` and `enum` blocks, the package is used as the namespace. Nested
+/// messages and enums are kept inside the blocks of their parents.
+pub fn searcher(source_str: &str) -> (String, IdlBlocksMap) {
+  let mut cbs_map = IdlBlocksMap::new();
+
+  let namespace = parse_package_by_re(source_str);
+
+  // messages and enums are searched together, so the nested ones are not taken as top level blocks
+  let re_head = Regex::new(r"^(message|enum)\s").unwrap();
+  let blocks = search_blocks_from_source(source_str, re_head, None, None, None);
+  let (enum_blocks, message_blocks): (Vec<CodeBlock>, Vec<CodeBlock>) = blocks
+    .into_iter()
+    .partition(|b| b.lines[0].trim_start().starts_with("enum"));
+  cbs_map.insert(IdlType::Interface, message_blocks);
+  cbs_map.insert(IdlType::Enum, enum_blocks);
+
+  (namespace, cbs_map)
+}
+
+#[test]
+fn test_searcher() {
+  let source = r#"syntax = "proto3";
+package foo.bar;
+
+import "google/protobuf/timestamp.proto";
+import public "common/base.proto";
+
+message Outer {
+  message Inner {
+    int32 a = 1;
+  }
+  enum Kind { KIND_UNSPECIFIED = 0; }
+  Inner inner = 1;
+}
+
+enum Status {
+  STATUS_UNSPECIFIED = 0;
+}
+"#;
+  assert_eq!(includes_searcher(source), ["common/base.proto"]);
+  let (namespace, cbs_map) = searcher(source);
+  assert_eq!(namespace, "foo.bar");
+  let messages = &cbs_map[&IdlType::Interface];
+  assert_eq!(messages.len(), 1);
+  assert_eq!(messages[0].name, "Outer");
+  assert_eq!(messages[0].lines.len(), 7);
+  let enums = &cbs_map[&IdlType::Enum];
+  assert_eq!(enums.len(), 1);
+  assert_eq!(enums[0].name, "Status");
+}
@@ -4,32 +4,145 @@ use ridl_utils::code_block::{search_blocks_from_source, CodeBlock};
 use ridl_utils::types::{IdlBlocksMap, IdlType};
 
 fn parse_namespace_by_re(source_str: &str) -> String {
-  let RE = Regex::new(r"(?:^|\n)namespace go ([\w\.]+)\n").unwrap();
-  match RE.captures(source_str) {
+  let re = Regex::new(r"(?:^|\n)namespace go ([\w\.]+)\n").unwrap();
+  match re.captures(source_str) {
     Some(c) => c.get(1).unwrap().as_str().to_string(),
     None => String::from(""),
   }
 }
 
+/// The path of the included files, e.g. `base.thrift` of `include "base.thrift"`.
+pub fn includes_searcher(source_str: &str) -> Vec<String> {
+  let re = Regex::new(r#"(?m)^\s*include\s+"([^"]+)""#).unwrap();
+  re.captures_iter(source_str)
+    .map(|c| c.get(1).unwrap().as_str().to_string())
+    .collect()
+}
+
+/// The regex of the head of the blocks by their keywords, the keyword starts the line so that e.g.
+/// the `enum` in `include "enums.thrift"` is not taken for a head.
+fn re_head(keywords: &[&str]) -> Regex {
+  Regex::new(&format!(r"^\s*(?:{})\s", keywords.join("|"))).unwrap()
+}
+
+/// The typedefs are a line each, e.g. `typedef map<string, i64> Scores`, so they are searched by
+/// lines instead of blocks.
+fn search_typedefs(source_str: &str) -> Vec<CodeBlock> {
+  let re = Regex::new(r"^\s*typedef\s+(?:[\w\.]+|(?:map|list|set)<.+>)\s+(\w+)").unwrap();
+  source_str
+    .lines()
+    .enumerate()
+    .filter_map(|(i, line)| {
+      re.captures(line).map(|c| CodeBlock {
+        name: c[1].to_string(),
+        lines: vec![line.to_string()],
+        order: i,
+      })
+    })
+    .collect()
+}
+
 pub fn searcher(source_str: &str) -> (String, IdlBlocksMap) {
-  let mut cbsMap = IdlBlocksMap::new();
+  let mut cbs_map = IdlBlocksMap::new();
 
   let namespace = parse_namespace_by_re(source_str);
-  println!("namespace {}", namespace);
 
-  // 1. searching interface blocks
-  println!("searching interface blocks..");
-  let re_interface = Regex::new(IdlType::Interface.as_str()).unwrap();
+  // 1. searching interface blocks, the exceptions are structs thrown by the methods
+  let re_interface = re_head(&["struct", "exception"]);
   let source = source_str;
   let interface_blocks: Vec<CodeBlock> =
     search_blocks_from_source(source, re_interface, None, None, None);
-  cbsMap.insert(IdlType::Interface, interface_blocks);
+  cbs_map.insert(IdlType::Interface, interface_blocks);
 
   // 2. searching enum blocks
-  println!("searching enum blocks..");
-  let re_enum = Regex::new(IdlType::Enum.as_str()).unwrap();
+  let re_enum = re_head(&["enum"]);
   let enum_blocks = search_blocks_from_source(source_str, re_enum, None, None, None);
-  cbsMap.insert(IdlType::Enum, enum_blocks);
+  cbs_map.insert(IdlType::Enum, enum_blocks);
+
+  // 3. searching service blocks
+  let re_service = re_head(&["service"]);
+  let service_blocks = search_blocks_from_source(source_str, re_service, None, None, None);
+  cbs_map.insert(IdlType::Service, service_blocks);
+
+  // 4. searching typedef lines and union blocks, which both stand for other types
+  let mut alias_blocks = search_typedefs(source_str);
+  alias_blocks.extend(search_blocks_from_source(
+    source_str,
+    re_head(&["union"]),
+    None,
+    None,
+    None,
+  ));
+  alias_blocks.sort_by_key(|b| b.order);
+  cbs_map.insert(IdlType::Alias, alias_blocks);
 
-  (namespace, cbsMap)
+  (namespace, cbs_map)
+}
+
+#[cfg(test)]
+mod test {
+  use super::searcher;
+  use ridl_utils::types::IdlType;
+
+  #[test]
+  fn test_searcher_demo() {
+    let source = include_str!("../../../playground/0-parser/demo/schedule.thrift");
+    let (namespace, blocks_map) = searcher(source);
+    assert_eq!(namespace, "ek.ecp.after_school");
+    let names = |idl_type: IdlType| -> Vec<String> {
+      blocks_map[&idl_type]
+        .iter()
+        .map(|b| b.name.clone())
+        .collect()
+    };
+    assert_eq!(
+      names(IdlType::Enum),
+      [
+        "ScheduleRuleType",
+        "LoopMode",
+        "SearchType",
+        "ScheduleConflictType",
+        "LessonStatus"
+      ]
+    );
+    assert_eq!(
+      names(IdlType::Interface)[..3],
+      ["ScheduleRuleBanTime", "ScheduleRule", "LessonTime"]
+    );
+  }
+
+  #[test]
+  fn test_searcher_typedef() {
+    let source = "namespace go foo\n\ntypedef i64 Id\ntypedef map<string, list<base.Base>> Bases // the bases\n\nstruct S {\n  1: Id id\n}\n";
+    let (_, blocks_map) = searcher(source);
+    let typedefs: Vec<(&str, &str)> = blocks_map[&IdlType::Alias]
+      .iter()
+      .map(|b| (b.name.as_str(), b.lines[0].as_str()))
+      .collect();
+    assert_eq!(
+      typedefs,
+      [
+        ("Id", "typedef i64 Id"),
+        (
+          "Bases",
+          "typedef map<string, list<base.Base>> Bases // the bases"
+        )
+      ]
+    );
+  }
+
+  #[test]
+  fn test_searcher_exception_and_union() {
+    let source = "namespace go foo\n\nexception NotFound {\n  1: string message\n}\n\nunion Id {\n  1: i64 num\n  2: string str\n}\n\ntypedef Id Key\n";
+    let (_, blocks_map) = searcher(source);
+    let names = |idl_type: IdlType| -> Vec<&str> {
+      blocks_map[&idl_type]
+        .iter()
+        .map(|b| b.name.as_str())
+        .collect()
+    };
+    assert_eq!(names(IdlType::Interface), ["NotFound"]);
+    assert_eq!(names(IdlType::Alias), ["Id", "Key"]);
+    assert_eq!(blocks_map[&IdlType::Alias][0].lines[0], "union Id {");
+  }
 }
@@ -0,0 +1,124 @@
+use serde_json::Value;
+
+use ridl_utils::code_block::CodeBlock;
+use ridl_utils::types::{IdlBlocksMap, IdlType};
+
+/// The json of `thrift --gen json` is generated for every included file when `-r` is given, and
+/// the references to other files are already resolved by the compiler, so there is nothing to
+/// include.
+pub fn includes_searcher(_source_str: &str) -> Vec<String> {
+  Vec::new()
+}
+
+/// Rewrite the classes of a definition, e.g. `base.Base` of the type of a field, by `resolve`, the
+/// ones it returns none for are left as they are.
+pub fn resolve_classes(value: &mut Value, resolve: &dyn Fn(&str) -> Option<String>) {
+  match value {
+    Value::Object(entries) => {
+      for (key, value) in entries.iter_mut() {
+        match value {
+          Value::String(class) if key == "class" => {
+            if let Some(resolved) = resolve(class) {
+              *class = resolved;
+            }
+          }
+          value => resolve_classes(value, resolve),
+        }
+      }
+    }
+    Value::Array(items) => items.iter_mut().for_each(|v| resolve_classes(v, resolve)),
+    _ => {}
+  }
+}
+
+/// Search the definitions of the json generated by `thrift --gen json`, every definition becomes a
+/// block of a single line, which is its json. The namespace is the `go` one, as for thrift files.
+/// The classes are qualified by their programs, the ones of the program itself are left bare.
+pub fn searcher(source_str: &str) -> (String, IdlBlocksMap) {
+  let mut cbs_map = IdlBlocksMap::new();
+  let program: Value = match serde_json::from_str(source_str) {
+    Ok(program) => program,
+    Err(err) => {
+      eprintln!("parsing thrift json with error: {}", err);
+      return (String::new(), cbs_map);
+    }
+  };
+  let namespace = program["namespaces"]["go"]
+    .as_str()
+    .unwrap_or("")
+    .to_string();
+  let own_program = format!("{}.", program["name"].as_str().unwrap_or(""));
+
+  for (key, idl_type) in [
+    ("structs", IdlType::Interface),
+    ("enums", IdlType::Enum),
+    ("typedefs", IdlType::Alias),
+    ("services", IdlType::Service),
+    ("constants", IdlType::Constant),
+  ] {
+    let Some(definitions) = program[key].as_array() else {
+      continue;
+    };
+    let blocks = definitions
+      .iter()
+      .map(|d| {
+        let mut d = d.clone();
+        resolve_classes(&mut d, &|class| {
+          class.strip_prefix(own_program.as_str()).map(str::to_string)
+        });
+        CodeBlock {
+          name: d["name"].as_str().unwrap_or("").to_string(),
+          lines: vec![d.to_string()],
+          // the json lists the definitions by their kinds, whose order in the source is lost
+          order: 0,
+        }
+      })
+      .collect();
+    cbs_map.insert(idl_type, blocks);
+  }
+
+  (namespace, cbs_map)
+}
+
+#[test]
+fn test_searcher() {
+  let (namespace, cbs_map) = searcher(
+    r#"{
+  "name": "course",
+  "namespaces": {"go": "course", "java": "com.course"},
+  "includes": ["base"],
+  "enums": [{"name": "Grade", "members": [{"name": "One", "value": 1}]}],
+  "typedefs": [{"name": "Id", "typeId": "i64"}],
+  "structs": [{"name": "Course", "fields": []}, {"name": "Lesson", "fields": [
+    {"name": "course", "type": {"typeId": "struct", "class": "course.Course"}},
+    {"name": "base", "type": {"typeId": "struct", "class": "base.Base"}}
+  ]}],
+  "constants": [],
+  "services": [{"name": "CourseService", "functions": []}]
+}"#,
+  );
+  assert_eq!(namespace, "course");
+  let names = |idl_type: IdlType| -> Vec<String> {
+    cbs_map[&idl_type].iter().map(|b| b.name.clone()).collect()
+  };
+  assert_eq!(names(IdlType::Interface), ["Course", "Lesson"]);
+  assert_eq!(names(IdlType::Enum), ["Grade"]);
+  assert_eq!(names(IdlType::Alias), ["Id"]);
+  assert_eq!(names(IdlType::Service), ["CourseService"]);
+  assert!(names(IdlType::Constant).is_empty());
+  assert_eq!(
+    cbs_map[&IdlType::Alias][0].lines,
+    [r#"{"name":"Id","typeId":"i64"}"#]
+  );
+  // the classes of the program itself are bare
+  assert_eq!(
+    cbs_map[&IdlType::Interface][1].lines,
+    [
+      r#"{"name":"Lesson","fields":[{"name":"course","type":{"typeId":"struct","class":"Course"}},{"name":"base","type":{"typeId":"struct","class":"base.Base"}}]}"#
+    ]
+  );
+
+  let (namespace, cbs_map) = searcher("not json");
+  assert_eq!(namespace, "");
+  assert!(cbs_map.is_empty());
+}
@@ -0,0 +1,76 @@
+/// Split an identifier into words, by the separators and the lowercase to uppercase boundaries.
+fn words(s: &str) -> Vec<String> {
+  let mut words: Vec<String> = Vec::new();
+  let mut prev_lower = false;
+  for c in s.chars() {
+    if !c.is_ascii_alphanumeric() {
+      prev_lower = false;
+      words.push(String::new());
+      continue;
+    }
+    if c.is_ascii_uppercase() && prev_lower || words.is_empty() {
+      words.push(String::new());
+    }
+    prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    words.last_mut().unwrap().push(c);
+  }
+  words.into_iter().filter(|w| !w.is_empty()).collect()
+}
+
+/// e.g. `loop_mode` of `LoopMode`.
+pub fn snake_case(s: &str) -> String {
+  words(s).join("_").to_lowercase()
+}
+
+/// e.g. `LOOP_MODE` of `LoopMode`.
+pub fn upper_snake_case(s: &str) -> String {
+  words(s).join("_").to_uppercase()
+}
+
+/// e.g. `GetPlan` of `get_plan`, `getPlan` or `GET_PLAN`.
+pub fn upper_camel_case(s: &str) -> String {
+  words(s)
+    .iter()
+    .map(|w| {
+      // the words all in capitals are capitalized, the others keep their inner capitals
+      let w = match w.chars().any(|c| c.is_ascii_lowercase()) {
+        true => w.clone(),
+        false => w.to_lowercase(),
+      };
+      let mut chars = w.chars();
+      chars
+        .next()
+        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+    })
+    .collect()
+}
+
+/// e.g. `getPlan` of `get_plan`, `GetPlan` or `GET_PLAN`.
+pub fn lower_camel_case(s: &str) -> String {
+  let s = upper_camel_case(s);
+  let mut chars = s.chars();
+  chars
+    .next()
+    .map(|c| c.to_ascii_lowercase().to_string() + chars.as_str())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+  use super::{lower_camel_case, snake_case, upper_camel_case, upper_snake_case};
+
+  #[test]
+  fn test_case() {
+    assert_eq!(snake_case("LoopMode"), "loop_mode");
+    assert_eq!(snake_case("list<i32>"), "list_i32");
+    assert_eq!(upper_snake_case("HTTPServer2"), "HTTPSERVER2");
+    assert_eq!(upper_snake_case("loop-mode"), "LOOP_MODE");
+    assert_eq!(upper_camel_case("getPlan"), "GetPlan");
+    assert_eq!(upper_camel_case("get_plan"), "GetPlan");
+    assert_eq!(upper_camel_case("NOT_STARTED"), "NotStarted");
+    assert_eq!(upper_camel_case("HTTPServer"), "HTTPServer");
+    assert_eq!(lower_camel_case("course_title"), "courseTitle");
+    assert_eq!(lower_camel_case("NOT_STARTED"), "notStarted");
+  }
+}
@@ -0,0 +1,72 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ridl_ir::schema::{Definition, Module};
+use ridl_ir::types::{Type, TypeRef};
+
+/// The name of the type of the module a type refers, through the aliases of the module, e.g.
+/// `Course` of `Parent` when the module declares `typedef Course Parent`.
+pub fn local_type<'a>(module: &'a Module, r#type: &'a Type) -> Option<&'a str> {
+  let aliases: HashMap<&str, &Type> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Alias(a) => Some((a.name.as_str(), &a.r#type)),
+      _ => None,
+    })
+    .collect();
+  let is_local = |r: &TypeRef| r.namespace.is_empty() || r.namespace.join(".") == module.namespace;
+  let mut r#type = r#type;
+  for _ in 0..=aliases.len() {
+    match r#type {
+      Type::Ref(r) if is_local(r) => match aliases.get(r.name.as_str()) {
+        Some(t) => r#type = t,
+        None => return Some(r.name.as_str()),
+      },
+      _ => return None,
+    }
+  }
+  None
+}
+
+/// The records of the module which hold themselves, directly or through other records, e.g. `A`
+/// and `B` of `A { b: B }` and `B { a: A? }`. The lists and maps do not count, as their items are
+/// on the heap.
+pub fn recursive_records(module: &Module) -> HashSet<String> {
+  let refs: BTreeMap<&str, Vec<&str>> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(r),
+      _ => None,
+    })
+    .map(|r| {
+      let refs = r
+        .fields
+        .iter()
+        .filter_map(|f| local_type(module, &f.r#type))
+        .collect();
+      (r.name.as_str(), refs)
+    })
+    .collect();
+
+  let reaches = |from: &str, to: &str| {
+    let mut visited = HashSet::new();
+    let mut stack = vec![from];
+    while let Some(name) = stack.pop() {
+      for &next in refs.get(name).into_iter().flatten() {
+        if next == to {
+          return true;
+        }
+        if visited.insert(next) {
+          stack.push(next);
+        }
+      }
+    }
+    false
+  };
+  refs
+    .keys()
+    .filter(|name| reaches(name, name))
+    .map(|name| name.to_string())
+    .collect()
+}
@@ -0,0 +1,541 @@
+use std::collections::{BTreeSet, HashMap};
+
+use ridl_ir::schema::{Alias, Constant, Definition, Enum, EnumValue, Module, Record, Union, Value};
+use ridl_ir::types::Type;
+
+use crate::case::{lower_camel_case, snake_case};
+use crate::{CodegenOptions, Int64Json};
+
+/// The reserved words, which are not allowed as identifiers.
+const KEYWORDS: &[&str] = &[
+  "assert", "await", "break", "case", "catch", "class", "const", "continue", "default", "do",
+  "else", "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+  "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void", "while",
+  "with", "yield",
+];
+
+/// The library of a namespace, which names its file, e.g. `ek_course` of `ek.course`.
+pub fn library_name(namespace: &str) -> String {
+  match snake_case(namespace) {
+    name if name.is_empty() => "index".to_string(),
+    name => name,
+  }
+}
+
+/// The prefix of an imported library, e.g. `$ek_course`, which the names of the idl can not shadow.
+fn import_prefix(library: &str) -> String {
+  format!("${}", library)
+}
+
+/// The names are kept as the ts target does, the keywords get a `$` suffix.
+fn ident(name: &str) -> String {
+  match KEYWORDS.contains(&name) {
+    true => format!("{}$", name),
+    false => name.to_string(),
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  doc
+    .iter()
+    .flat_map(|d| d.lines())
+    .map(|line| format!("{}/// {}\n", indent, line).replace("/// \n", "///\n"))
+    .collect()
+}
+
+fn string_literal(s: &str) -> String {
+  format!("{:?}", s).replace('$', "\\$")
+}
+
+/// The local definitions the conversions of the json depend on.
+enum Local<'a> {
+  Alias(&'a Type),
+  Union,
+  Other,
+}
+
+/// Generates the dart of a module, the imported libraries are collected along.
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  locals: HashMap<&'a str, Local<'a>>,
+  imports: BTreeSet<String>,
+  warnings: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+  fn local(&self, r#type: &Type) -> Option<&Local<'a>> {
+    match r#type {
+      Type::Ref(r) if r.namespace.is_empty() || r.namespace.join(".") == self.module.namespace => {
+        self.locals.get(r.name.as_str())
+      }
+      _ => None,
+    }
+  }
+
+  fn int64_as_string(&self) -> bool {
+    self.options.int64_json == Int64Json::String
+  }
+
+  fn type_str(&mut self, r#type: &Type) -> String {
+    match r#type {
+      Type::Bool => "bool".to_string(),
+      Type::Int8
+      | Type::Int16
+      | Type::Int32
+      | Type::Int64
+      | Type::UInt8
+      | Type::UInt16
+      | Type::UInt32
+      | Type::UInt64 => "int".to_string(),
+      Type::Float32 | Type::Float64 => "double".to_string(),
+      Type::String
+      | Type::Bytes
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => "String".to_string(),
+      Type::Any => "dynamic".to_string(),
+      Type::Void => "Null".to_string(),
+      Type::List(item) => format!("List<{}>", self.type_str(item)),
+      Type::Set(item) => format!("Set<{}>", self.type_str(item)),
+      Type::Map(key, value) => format!("Map<{}, {}>", self.type_str(key), self.type_str(value)),
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          r.name.clone()
+        } else {
+          let library = library_name(&namespace);
+          let prefix = import_prefix(&library);
+          self.imports.insert(library);
+          format!("{}.{}", prefix, r.name)
+        }
+      }
+    }
+  }
+
+  /// The expression decoding the json value `e` of the type. The 64-bit integers are parsed from the
+  /// strings unless they are configured to be numbers, and the types of other namespaces are
+  /// records or enums, which are decoded by their `fromJson`.
+  fn decode(&mut self, e: &str, r#type: &Type) -> String {
+    match r#type {
+      Type::Int64 | Type::UInt64 if self.int64_as_string() => format!("int.parse({} as String)", e),
+      Type::Float32 | Type::Float64 => format!("({} as num).toDouble()", e),
+      Type::Any | Type::Void => e.to_string(),
+      Type::List(item) | Type::Set(item) => {
+        let collect = match r#type {
+          Type::Set(_) => "toSet",
+          _ => "toList",
+        };
+        format!(
+          "({} as List<dynamic>).map((e) => {}).{}()",
+          e,
+          self.decode("e", item),
+          collect
+        )
+      }
+      Type::Map(key, value) => {
+        let key = match **key {
+          Type::String => "k".to_string(),
+          _ => self.decode_key(key),
+        };
+        format!(
+          "({} as Map<String, dynamic>).map((k, v) => MapEntry({}, {}))",
+          e,
+          key,
+          self.decode("v", value)
+        )
+      }
+      Type::Ref(_) => match self.local(r#type) {
+        Some(Local::Alias(t)) => {
+          let t = *t;
+          self.decode(e, t)
+        }
+        Some(Local::Union) => e.to_string(),
+        _ => format!("{}.fromJson({})", self.type_str(r#type), e),
+      },
+      t => format!("{} as {}", e, self.type_str(t)),
+    }
+  }
+
+  /// The keys of json are strings, the integer keys are parsed.
+  fn decode_key(&mut self, key: &Type) -> String {
+    match key {
+      Type::Bool => "k == 'true'".to_string(),
+      Type::Float32 | Type::Float64 => "double.parse(k)".to_string(),
+      t if self.type_str(t) == "int" => "int.parse(k)".to_string(),
+      _ => self.decode("k", key),
+    }
+  }
+
+  /// The expression encoding the value `e` of the type into json, `None` if it is the json already.
+  fn encode(&mut self, e: &str, r#type: &Type) -> Option<String> {
+    match r#type {
+      Type::Int64 | Type::UInt64 if self.int64_as_string() => Some(format!("{}.toString()", e)),
+      Type::List(item) | Type::Set(item) => match (self.encode("e", item), r#type) {
+        (Some(item), _) => Some(format!("{}.map((e) => {}).toList()", e, item)),
+        (None, Type::Set(_)) => Some(format!("{}.toList()", e)),
+        (None, _) => None,
+      },
+      Type::Map(key, value) => {
+        let key = match **key {
+          Type::String => None,
+          _ => Some("k.toString()".to_string()),
+        };
+        match (key, self.encode("v", value)) {
+          (None, None) => None,
+          (key, value) => Some(format!(
+            "{}.map((k, v) => MapEntry({}, {}))",
+            e,
+            key.unwrap_or("k".to_string()),
+            value.unwrap_or("v".to_string())
+          )),
+        }
+      }
+      Type::Ref(_) => match self.local(r#type) {
+        Some(Local::Alias(t)) => {
+          let t = *t;
+          self.encode(e, t)
+        }
+        Some(Local::Union) => None,
+        _ => Some(format!("{}.toJson()", e)),
+      },
+      _ => None,
+    }
+  }
+
+  /// The immutable classes are built by the named params, the optional fields are nullable and
+  /// absent from the json when they are null.
+  fn gen_class(&mut self, record: &Record) -> String {
+    let name = &record.name;
+    let mut fields = String::new();
+    let mut params = String::new();
+    let mut decodes = String::new();
+    let mut encodes = String::new();
+    for f in &record.fields {
+      let ident = ident(&f.name);
+      let mut r#type = self.type_str(&f.r#type);
+      let key = string_literal(&f.name);
+      let value = format!("json[{}]", key);
+      let decode = self.decode(&value, &f.r#type);
+      if f.optional {
+        if r#type != "dynamic" {
+          r#type.push('?');
+        }
+        params.push_str(&format!("    this.{},\n", ident));
+        match decode == value {
+          true => decodes.push_str(&format!("        {}: {},\n", ident, value)),
+          false => decodes.push_str(&format!(
+            "        {}: {} == null ? null : {},\n",
+            ident, value, decode
+          )),
+        }
+        let encode = self.encode(&format!("{}!", ident), &f.r#type);
+        encodes.push_str(&format!(
+          "        if ({} != null) {}: {},\n",
+          ident,
+          key,
+          encode.unwrap_or(ident.clone())
+        ));
+      } else {
+        params.push_str(&format!("    required this.{},\n", ident));
+        decodes.push_str(&format!("        {}: {},\n", ident, decode));
+        let encode = self.encode(&ident, &f.r#type);
+        encodes.push_str(&format!(
+          "        {}: {},\n",
+          key,
+          encode.unwrap_or(ident.clone())
+        ));
+      }
+      fields.push_str(&gen_doc(&f.doc, "  "));
+      fields.push_str(&format!("  final {} {};\n", r#type, ident));
+    }
+
+    let mut code = gen_doc(&record.doc, "");
+    code.push_str(&format!("class {} {{\n", name));
+    if record.fields.is_empty() {
+      code.push_str(&format!(
+        "  const {name}();\n\n  factory {name}.fromJson(Map<String, dynamic> json) => const {name}();\n\n  Map<String, dynamic> toJson() => {{}};\n}}\n"
+      ));
+      return code;
+    }
+    code.push_str(&format!(
+      "{fields}\n  const {name}({{\n{params}  }});\n\n  factory {name}.fromJson(Map<String, dynamic> json) => {name}(\n{decodes}      );\n\n  Map<String, dynamic> toJson() => {{\n{encodes}      }};\n}}\n"
+    ));
+    code
+  }
+
+  /// The enums carry the values of the idl, which are the json of the enums.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    let is_int = e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(_)));
+    let value_type = if is_int { "int" } else { "String" };
+    let entries: Vec<String> = e
+      .variants
+      .iter()
+      .map(|v| {
+        let value = match &v.value {
+          EnumValue::Int(value) if is_int => value.to_string(),
+          EnumValue::Int(_) => string_literal(&v.name),
+          EnumValue::String(value) => string_literal(value),
+        };
+        // the members of the enums are not allowed as the names of their entries
+        let entry = match lower_camel_case(&v.name) {
+          entry if ["index", "values"].contains(&entry.as_str()) => format!("{}$", entry),
+          entry => ident(&entry),
+        };
+        format!("  {}({})", entry, value)
+      })
+      .collect();
+    format!(
+      "{doc}enum {name} {{\n{entries};\n\n  const {name}(this.value);\n\n  final {value_type} value;\n\n  static {name} fromJson(dynamic value) =>\n      values.firstWhere((e) => e.value == value);\n\n  {value_type} toJson() => value;\n}}\n",
+      doc = gen_doc(&e.doc, ""),
+      name = e.name,
+      entries = entries.join(",\n"),
+    )
+  }
+
+  /// Dart has no unions, the value of an union is its json.
+  fn gen_union(&mut self, union: &Union) -> String {
+    self.warnings.push(format!(
+      "`{}` is a union, dart has no unions and it is its json as is",
+      union.name
+    ));
+    let variants: Vec<String> = union.variants.iter().map(|t| self.type_str(t)).collect();
+    format!(
+      "{}/// One of {}.\ntypedef {} = dynamic;\n",
+      gen_doc(&union.doc, ""),
+      variants.join(", "),
+      union.name
+    )
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    format!(
+      "{}typedef {} = {};\n",
+      gen_doc(&alias.doc, ""),
+      alias.name,
+      self.type_str(&alias.r#type)
+    )
+  }
+
+  fn gen_constant(&mut self, constant: &Constant) -> Option<String> {
+    let Some(value) = value_str(&constant.value) else {
+      self.warnings.push(format!(
+        "constant `{}` refers to other values, it is skipped",
+        constant.name
+      ));
+      return None;
+    };
+    Some(format!(
+      "{}const {} {} = {};\n",
+      gen_doc(&constant.doc, ""),
+      self.type_str(&constant.r#type),
+      constant.name,
+      value
+    ))
+  }
+}
+
+fn value_str(value: &Value) -> Option<String> {
+  match value {
+    Value::Bool(b) => Some(b.to_string()),
+    Value::Int(i) => Some(i.to_string()),
+    Value::Float(f) => Some(format!("{:?}", f)),
+    Value::String(s) => Some(string_literal(s)),
+    Value::List(items) => {
+      let items: Option<Vec<String>> = items.iter().map(value_str).collect();
+      items.map(|items| format!("[{}]", items.join(", ")))
+    }
+    Value::Map(entries) => {
+      let entries: Option<Vec<String>> = entries
+        .iter()
+        .map(|(k, v)| Some(format!("{}: {}", value_str(k)?, value_str(v)?)))
+        .collect();
+      entries.map(|entries| format!("{{{}}}", entries.join(", ")))
+    }
+    Value::Ref(_) => None,
+  }
+}
+
+/// Generate the dart classes of a module with their `fromJson` and `toJson`, the libraries of other
+/// namespaces are imported with their names as prefixes, e.g. `$ek_user`.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let locals = module
+    .definitions
+    .iter()
+    .map(|d| {
+      let local = match d {
+        Definition::Alias(a) => Local::Alias(&a.r#type),
+        Definition::Union(_) => Local::Union,
+        _ => Local::Other,
+      };
+      (d.name(), local)
+    })
+    .collect();
+  let mut generator = Generator {
+    module,
+    options,
+    locals,
+    imports: BTreeSet::new(),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(generator.gen_class(r)),
+      Definition::Enum(e) => Some(generator.gen_enum(e)),
+      Definition::Union(u) => Some(generator.gen_union(u)),
+      Definition::Alias(a) => Some(generator.gen_alias(a)),
+      Definition::Constant(c) => generator.gen_constant(c),
+      Definition::Service(_) => None,
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+
+  let mut code = format!("// namespace {}\n\n", module.namespace);
+  if !generator.imports.is_empty() {
+    for library in &generator.imports {
+      code.push_str(&format!(
+        "import '{}.dart' as {};\n",
+        library,
+        import_prefix(library)
+      ));
+    }
+    code.push('\n');
+  }
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{
+    Alias, Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The title.".to_string()),
+              ..Field::new("course_title", Type::String, true)
+            },
+            Field::new("score", Type::Float64, false),
+            Field::new("lesson_ids", Type::local("Ids"), false),
+            Field::new(
+              "grades",
+              Type::map(Type::Int32, Type::local("Status")),
+              true,
+            ),
+            Field::new(
+              "teacher",
+              Type::refer("Teacher", vec!["ek".to_string(), "user".to_string()]),
+              false,
+            ),
+          ],
+          doc: Some("A course.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("NOT_STARTED", 0), ("FINISHED", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Alias(Alias {
+          name: "Ids".to_string(),
+          r#type: Type::set(Type::Int64),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "PRICE".to_string(),
+          r#type: Type::String,
+          value: Value::String("$1".to_string()),
+          doc: None,
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r#"// namespace ek.course
+
+import 'ek_user.dart' as $ek_user;
+
+/// A course.
+class Course {
+  final int id;
+  /// The title.
+  final String? course_title;
+  final double score;
+  final Ids lesson_ids;
+  final Map<int, Status>? grades;
+  final $ek_user.Teacher teacher;
+
+  const Course({
+    required this.id,
+    this.course_title,
+    required this.score,
+    required this.lesson_ids,
+    this.grades,
+    required this.teacher,
+  });
+
+  factory Course.fromJson(Map<String, dynamic> json) => Course(
+        id: int.parse(json["id"] as String),
+        course_title: json["course_title"] == null ? null : json["course_title"] as String,
+        score: (json["score"] as num).toDouble(),
+        lesson_ids: (json["lesson_ids"] as List<dynamic>).map((e) => int.parse(e as String)).toSet(),
+        grades: json["grades"] == null ? null : (json["grades"] as Map<String, dynamic>).map((k, v) => MapEntry(int.parse(k), Status.fromJson(v))),
+        teacher: $ek_user.Teacher.fromJson(json["teacher"]),
+      );
+
+  Map<String, dynamic> toJson() => {
+        "id": id.toString(),
+        if (course_title != null) "course_title": course_title,
+        "score": score,
+        "lesson_ids": lesson_ids.map((e) => e.toString()).toList(),
+        if (grades != null) "grades": grades!.map((k, v) => MapEntry(k.toString(), v.toJson())),
+        "teacher": teacher.toJson(),
+      };
+}
+
+enum Status {
+  notStarted(0),
+  finished(2);
+
+  const Status(this.value);
+
+  final int value;
+
+  static Status fromJson(dynamic value) =>
+      values.firstWhere((e) => e.value == value);
+
+  int toJson() => value;
+}
+
+typedef Ids = Set<int>;
+
+const String PRICE = "\$1";
+"#
+    );
+  }
+}
@@ -0,0 +1,547 @@
+use std::collections::{BTreeSet, HashSet};
+
+use ridl_ir::schema::{
+  Alias, Constant, Definition, Enum, EnumValue, Field, Module, Record, Union, Value,
+};
+use ridl_ir::types::Type;
+
+use crate::case::{snake_case, upper_camel_case};
+use crate::{CodegenOptions, Int64Json};
+
+/// The package of a namespace, which is the folder and the name of its file, e.g. `ek_course` of
+/// `ek.course`.
+pub fn package_name(namespace: &str) -> String {
+  match snake_case(namespace) {
+    name if name.is_empty() => "index".to_string(),
+    name => name,
+  }
+}
+
+/// The exported identifier of a name, the identifiers can not start with digits.
+fn ident(name: &str) -> String {
+  let ident = upper_camel_case(name);
+  match ident.starts_with(|c: char| c.is_ascii_digit()) {
+    true => format!("X{}", ident),
+    false => ident,
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  doc
+    .iter()
+    .flat_map(|d| d.lines())
+    .map(|line| format!("{}// {}\n", indent, line).replace("// \n", "//\n"))
+    .collect()
+}
+
+/// Pad the columns of the lines to the widest one, as gofmt aligns the fields and the constants.
+fn align(rows: &[Vec<String>]) -> Vec<String> {
+  let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+  let widths: Vec<usize> = (0..columns)
+    .map(|i| {
+      rows
+        .iter()
+        .filter(|r| r.len() > i + 1)
+        .map(|r| r[i].len())
+        .max()
+        .unwrap_or(0)
+    })
+    .collect();
+  rows
+    .iter()
+    .map(|r| {
+      let last = r.len() - 1;
+      r.iter()
+        .enumerate()
+        .map(|(i, cell)| match i == last {
+          true => cell.clone(),
+          false => format!("{:width$} ", cell, width = widths[i]),
+        })
+        .collect()
+    })
+    .collect()
+}
+
+fn holds_int64(r#type: &Type) -> bool {
+  match r#type {
+    Type::Int64 | Type::UInt64 => true,
+    Type::List(item) | Type::Set(item) => holds_int64(item),
+    Type::Map(key, value) => holds_int64(key) || holds_int64(value),
+    _ => false,
+  }
+}
+
+/// Generates the go of a module, the imports the code needs are collected along.
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  imports: BTreeSet<String>,
+  warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+  fn type_str(&mut self, r#type: &Type) -> String {
+    match r#type {
+      Type::Bool => "bool".to_string(),
+      Type::Int8 => "int8".to_string(),
+      Type::Int16 => "int16".to_string(),
+      Type::Int32 => "int32".to_string(),
+      Type::Int64 => "int64".to_string(),
+      Type::UInt8 => "uint8".to_string(),
+      Type::UInt16 => "uint16".to_string(),
+      Type::UInt32 => "uint32".to_string(),
+      Type::UInt64 => "uint64".to_string(),
+      Type::Float32 => "float32".to_string(),
+      Type::Float64 => "float64".to_string(),
+      Type::Timestamp => {
+        self.imports.insert("time".to_string());
+        "time.Time".to_string()
+      }
+      Type::String | Type::Date | Type::Time | Type::Duration | Type::Uuid | Type::Decimal => {
+        "string".to_string()
+      }
+      Type::Bytes => "[]byte".to_string(),
+      Type::Any => "any".to_string(),
+      Type::Void => "struct{}".to_string(),
+      Type::List(item) | Type::Set(item) => format!("[]{}", self.type_str(item)),
+      Type::Map(key, value) => format!("map[{}]{}", self.type_str(key), self.type_str(value)),
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          ident(&r.name)
+        } else {
+          let package = package_name(&namespace);
+          self.imports.insert(match self.options.go_module.as_str() {
+            "" => package.clone(),
+            module => format!("{}/{}", module, package),
+          });
+          format!("{}.{}", package, ident(&r.name))
+        }
+      }
+    }
+  }
+
+  /// The tag of a field, the `go.tag` annotation is appended verbatim and replaces the json tag when
+  /// it has one. The 64-bit integers are quoted by the `string` option of encoding/json.
+  fn gen_tag(&mut self, owner: &str, f: &Field) -> String {
+    let go_tag = f.annotations.get("go.tag").map(|t| t.trim());
+    if go_tag.is_some_and(|t| t.starts_with("json:") || t.contains(" json:")) {
+      return format!("`{}`", go_tag.unwrap());
+    }
+    let mut json = vec![f.name.clone()];
+    if f.optional {
+      json.push("omitempty".to_string());
+    }
+    if self.options.int64_json == Int64Json::String {
+      match &f.r#type {
+        Type::Int64 | Type::UInt64 => json.push("string".to_string()),
+        t if holds_int64(t) => self.warnings.push(format!(
+          "`{}.{}` holds 64-bit integers in a container, they are json numbers in go",
+          owner, f.name
+        )),
+        _ => {}
+      }
+    }
+    let mut tag = format!("json:\"{}\"", json.join(","));
+    if let Some(go_tag) = go_tag.filter(|t| !t.is_empty()) {
+      tag.push(' ');
+      tag.push_str(go_tag);
+    }
+    format!("`{}`", tag)
+  }
+
+  /// The optional fields are pointers, except the types which are nil already.
+  fn gen_struct(&mut self, record: &Record) -> String {
+    let mut rows = Vec::new();
+    let mut docs = Vec::new();
+    for f in &record.fields {
+      let mut r#type = self.type_str(&f.r#type);
+      let nilable = matches!(
+        f.r#type,
+        Type::List(_) | Type::Set(_) | Type::Map(..) | Type::Bytes | Type::Any
+      );
+      if (f.optional
+        || matches!(&f.r#type, Type::Ref(r) if r.namespace.is_empty() && r.name == record.name))
+        && !nilable
+      {
+        r#type = format!("*{}", r#type);
+      }
+      docs.push(gen_doc(&f.doc, "\t"));
+      rows.push(vec![ident(&f.name), r#type, self.gen_tag(&record.name, f)]);
+    }
+    let fields: String = docs
+      .iter()
+      .zip(align(&rows))
+      .map(|(doc, row)| format!("{}\t{}\n", doc, row))
+      .collect();
+    format!(
+      "{}type {} struct {{\n{}}}\n",
+      gen_doc(&record.doc, ""),
+      ident(&record.name),
+      fields
+    )
+  }
+
+  /// The enums are typed constants named after their type, e.g. `StatusFinished`. The int enums have
+  /// `String()` of their names in the idl.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    let name = ident(&e.name);
+    let is_int = e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(_)));
+    let rows: Vec<Vec<String>> = e
+      .variants
+      .iter()
+      .map(|v| {
+        // the int values of a string enum are their names
+        let value = match (&v.value, is_int) {
+          (EnumValue::Int(value), true) => value.to_string(),
+          (EnumValue::Int(_), false) => go_string(&v.name),
+          (EnumValue::String(value), _) => go_string(value),
+        };
+        vec![
+          format!("{}{}", name, ident(&v.name)),
+          name.clone(),
+          "=".to_string(),
+          value,
+        ]
+      })
+      .collect();
+    let underlying = match is_int {
+      true
+        if e
+          .variants
+          .iter()
+          .all(|v| matches!(v.value, EnumValue::Int(i) if i32::try_from(i).is_ok())) =>
+      {
+        "int32"
+      }
+      true => "int64",
+      false => "string",
+    };
+    let mut code = gen_doc(&e.doc, "");
+    code.push_str(&format!("type {} {}\n\nconst (\n", name, underlying));
+    for row in align(&rows) {
+      code.push_str(&format!("\t{}\n", row));
+    }
+    code.push_str(")\n");
+    if is_int {
+      self.imports.insert("fmt".to_string());
+      code.push_str(&format!(
+        "\nfunc (e {}) String() string {{\n\tswitch e {{\n",
+        name
+      ));
+      // the aliases of a value are the same case
+      let mut values = HashSet::new();
+      for v in &e.variants {
+        if let EnumValue::Int(value) = v.value {
+          if values.insert(value) {
+            code.push_str(&format!(
+              "\tcase {}{}:\n\t\treturn {:?}\n",
+              name,
+              ident(&v.name),
+              v.name
+            ));
+          }
+        }
+      }
+      code.push_str(&format!(
+        "\t}}\n\treturn fmt.Sprintf(\"{}(%d)\", {}(e))\n}}\n",
+        name, underlying
+      ));
+    }
+    code
+  }
+
+  /// Go has no unions, the value of an union is any of its variants.
+  fn gen_union(&mut self, union: &Union) -> String {
+    let variants: Vec<String> = union.variants.iter().map(|t| self.type_str(t)).collect();
+    self.warnings.push(format!(
+      "`{}` is a union, go has no unions and it is any",
+      union.name
+    ));
+    format!(
+      "{}// {} is one of {}.\ntype {} = any\n",
+      gen_doc(&union.doc, ""),
+      ident(&union.name),
+      variants.join(", "),
+      ident(&union.name)
+    )
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    format!(
+      "{}type {} = {}\n",
+      gen_doc(&alias.doc, ""),
+      ident(&alias.name),
+      self.type_str(&alias.r#type)
+    )
+  }
+
+  /// The scalars are constants, the lists and maps are variables of composite literals.
+  fn gen_constant(&mut self, constant: &Constant) -> Option<String> {
+    let Some(value) = value_str(&constant.value) else {
+      self.warnings.push(format!(
+        "constant `{}` refers to other values, it is skipped",
+        constant.name
+      ));
+      return None;
+    };
+    let keyword = match constant.value {
+      Value::List(_) | Value::Map(_) => "var",
+      _ => "const",
+    };
+    let r#type = self.type_str(&constant.r#type);
+    let value = match keyword {
+      "var" => format!("{}{}", r#type, value),
+      _ => format!("{}({})", r#type, value),
+    };
+    Some(format!(
+      "{}{} {} = {}\n",
+      gen_doc(&constant.doc, ""),
+      keyword,
+      ident(&constant.name),
+      value
+    ))
+  }
+}
+
+/// The interpreted string literal of go, e.g. `"a\tb"`, the other control characters are `\u`
+/// escapes and the unicode is kept as it is in the utf-8 source.
+fn go_string(s: &str) -> String {
+  let mut literal = String::from('"');
+  for c in s.chars() {
+    match c {
+      '"' => literal.push_str("\\\""),
+      '\\' => literal.push_str("\\\\"),
+      '\n' => literal.push_str("\\n"),
+      '\r' => literal.push_str("\\r"),
+      '\t' => literal.push_str("\\t"),
+      c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+      c => literal.push(c),
+    }
+  }
+  literal.push('"');
+  literal
+}
+
+/// The value of a constant, the items of composite literals elide their types.
+fn value_str(value: &Value) -> Option<String> {
+  match value {
+    Value::Bool(b) => Some(b.to_string()),
+    Value::Int(i) => Some(i.to_string()),
+    Value::Float(f) => Some(format!("{:?}", f)),
+    Value::String(s) => Some(go_string(s)),
+    Value::List(items) => {
+      let items: Option<Vec<String>> = items.iter().map(value_str).collect();
+      items.map(|items| format!("{{{}}}", items.join(", ")))
+    }
+    Value::Map(entries) => {
+      let entries: Option<Vec<String>> = entries
+        .iter()
+        .map(|(k, v)| Some(format!("{}: {}", value_str(k)?, value_str(v)?)))
+        .collect();
+      entries.map(|entries| format!("{{{}}}", entries.join(", ")))
+    }
+    Value::Ref(_) => None,
+  }
+}
+
+/// Generate the go structs of a module as a package named after the namespace. The types of other
+/// namespaces are imported from their packages under the go module of the options.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    options,
+    imports: BTreeSet::new(),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(generator.gen_struct(r)),
+      Definition::Enum(e) => Some(generator.gen_enum(e)),
+      Definition::Union(u) => Some(generator.gen_union(u)),
+      Definition::Alias(a) => Some(generator.gen_alias(a)),
+      Definition::Constant(c) => generator.gen_constant(c),
+      Definition::Service(_) => None,
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+
+  let mut code = format!(
+    "// namespace {}\n\npackage {}\n\n",
+    module.namespace,
+    package_name(&module.namespace)
+  );
+  match generator.imports.len() {
+    0 => {}
+    1 => code.push_str(&format!(
+      "import {:?}\n\n",
+      generator.imports.first().unwrap()
+    )),
+    _ => {
+      code.push_str("import (\n");
+      for import in &generator.imports {
+        code.push_str(&format!("\t{:?}\n", import));
+      }
+      code.push_str(")\n\n");
+    }
+  }
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let tagged = |field: Field, tag: &str| {
+      let mut field = field;
+      field
+        .annotations
+        .insert("go.tag".to_string(), tag.to_string());
+      field
+    };
+    let module = Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The title.".to_string()),
+              ..Field::new("course_title", Type::String, false)
+            },
+            tagged(
+              Field::new("offset", Type::Int32, true),
+              "validate:\"min=0\"",
+            ),
+            tagged(Field::new("count", Type::Int32, false), "json:\"cnt\""),
+            Field::new("lessonIds", Type::list(Type::Int64), true),
+            Field::new("status", Type::local("Status"), true),
+            Field::new("startAt", Type::Timestamp, false),
+            Field::new(
+              "teacher",
+              Type::refer("Teacher", vec!["ek".to_string(), "user".to_string()]),
+              false,
+            ),
+          ],
+          doc: Some("A course.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("NOT_STARTED", 0), ("FINISHED", 2), ("DONE", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Union(Union {
+          name: "Id".to_string(),
+          variants: vec![Type::Int64, Type::local("Course")],
+          ..Union::default()
+        }),
+        Definition::Constant(Constant {
+          name: "GRADES".to_string(),
+          r#type: Type::map(Type::Int32, Type::String),
+          value: Value::Map(vec![(Value::Int(1), Value::String("one".to_string()))]),
+          doc: None,
+        }),
+      ],
+    };
+    let options = CodegenOptions {
+      go_module: "example.com/api".to_string(),
+      ..CodegenOptions::default()
+    };
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module, &options, &mut warnings),
+      r#"// namespace ek.course
+
+package ek_course
+
+import (
+	"example.com/api/ek_user"
+	"fmt"
+	"time"
+)
+
+// A course.
+type Course struct {
+	Id          int64           `json:"id,string"`
+	// The title.
+	CourseTitle string          `json:"course_title"`
+	Offset      *int32          `json:"offset,omitempty" validate:"min=0"`
+	Count       int32           `json:"cnt"`
+	LessonIds   []int64         `json:"lessonIds,omitempty"`
+	Status      *Status         `json:"status,omitempty"`
+	StartAt     time.Time       `json:"startAt"`
+	Teacher     ek_user.Teacher `json:"teacher"`
+}
+
+type Status int32
+
+const (
+	StatusNotStarted Status = 0
+	StatusFinished   Status = 2
+	StatusDone       Status = 2
+)
+
+func (e Status) String() string {
+	switch e {
+	case StatusNotStarted:
+		return "NOT_STARTED"
+	case StatusFinished:
+		return "FINISHED"
+	}
+	return fmt.Sprintf("Status(%d)", int32(e))
+}
+
+// Id is one of int64, Course.
+type Id = any
+
+var Grades = map[int32]string{1: "one"}
+"#
+    );
+    assert_eq!(
+      warnings,
+      [
+        "`Course.lessonIds` holds 64-bit integers in a container, they are json numbers in go",
+        "`Id` is a union, go has no unions and it is any"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_codegen_string_constant() {
+    let module = Module {
+      namespace: "foo".to_string(),
+      definitions: vec![Definition::Constant(Constant {
+        name: "GREETING".to_string(),
+        r#type: Type::String,
+        value: Value::String("h\u{e9}llo \u{4e16}\u{754c}\u{1}\u{85}\n\"$\\".to_string()),
+        doc: None,
+      })],
+    };
+    let code = codegen(&module, &CodegenOptions::default(), &mut Vec::new());
+    assert!(
+      code.contains(r#""héllo 世界\u0001\u0085\n\"$\\""#),
+      "{}",
+      code
+    );
+  }
+}
@@ -0,0 +1,346 @@
+use serde_json::{json, Map, Value as Json};
+
+use ridl_ir::schema::{Definition, Enum, EnumValue, Module, Record, Union, Value};
+use ridl_ir::types::Type;
+
+use crate::{CodegenOptions, Int64Json};
+
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Where the definitions of a namespace are, which the refs point to.
+pub struct Defs {
+  /// The json pointer of the definitions in the document, e.g. `$defs`.
+  pub pointer: &'static str,
+  /// The extension of the document file named after the namespace, e.g. `.schema.json`.
+  pub extension: &'static str,
+}
+
+const DEFS: Defs = Defs {
+  pointer: "$defs",
+  extension: ".schema.json",
+};
+
+impl Defs {
+  pub fn file_name(&self, namespace: &str) -> String {
+    format!("{}{}", namespace, self.extension)
+  }
+
+  fn refer(&self, name: &str, ref_namespace: &[String], namespace: &str) -> Json {
+    let ref_namespace = ref_namespace.join(".");
+    let file = if ref_namespace.is_empty() || ref_namespace == namespace {
+      String::new()
+    } else {
+      self.file_name(&ref_namespace)
+    };
+    json!({"$ref": format!("{}#/{}/{}", file, self.pointer, name)})
+  }
+}
+
+fn with_doc(mut schema: Json, doc: &Option<String>) -> Json {
+  if let (Some(doc), Some(object)) = (doc, schema.as_object_mut()) {
+    object.insert("description".to_string(), json!(doc));
+  }
+  schema
+}
+
+fn integer(min: i64, max: u64) -> Json {
+  json!({"type": "integer", "minimum": min, "maximum": max})
+}
+
+/// The schema of a type by the json mapping. The 64-bit integers are strings of digits or numbers
+/// by `options.int64_json`, as the other targets present them.
+pub fn type_schema(r#type: &Type, namespace: &str, defs: &Defs, options: &CodegenOptions) -> Json {
+  let type_schema = |t: &Type| type_schema(t, namespace, defs, options);
+  match r#type {
+    Type::Bool => json!({"type": "boolean"}),
+    Type::Int8 => integer(i8::MIN.into(), i8::MAX as u64),
+    Type::Int16 => integer(i16::MIN.into(), i16::MAX as u64),
+    Type::Int32 => integer(i32::MIN.into(), i32::MAX as u64),
+    Type::UInt8 => integer(0, u8::MAX.into()),
+    Type::UInt16 => integer(0, u16::MAX.into()),
+    Type::UInt32 => integer(0, u32::MAX.into()),
+    Type::Int64 => match options.int64_json {
+      Int64Json::String => json!({"type": "string", "pattern": "^-?[0-9]+$"}),
+      Int64Json::Number => integer(i64::MIN, i64::MAX as u64),
+    },
+    Type::UInt64 => match options.int64_json {
+      Int64Json::String => json!({"type": "string", "pattern": "^[0-9]+$"}),
+      Int64Json::Number => integer(0, u64::MAX),
+    },
+    Type::Float32 | Type::Float64 => json!({"type": "number"}),
+    Type::String | Type::Decimal => json!({"type": "string"}),
+    Type::Bytes => json!({"type": "string", "contentEncoding": "base64"}),
+    Type::Timestamp => json!({"type": "string", "format": "date-time"}),
+    Type::Date => json!({"type": "string", "format": "date"}),
+    Type::Time => json!({"type": "string", "format": "time"}),
+    Type::Duration => json!({"type": "string", "format": "duration"}),
+    Type::Uuid => json!({"type": "string", "format": "uuid"}),
+    Type::Any => json!({}),
+    Type::Void => json!({"type": "null"}),
+    Type::List(item) => json!({"type": "array", "items": type_schema(item)}),
+    Type::Set(item) => json!({
+      "type": "array",
+      "items": type_schema(item),
+      "uniqueItems": true
+    }),
+    Type::Map(key, value) => {
+      let mut schema = json!({
+        "type": "object",
+        "additionalProperties": type_schema(value)
+      });
+      // the keys of json are strings, the integer keys are their digits
+      if let Some(pattern) = match **key {
+        Type::Int8 | Type::Int16 | Type::Int32 | Type::Int64 => Some("^-?[0-9]+$"),
+        Type::UInt8 | Type::UInt16 | Type::UInt32 | Type::UInt64 => Some("^[0-9]+$"),
+        _ => None,
+      } {
+        schema["propertyNames"] = json!({ "pattern": pattern });
+      }
+      schema
+    }
+    Type::Ref(r) => defs.refer(&r.name, &r.namespace, namespace),
+  }
+}
+
+fn record_schema(record: &Record, namespace: &str, defs: &Defs, options: &CodegenOptions) -> Json {
+  let mut properties = Map::new();
+  let mut required = Vec::new();
+  for f in &record.fields {
+    properties.insert(
+      f.name.clone(),
+      with_doc(type_schema(&f.r#type, namespace, defs, options), &f.doc),
+    );
+    if !f.optional {
+      required.push(json!(f.name));
+    }
+  }
+  let mut schema = json!({"type": "object", "properties": properties});
+  if !required.is_empty() {
+    schema["required"] = Json::Array(required);
+  }
+  with_doc(schema, &record.doc)
+}
+
+fn enum_schema(e: &Enum) -> Json {
+  let values: Vec<Json> = e
+    .variants
+    .iter()
+    .map(|v| match &v.value {
+      EnumValue::Int(i) => json!(i),
+      EnumValue::String(s) => json!(s),
+    })
+    .collect();
+  with_doc(json!({ "enum": values }), &e.doc)
+}
+
+fn union_schema(union: &Union, namespace: &str, defs: &Defs, options: &CodegenOptions) -> Json {
+  let variants: Vec<Json> = union
+    .variants
+    .iter()
+    .map(|t| type_schema(t, namespace, defs, options))
+    .collect();
+  with_doc(json!({ "oneOf": variants }), &union.doc)
+}
+
+fn value_json(value: &Value) -> Json {
+  match value {
+    Value::Bool(b) => json!(b),
+    Value::Int(i) => json!(i),
+    Value::Float(f) => json!(f),
+    Value::String(s) | Value::Ref(s) => json!(s),
+    Value::List(items) => Json::Array(items.iter().map(value_json).collect()),
+    Value::Map(entries) => Json::Object(
+      entries
+        .iter()
+        .map(|(k, v)| {
+          let key = match value_json(k) {
+            Json::String(s) => s,
+            k => k.to_string(),
+          };
+          (key, value_json(v))
+        })
+        .collect(),
+    ),
+  }
+}
+
+/// The schemas of the types of a module keyed by their names, the services have no values to
+/// validate.
+pub fn definition_schemas(
+  module: &Module,
+  defs: &Defs,
+  options: &CodegenOptions,
+) -> Map<String, Json> {
+  let namespace = module.namespace.as_str();
+  let mut schemas = Map::new();
+  for d in &module.definitions {
+    let schema = match d {
+      Definition::Record(r) => record_schema(r, namespace, defs, options),
+      Definition::Enum(e) => enum_schema(e),
+      Definition::Union(u) => union_schema(u, namespace, defs, options),
+      Definition::Alias(a) => with_doc(type_schema(&a.r#type, namespace, defs, options), &a.doc),
+      Definition::Constant(c) => with_doc(json!({ "const": value_json(&c.value) }), &c.doc),
+      Definition::Service(_) => continue,
+    };
+    schemas.insert(d.name().to_string(), schema);
+  }
+  schemas
+}
+
+/// Generate the json schema of a module, every type is a definition in `$defs` that is referred by
+/// `#/$defs/<name>`, or `<namespace>.schema.json#/$defs/<name>` from other namespaces.
+pub fn codegen(module: &Module, options: &CodegenOptions, _warnings: &mut Vec<String>) -> String {
+  let namespace = module.namespace.as_str();
+  let stem = if namespace.is_empty() {
+    "index"
+  } else {
+    namespace
+  };
+  let document = json!({
+    "$schema": DRAFT,
+    "$id": DEFS.file_name(stem),
+    "$defs": definition_schemas(module, &DEFS, options)
+  });
+  format!("{}\n", serde_json::to_string_pretty(&document).unwrap())
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::{CodegenOptions, Int64Json};
+  use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union};
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The grade.".to_string()),
+              ..Field::new("grade", Type::local("Grade"), true)
+            },
+            Field::new("tags", Type::set(Type::String), false),
+            Field::new("scores", Type::map(Type::Int32, Type::Float64), true),
+            Field::new("base", Type::refer("Base", vec!["base".to_string()]), true),
+          ],
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Grade".to_string(),
+          variants: [("One", 1), ("Two", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          ..Enum::default()
+        }),
+        Definition::Union(Union {
+          name: "Id".to_string(),
+          variants: vec![Type::Uuid, Type::UInt8],
+          ..Union::default()
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "course.schema.json",
+  "$defs": {
+    "Course": {
+      "type": "object",
+      "properties": {
+        "id": {
+          "type": "string",
+          "pattern": "^-?[0-9]+$"
+        },
+        "grade": {
+          "$ref": "#/$defs/Grade",
+          "description": "The grade."
+        },
+        "tags": {
+          "type": "array",
+          "items": {
+            "type": "string"
+          },
+          "uniqueItems": true
+        },
+        "scores": {
+          "type": "object",
+          "additionalProperties": {
+            "type": "number"
+          },
+          "propertyNames": {
+            "pattern": "^-?[0-9]+$"
+          }
+        },
+        "base": {
+          "$ref": "base.schema.json#/$defs/Base"
+        }
+      },
+      "required": [
+        "id",
+        "tags"
+      ]
+    },
+    "Grade": {
+      "enum": [
+        1,
+        2
+      ]
+    },
+    "Id": {
+      "oneOf": [
+        {
+          "type": "string",
+          "format": "uuid"
+        },
+        {
+          "type": "integer",
+          "minimum": 0,
+          "maximum": 255
+        }
+      ]
+    }
+  }
+}
+"##
+    );
+  }
+
+  #[test]
+  fn test_codegen_int64_number() {
+    let module = Module {
+      namespace: "course".to_string(),
+      definitions: vec![Definition::Record(Record {
+        name: "Course".to_string(),
+        fields: vec![
+          Field::new("id", Type::Int64, false),
+          Field::new("ids", Type::list(Type::UInt64), false),
+        ],
+        ..Record::default()
+      })],
+    };
+    let options = CodegenOptions {
+      int64_json: Int64Json::Number,
+      ..CodegenOptions::default()
+    };
+    let document: serde_json::Value =
+      serde_json::from_str(&codegen(&module, &options, &mut Vec::new())).unwrap();
+    let properties = &document["$defs"]["Course"]["properties"];
+    assert_eq!(
+      properties["id"],
+      serde_json::json!({"type": "integer", "minimum": i64::MIN, "maximum": i64::MAX})
+    );
+    assert_eq!(
+      properties["ids"]["items"],
+      serde_json::json!({"type": "integer", "minimum": 0, "maximum": u64::MAX})
+    );
+  }
+}
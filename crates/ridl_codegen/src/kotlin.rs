@@ -0,0 +1,513 @@
+use std::collections::BTreeSet;
+
+use ridl_ir::schema::{Alias, Constant, Definition, Enum, EnumValue, Module, Record, Union, Value};
+use ridl_ir::types::Type;
+
+use crate::case::{lower_camel_case, upper_camel_case};
+use crate::{CodegenOptions, Int64Json};
+
+const KEYWORDS: &[&str] = &[
+  "as",
+  "break",
+  "class",
+  "continue",
+  "do",
+  "else",
+  "false",
+  "for",
+  "fun",
+  "if",
+  "in",
+  "interface",
+  "is",
+  "null",
+  "object",
+  "package",
+  "return",
+  "super",
+  "this",
+  "throw",
+  "true",
+  "try",
+  "typealias",
+  "typeof",
+  "val",
+  "var",
+  "when",
+  "while",
+];
+
+/// The file of a namespace in the folders of its package, e.g. `ek/course/Course` of `ek.course`.
+pub fn file_stem(namespace: &str) -> String {
+  match namespace.rsplit('.').next() {
+    _ if namespace.is_empty() => "Index".to_string(),
+    last => format!(
+      "{}/{}",
+      namespace.replace('.', "/"),
+      upper_camel_case(last.unwrap_or_default())
+    ),
+  }
+}
+
+fn ident(name: &str) -> String {
+  let ident = lower_camel_case(name);
+  match KEYWORDS.contains(&ident.as_str()) {
+    true => format!("`{}`", ident),
+    false => ident,
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  match doc.as_deref().map(|d| d.lines().collect::<Vec<&str>>()) {
+    None => String::new(),
+    Some(lines) if lines.len() == 1 => format!("{}/** {} */\n", indent, lines[0]),
+    Some(lines) => {
+      let mut code = format!("{}/**\n", indent);
+      for line in lines {
+        code.push_str(&format!("{} * {}\n", indent, line).replace(" * \n", " *\n"));
+      }
+      code.push_str(&format!("{} */\n", indent));
+      code
+    }
+  }
+}
+
+/// Generates the kotlin of a module, the imports the code needs are collected along.
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  imports: BTreeSet<&'static str>,
+  warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+  /// The type of kotlin, the longs of the serialized types are annotated to be strings by the
+  /// options.
+  fn type_str(&mut self, r#type: &Type, context: &str, serial: bool) -> String {
+    match r#type {
+      Type::Bool => "Boolean".to_string(),
+      Type::Int8 => "Byte".to_string(),
+      Type::Int16 => "Short".to_string(),
+      Type::Int32 => "Int".to_string(),
+      Type::Int64 | Type::UInt64 => {
+        if let Type::UInt64 = r#type {
+          self.warnings.push(format!(
+            "`{}` is u64, it is emitted as Long of kotlin",
+            context
+          ));
+        }
+        // kotlinx presents the longs as numbers, unless they are serialized as strings
+        match self.options.int64_json {
+          Int64Json::String if serial => {
+            self
+              .imports
+              .insert("kotlinx.serialization.builtins.LongAsStringSerializer");
+            "@Serializable(with = LongAsStringSerializer::class) Long".to_string()
+          }
+          _ => "Long".to_string(),
+        }
+      }
+      Type::UInt8 => "UByte".to_string(),
+      Type::UInt16 => "UShort".to_string(),
+      Type::UInt32 => "UInt".to_string(),
+      Type::Float32 => "Float".to_string(),
+      Type::Float64 => "Double".to_string(),
+      Type::String
+      | Type::Bytes
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => "String".to_string(),
+      Type::Any => {
+        self
+          .imports
+          .insert("kotlinx.serialization.json.JsonElement");
+        "JsonElement".to_string()
+      }
+      Type::Void => "Unit".to_string(),
+      Type::List(item) => format!("List<{}>", self.type_str(item, context, serial)),
+      Type::Set(item) => format!("Set<{}>", self.type_str(item, context, serial)),
+      Type::Map(key, value) => format!(
+        "Map<{}, {}>",
+        self.type_str(key, context, serial),
+        self.type_str(value, context, serial)
+      ),
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          upper_camel_case(&r.name)
+        } else {
+          format!("{}.{}", namespace, upper_camel_case(&r.name))
+        }
+      }
+    }
+  }
+
+  /// The properties keep their wire names by `@SerialName`, the optional ones are nullable and
+  /// default to null.
+  fn gen_class(&mut self, record: &Record) -> String {
+    let mut code = gen_doc(&record.doc, "");
+    code.push_str("@Serializable\n");
+    if record.fields.is_empty() {
+      code.push_str(&format!("class {}\n", upper_camel_case(&record.name)));
+      return code;
+    }
+    code.push_str(&format!("data class {}(\n", upper_camel_case(&record.name)));
+    for f in &record.fields {
+      let context = format!("{}.{}", record.name, f.name);
+      let name = ident(&f.name);
+      code.push_str(&gen_doc(&f.doc, "    "));
+      if name != f.name {
+        code.push_str(&format!("    @SerialName({})\n", kotlin_string(&f.name)));
+        self.imports.insert("kotlinx.serialization.SerialName");
+      }
+      let r#type = self.type_str(&f.r#type, &context, true);
+      match f.optional {
+        true => code.push_str(&format!("    val {}: {}? = null,\n", name, r#type)),
+        false => code.push_str(&format!("    val {}: {},\n", name, r#type)),
+      }
+    }
+    code.push_str(")\n");
+    code
+  }
+
+  /// The int enums are serialized as their values by a serializer of their own, the string enums as
+  /// the serial names of their entries.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    let name = upper_camel_case(&e.name);
+    let is_int = e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(_)));
+    let (value_type, kind) = match e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(i) if i32::try_from(i).is_ok()))
+    {
+      true => ("Int", "INT"),
+      false => ("Long", "LONG"),
+    };
+    let mut code = gen_doc(&e.doc, "");
+    let entries: Vec<String> = e
+      .variants
+      .iter()
+      .map(|v| match &v.value {
+        EnumValue::Int(value) if is_int => match value_type {
+          "Long" => format!("    {}({}L)", v.name, value),
+          _ => format!("    {}({})", v.name, value),
+        },
+        EnumValue::Int(_) => format!(
+          "    @SerialName({:?})\n    {}({:?})",
+          v.name, v.name, v.name
+        ),
+        EnumValue::String(value) => {
+          let value = kotlin_string(value);
+          format!("    @SerialName({})\n    {}({})", value, v.name, value)
+        }
+      })
+      .collect();
+    if !is_int {
+      self.imports.insert("kotlinx.serialization.SerialName");
+      code.push_str(&format!(
+        "@Serializable\nenum class {}(val value: String) {{\n{};\n}}\n",
+        name,
+        entries.join(",\n")
+      ));
+      return code;
+    }
+    for import in [
+      "kotlinx.serialization.KSerializer",
+      "kotlinx.serialization.descriptors.PrimitiveKind",
+      "kotlinx.serialization.descriptors.PrimitiveSerialDescriptor",
+      "kotlinx.serialization.encoding.Decoder",
+      "kotlinx.serialization.encoding.Encoder",
+    ] {
+      self.imports.insert(import);
+    }
+    code.push_str(&format!(
+      r#"@Serializable(with = {name}.Serializer::class)
+enum class {name}(val value: {value_type}) {{
+{entries};
+
+    object Serializer : KSerializer<{name}> {{
+        override val descriptor = PrimitiveSerialDescriptor("{name}", PrimitiveKind.{kind})
+
+        override fun serialize(encoder: Encoder, value: {name}) = encoder.encode{value_type}(value.value)
+
+        override fun deserialize(decoder: Decoder): {name} {{
+            val value = decoder.decode{value_type}()
+            return values().first {{ it.value == value }}
+        }}
+    }}
+}}
+"#,
+      entries = entries.join(",\n"),
+    ));
+    code
+  }
+
+  /// Kotlin has no untagged unions, the value of an union is any json element.
+  fn gen_union(&mut self, union: &Union) -> String {
+    self.warnings.push(format!(
+      "`{}` is a union, kotlin has no untagged unions and it is JsonElement",
+      union.name
+    ));
+    self
+      .imports
+      .insert("kotlinx.serialization.json.JsonElement");
+    let variants: Vec<String> = union
+      .variants
+      .iter()
+      .map(|t| self.type_str(t, &union.name, false))
+      .collect();
+    format!(
+      "{}/** One of {}. */\ntypealias {} = JsonElement\n",
+      gen_doc(&union.doc, ""),
+      variants.join(", "),
+      upper_camel_case(&union.name)
+    )
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    format!(
+      "{}typealias {} = {}\n",
+      gen_doc(&alias.doc, ""),
+      upper_camel_case(&alias.name),
+      self.type_str(&alias.r#type, &alias.name, true)
+    )
+  }
+
+  /// The primitives are `const val`, the lists and maps are `val` of their builders.
+  fn gen_constant(&mut self, constant: &Constant) -> Option<String> {
+    let Some(value) = value_str(&constant.value, &constant.r#type) else {
+      self.warnings.push(format!(
+        "constant `{}` refers to other values, it is skipped",
+        constant.name
+      ));
+      return None;
+    };
+    let keyword = match constant.value {
+      Value::List(_) | Value::Map(_) => "val",
+      _ => "const val",
+    };
+    let r#type = self.type_str(&constant.r#type, &constant.name, false);
+    Some(format!(
+      "{}{} {}: {} = {}\n",
+      gen_doc(&constant.doc, ""),
+      keyword,
+      constant.name,
+      r#type,
+      value
+    ))
+  }
+}
+
+/// The string literal of kotlin, e.g. `"a\$b"`, the `$` is escaped so that it starts no template.
+fn kotlin_string(s: &str) -> String {
+  let mut literal = String::from('"');
+  for c in s.chars() {
+    match c {
+      '"' => literal.push_str("\\\""),
+      '\\' => literal.push_str("\\\\"),
+      '$' => literal.push_str("\\$"),
+      '\n' => literal.push_str("\\n"),
+      '\r' => literal.push_str("\\r"),
+      '\t' => literal.push_str("\\t"),
+      c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+      c => literal.push(c),
+    }
+  }
+  literal.push('"');
+  literal
+}
+
+/// The value of a constant of the type, the longs are suffixed by `L`.
+fn value_str(value: &Value, r#type: &Type) -> Option<String> {
+  let (item, key) = match r#type {
+    Type::List(item) | Type::Set(item) => (item.as_ref(), &Type::Any),
+    Type::Map(key, value) => (value.as_ref(), key.as_ref()),
+    t => (t, t),
+  };
+  match value {
+    Value::Bool(b) => Some(b.to_string()),
+    Value::Int(i) => match r#type {
+      Type::Int64 | Type::UInt64 => Some(format!("{}L", i)),
+      Type::Float32 => Some(format!("{}f", i)),
+      Type::Float64 => Some(format!("{}.0", i)),
+      _ => Some(i.to_string()),
+    },
+    Value::Float(f) => match r#type {
+      Type::Float32 => Some(format!("{:?}f", f)),
+      _ => Some(format!("{:?}", f)),
+    },
+    Value::String(s) => Some(kotlin_string(s)),
+    Value::List(items) => {
+      let items: Option<Vec<String>> = items.iter().map(|v| value_str(v, item)).collect();
+      let builder = match r#type {
+        Type::Set(_) => "setOf",
+        _ => "listOf",
+      };
+      items.map(|items| format!("{}({})", builder, items.join(", ")))
+    }
+    Value::Map(entries) => {
+      let entries: Option<Vec<String>> = entries
+        .iter()
+        .map(|(k, v)| Some(format!("{} to {}", value_str(k, key)?, value_str(v, item)?)))
+        .collect();
+      entries.map(|entries| format!("mapOf({})", entries.join(", ")))
+    }
+    Value::Ref(_) => None,
+  }
+}
+
+/// Generate the kotlinx serializable classes of a module in the package of its namespace, the
+/// types of other namespaces are referred by their qualified names.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    options,
+    imports: BTreeSet::new(),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(generator.gen_class(r)),
+      Definition::Enum(e) => Some(generator.gen_enum(e)),
+      Definition::Union(u) => Some(generator.gen_union(u)),
+      Definition::Alias(a) => Some(generator.gen_alias(a)),
+      Definition::Constant(c) => generator.gen_constant(c),
+      Definition::Service(_) => None,
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+  if blocks.iter().any(|b| b.contains("@Serializable")) {
+    generator
+      .imports
+      .insert("kotlinx.serialization.Serializable");
+  }
+
+  let mut code = format!("// namespace {}\n\n", module.namespace);
+  if !module.namespace.is_empty() {
+    code.push_str(&format!("package {}\n\n", module.namespace));
+  }
+  if !generator.imports.is_empty() {
+    for import in &generator.imports {
+      code.push_str(&format!("import {}\n", import));
+    }
+    code.push('\n');
+  }
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::{codegen, file_stem};
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The title.".to_string()),
+              ..Field::new("course_title", Type::String, true)
+            },
+            Field::new("tags", Type::set(Type::String), false),
+            Field::new("level", Type::local("Level"), true),
+            Field::new(
+              "teacher",
+              Type::refer("Teacher", vec!["ek".to_string(), "user".to_string()]),
+              false,
+            ),
+          ],
+          doc: Some("A course.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Level".to_string(),
+          variants: [("HIGH", "high"), ("LOW", "low")]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::String(value.to_string()),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "LIMITS".to_string(),
+          r#type: Type::map(Type::String, Type::Int64),
+          value: Value::Map(vec![(Value::String("a".to_string()), Value::Int(1))]),
+          doc: None,
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r#"// namespace ek.course
+
+package ek.course
+
+import kotlinx.serialization.SerialName
+import kotlinx.serialization.Serializable
+import kotlinx.serialization.builtins.LongAsStringSerializer
+
+/** A course. */
+@Serializable
+data class Course(
+    val id: @Serializable(with = LongAsStringSerializer::class) Long,
+    /** The title. */
+    @SerialName("course_title")
+    val courseTitle: String? = null,
+    val tags: Set<String>,
+    val level: Level? = null,
+    val teacher: ek.user.Teacher,
+)
+
+@Serializable
+enum class Level(val value: String) {
+    @SerialName("high")
+    HIGH("high"),
+    @SerialName("low")
+    LOW("low");
+}
+
+val LIMITS: Map<String, Long> = mapOf("a" to 1L)
+"#
+    );
+    assert_eq!(file_stem("ek.course"), "ek/course/Course");
+    assert_eq!(file_stem(""), "Index");
+  }
+
+  #[test]
+  fn test_codegen_string_constant() {
+    let module = Module {
+      namespace: "foo".to_string(),
+      definitions: vec![Definition::Constant(Constant {
+        name: "GREETING".to_string(),
+        r#type: Type::String,
+        value: Value::String("h\u{e9}llo \u{4e16}\u{754c}\u{1}\u{85}\n\"$\\".to_string()),
+        doc: None,
+      })],
+    };
+    let code = codegen(&module, &CodegenOptions::default(), &mut Vec::new());
+    assert!(
+      code.contains(r#""héllo 世界\u0001\u0085\n\"\$\\""#),
+      "{}",
+      code
+    );
+  }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use ridl_ir::schema::{Module, Schema};
+
+mod case;
+mod cycle;
+mod dart;
+mod go;
+mod json_schema;
+mod kotlin;
+mod mock;
+mod openapi;
+mod proto;
+mod python;
+mod rust;
+mod swift;
+mod thrift;
+mod ts;
+mod ts_guard;
+mod ts_mock;
+mod validator;
+mod validator_io_ts;
+mod validator_valibot;
+mod validator_zod;
+use dart::codegen as codegen_dart;
+use go::codegen as codegen_go;
+use json_schema::codegen as codegen_json_schema;
+use kotlin::codegen as codegen_kotlin;
+use openapi::codegen as codegen_openapi;
+use proto::codegen as codegen_proto;
+use python::codegen as codegen_python;
+use rust::codegen as codegen_rust;
+use swift::codegen as codegen_swift;
+use thrift::codegen as codegen_thrift;
+use ts::codegen as codegen_ts;
+use ts_mock::codegen as codegen_ts_mock;
+use validator::codegen as codegen_validator;
+
+pub use mock::{mock_samples, MockOptions};
+
+/// The target languages of codegen
+pub enum CodegenTargetType {
+  Ts,
+  Proto,
+  Thrift,
+  JsonSchema,
+  OpenApi,
+  Rust,
+  Go,
+  Python,
+  Kotlin,
+  Swift,
+  Dart,
+  /// The ts types along with their schemas of a validator library.
+  Validator,
+  /// The ts factories mocking the types, along with the types of the ts target.
+  Mock,
+}
+
+impl CodegenTargetType {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CodegenTargetType::Ts => ".ts",
+      CodegenTargetType::Proto => ".proto",
+      CodegenTargetType::Thrift => ".thrift",
+      CodegenTargetType::JsonSchema => ".schema.json",
+      CodegenTargetType::OpenApi => ".openapi.json",
+      CodegenTargetType::Rust => ".rs",
+      CodegenTargetType::Go => ".go",
+      CodegenTargetType::Python => ".py",
+      CodegenTargetType::Kotlin => ".kt",
+      CodegenTargetType::Swift => ".swift",
+      CodegenTargetType::Dart => ".dart",
+      CodegenTargetType::Validator => ".ts",
+      CodegenTargetType::Mock => ".mock.ts",
+    }
+  }
+
+  /// The target of the name given on the command line.
+  pub fn from_name(name: &str) -> Option<CodegenTargetType> {
+    match name {
+      "ts" | "typescript" => Some(CodegenTargetType::Ts),
+      "proto" | "proto3" | "protobuf" => Some(CodegenTargetType::Proto),
+      "thrift" => Some(CodegenTargetType::Thrift),
+      "jsonschema" | "json-schema" => Some(CodegenTargetType::JsonSchema),
+      "openapi" => Some(CodegenTargetType::OpenApi),
+      "rust" | "rs" => Some(CodegenTargetType::Rust),
+      "go" | "golang" => Some(CodegenTargetType::Go),
+      "python" | "py" => Some(CodegenTargetType::Python),
+      "kotlin" | "kt" => Some(CodegenTargetType::Kotlin),
+      "swift" => Some(CodegenTargetType::Swift),
+      "dart" => Some(CodegenTargetType::Dart),
+      "validator" => Some(CodegenTargetType::Validator),
+      "mock" => Some(CodegenTargetType::Mock),
+      _ => None,
+    }
+  }
+}
+
+/// How the 64-bit integers are presented in json, strings by default as they overflow the numbers
+/// of js.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Int64Json {
+  #[default]
+  String,
+  Number,
+}
+
+impl Int64Json {
+  pub fn from_name(name: &str) -> Option<Int64Json> {
+    match name {
+      "string" => Some(Int64Json::String),
+      "number" => Some(Int64Json::Number),
+      _ => None,
+    }
+  }
+}
+
+/// The classes of the python target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PythonStyle {
+  #[default]
+  Dataclass,
+  TypedDict,
+  Pydantic,
+}
+
+impl PythonStyle {
+  pub fn from_name(name: &str) -> Option<PythonStyle> {
+    match name {
+      "dataclass" => Some(PythonStyle::Dataclass),
+      "typeddict" | "typed-dict" => Some(PythonStyle::TypedDict),
+      "pydantic" => Some(PythonStyle::Pydantic),
+      _ => None,
+    }
+  }
+}
+
+/// The library of the schemas of the validator target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TsValidator {
+  #[default]
+  Zod,
+  IoTs,
+  Valibot,
+}
+
+impl TsValidator {
+  pub fn from_name(name: &str) -> Option<TsValidator> {
+    match name {
+      "zod" => Some(TsValidator::Zod),
+      "io-ts" | "iots" => Some(TsValidator::IoTs),
+      "valibot" => Some(TsValidator::Valibot),
+      _ => None,
+    }
+  }
+}
+
+/// The options shared by the targets, a target ignores the ones it has nothing to do with.
+#[derive(Debug, Clone, Default)]
+pub struct CodegenOptions {
+  pub int64_json: Int64Json,
+  /// The go module the packages of the namespaces are in, which prefixes their import paths.
+  pub go_module: String,
+  pub python_style: PythonStyle,
+  /// Emit the runtime type guards along with the types of ts, e.g. `isLesson(x)`.
+  pub ts_guards: bool,
+  pub validator: TsValidator,
+}
+
+/// The generated files, keyed and sorted by the relative file path.
+pub type CodegenFilesMap = BTreeMap<String, String>;
+
+/// Every target generates the code of a module from the ir only. The constructs the target can not
+/// present exactly are pushed to `warnings`, the code is generated anyway.
+type Codegen = fn(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String;
+
+/// Generate the files of all modules, along with the warnings prefixed by their file path.
+pub fn gen_code_from_schema(
+  target_type: &CodegenTargetType,
+  schema: &Schema,
+  options: &CodegenOptions,
+) -> (CodegenFilesMap, Vec<String>) {
+  let codegen: Codegen = match target_type {
+    CodegenTargetType::Ts => codegen_ts,
+    CodegenTargetType::Proto => codegen_proto,
+    CodegenTargetType::Thrift => codegen_thrift,
+    CodegenTargetType::JsonSchema => codegen_json_schema,
+    CodegenTargetType::OpenApi => codegen_openapi,
+    CodegenTargetType::Rust => codegen_rust,
+    CodegenTargetType::Go => codegen_go,
+    CodegenTargetType::Python => codegen_python,
+    CodegenTargetType::Kotlin => codegen_kotlin,
+    CodegenTargetType::Swift => codegen_swift,
+    CodegenTargetType::Dart => codegen_dart,
+    CodegenTargetType::Validator => codegen_validator,
+    CodegenTargetType::Mock => codegen_ts_mock,
+  };
+
+  let mut files_map = CodegenFilesMap::new();
+  let mut warnings = Vec::new();
+  for (namespace, module) in &schema.modules {
+    let file_path = format!(
+      "{}{}",
+      get_file_stem(target_type, namespace),
+      target_type.as_str()
+    );
+    let mut module_warnings = Vec::new();
+    files_map.insert(
+      file_path.clone(),
+      codegen(module, options, &mut module_warnings),
+    );
+    warnings.extend(
+      module_warnings
+        .into_iter()
+        .map(|w| format!("{}: {}", file_path, w)),
+    );
+  }
+  // the modules of rust are declared by their parent, the ones of python are a package, the
+  // namespaces of swift are the enums extended by their files along with the wrapper of the 64-bit
+  // integers, and the mocks share a runtime
+  match target_type {
+    CodegenTargetType::Rust => {
+      files_map.insert("mod.rs".to_string(), rust::gen_mod(schema.modules.keys()));
+    }
+    CodegenTargetType::Python => {
+      files_map.insert(
+        "__init__.py".to_string(),
+        python::gen_init(schema.modules.keys()),
+      );
+    }
+    CodegenTargetType::Swift => {
+      files_map.insert(
+        "Namespaces.swift".to_string(),
+        swift::gen_namespaces(schema.modules.keys()),
+      );
+      if options.int64_json == Int64Json::String {
+        files_map.insert(
+          "StringInteger.swift".to_string(),
+          swift::gen_string_integer(),
+        );
+      }
+    }
+    CodegenTargetType::Mock => {
+      files_map.insert("mock.runtime.ts".to_string(), mock::gen_runtime());
+    }
+    _ => {}
+  }
+  (files_map, warnings)
+}
+
+/// The file of a namespace is named after the namespace, files without namespace go to `index`.
+/// The packages of go and kotlin are folders.
+fn get_file_stem(target_type: &CodegenTargetType, namespace: &str) -> String {
+  match target_type {
+    CodegenTargetType::Go => format!("{0}/{0}", go::package_name(namespace)),
+    CodegenTargetType::Kotlin => kotlin::file_stem(namespace),
+    CodegenTargetType::Swift => swift::file_stem(namespace),
+    _ if namespace.is_empty() => "index".to_string(),
+    CodegenTargetType::Thrift => thrift::program_name(namespace),
+    CodegenTargetType::Rust => rust::module_name(namespace),
+    CodegenTargetType::Python => python::module_name(namespace),
+    CodegenTargetType::Dart => dart::library_name(namespace),
+    _ => namespace.to_string(),
+  }
+}
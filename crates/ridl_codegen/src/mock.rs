@@ -0,0 +1,652 @@
+use ridl_ir::schema::{Definition, EnumValue, Schema};
+use ridl_ir::types::{Type, TypeRef};
+use serde_json::{Map, Value as Json};
+
+use crate::case::snake_case;
+use crate::{CodegenOptions, Int64Json};
+
+/// The first names of the fields named like `name`.
+const NAMES: [&str; 8] = [
+  "Alice", "Bob", "Carol", "Dave", "Erin", "Frank", "Grace", "Heidi",
+];
+
+/// The times are in the seconds from 2020 to 2029.
+const TIME_RANGE: (i64, i64) = (1577836800, 1893455999);
+
+const ID_RANGE: (i64, i64) = (1, 99999);
+
+/// The options of the mocks, which the ts factories take at runtime as well.
+#[derive(Debug, Clone)]
+pub struct MockOptions {
+  /// The same seed mocks the same values.
+  pub seed: u32,
+  pub list_size: usize,
+  /// The probability an optional field is present.
+  pub optional_probability: f64,
+  /// The depth of the nested records where the optional fields are left out and the lists are
+  /// empty, which ends the recursive types.
+  pub max_depth: usize,
+}
+
+impl Default for MockOptions {
+  fn default() -> Self {
+    MockOptions {
+      seed: 1,
+      list_size: 2,
+      optional_probability: 0.5,
+      max_depth: 3,
+    }
+  }
+}
+
+/// What a field holds by its name, e.g. `course_id` is an id and `start_time` is a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+  Id,
+  Name,
+  Time,
+  Plain,
+}
+
+impl Hint {
+  pub fn of(name: &str) -> Hint {
+    let name = snake_case(name);
+    let words: Vec<&str> = name.split('_').collect();
+    match words.last().copied() {
+      Some("id" | "ids") => Hint::Id,
+      _ if words.contains(&"name") || words.contains(&"names") => Hint::Name,
+      Some("at") => Hint::Time,
+      _ if words
+        .iter()
+        .any(|w| ["time", "times", "date", "timestamp"].contains(w)) =>
+      {
+        Hint::Time
+      }
+      _ => Hint::Plain,
+    }
+  }
+
+  /// The hint of a field of the integer type, the ids and the times out of the range of the type,
+  /// e.g. the times of `i16`, are plain integers instead.
+  pub fn of_integer(name: &str, r#type: &Type) -> Hint {
+    let max = match r#type {
+      Type::Int8 => i8::MAX as i64,
+      Type::Int16 => i16::MAX as i64,
+      Type::Int32 => i32::MAX as i64,
+      Type::UInt8 => u8::MAX as i64,
+      Type::UInt16 => u16::MAX as i64,
+      Type::UInt32 => u32::MAX as i64,
+      _ => i64::MAX,
+    };
+    match Hint::of(name) {
+      Hint::Id if ID_RANGE.1 > max => Hint::Plain,
+      Hint::Time if TIME_RANGE.1 > max => Hint::Plain,
+      hint => hint,
+    }
+  }
+}
+
+/// The runtime of the ts factories, a seeded random source by mulberry32 that mocks the values the
+/// same way as `Mock`.
+pub const MOCK_RUNTIME: &str = r#"// the runtime of the mock factories
+
+/** The same seed mocks the same values. */
+export interface MockOptions {
+  seed?: number;
+  listSize?: number;
+  /** The probability an optional field is present. */
+  optionalProbability?: number;
+  /** The depth of the nested objects where the optional fields are left out and the lists are empty. */
+  maxDepth?: number;
+}
+
+const NAMES = $NAMES;
+
+export class Mock {
+  private state: number;
+  private depth = 0;
+  readonly listSize: number;
+  readonly optionalProbability: number;
+  readonly maxDepth: number;
+
+  constructor(options: MockOptions = {}) {
+    this.state = options.seed ?? $SEED;
+    this.listSize = options.listSize ?? $LIST_SIZE;
+    this.optionalProbability = options.optionalProbability ?? $OPTIONAL_PROBABILITY;
+    this.maxDepth = options.maxDepth ?? $MAX_DEPTH;
+  }
+
+  /** A number in [0, 1). */
+  next(): number {
+    let t = (this.state = (this.state + 0x6d2b79f5) | 0);
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  }
+
+  int(min: number, max: number): number {
+    return min + Math.floor(this.next() * (max - min + 1));
+  }
+
+  bool(): boolean {
+    return this.next() < 0.5;
+  }
+
+  float(): number {
+    return Math.round(this.next() * 10000) / 100;
+  }
+
+  pick<T>(items: readonly T[]): T {
+    return items[this.int(0, items.length - 1)];
+  }
+
+  oneOf<T>(items: readonly (() => T)[]): T {
+    return this.pick(items)();
+  }
+
+  id(): number {
+    return this.int($ID_MIN, $ID_MAX);
+  }
+
+  name(): string {
+    return this.pick(NAMES);
+  }
+
+  /** The seconds of a time. */
+  time(): number {
+    return this.int($TIME_MIN, $TIME_MAX);
+  }
+
+  isoTime(): string {
+    return new Date(this.time() * 1000).toISOString();
+  }
+
+  uuid(): string {
+    const hex = [0, 1, 2, 3]
+      .map(() => this.int(0, 0xffffffff).toString(16).padStart(8, "0"))
+      .join("");
+    return `${hex.slice(0, 8)}-${hex.slice(8, 12)}-${hex.slice(12, 16)}-${hex.slice(16, 20)}-${hex.slice(20)}`;
+  }
+
+  string(name: string): string {
+    return `${name}_${this.int(1, 999)}`;
+  }
+
+  optional(): boolean {
+    return this.depth < this.maxDepth && this.next() < this.optionalProbability;
+  }
+
+  list<T>(item: () => T): T[] {
+    const size = this.depth < this.maxDepth ? this.listSize : 0;
+    return Array.from({ length: size }, item);
+  }
+
+  map<K, V>(key: () => K, value: () => V): Record<string, V> {
+    const size = this.depth < this.maxDepth ? this.listSize : 0;
+    const map: Record<string, V> = {};
+    for (let i = 0; i < size; i++) {
+      const k = key();
+      map[String(k)] = value();
+    }
+    return map;
+  }
+
+  nested<T>(mock: () => T): T {
+    this.depth++;
+    try {
+      return mock();
+    } finally {
+      this.depth--;
+    }
+  }
+}
+"#;
+
+/// The runtime of the ts factories along with the default options.
+pub fn gen_runtime() -> String {
+  let options = MockOptions::default();
+  let names: Vec<String> = NAMES.iter().map(|n| format!("{:?}", n)).collect();
+  MOCK_RUNTIME
+    .replace("$NAMES", &format!("[{}]", names.join(", ")))
+    .replace("$SEED", &options.seed.to_string())
+    .replace("$LIST_SIZE", &options.list_size.to_string())
+    .replace(
+      "$OPTIONAL_PROBABILITY",
+      &options.optional_probability.to_string(),
+    )
+    .replace("$MAX_DEPTH", &options.max_depth.to_string())
+    .replace("$ID_MIN", &ID_RANGE.0.to_string())
+    .replace("$ID_MAX", &ID_RANGE.1.to_string())
+    .replace("$TIME_MIN", &TIME_RANGE.0.to_string())
+    .replace("$TIME_MAX", &TIME_RANGE.1.to_string())
+}
+
+/// e.g. `2020-01-01T00:00:00.000Z` of `1577836800`, as `Date.toISOString` of js.
+fn iso_time(seconds: i64) -> String {
+  // the civil date of the days since 1970-01-01, by the algorithm of Howard Hinnant
+  let days = seconds.div_euclid(86400) + 719468;
+  let seconds = seconds.rem_euclid(86400);
+  let era = days.div_euclid(146097);
+  let day_of_era = days - era * 146097;
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let mp = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.000Z",
+    year,
+    month,
+    day,
+    seconds / 3600,
+    seconds % 3600 / 60,
+    seconds % 60
+  )
+}
+
+/// The numbers are integers when they are whole, as js prints them.
+fn number(f: f64) -> Json {
+  match f.fract() == 0.0 {
+    true => Json::from(f as i64),
+    false => Json::from(f),
+  }
+}
+
+/// The seeded random source, every value is drawn as `Mock` of the runtime draws it.
+struct Mock<'a> {
+  state: u32,
+  depth: usize,
+  options: &'a MockOptions,
+}
+
+impl Mock<'_> {
+  fn next(&mut self) -> f64 {
+    self.state = self.state.wrapping_add(0x6d2b79f5);
+    let mut t = self.state;
+    t = (t ^ (t >> 15)).wrapping_mul(t | 1);
+    t ^= t.wrapping_add((t ^ (t >> 7)).wrapping_mul(t | 61));
+    (t ^ (t >> 14)) as f64 / 4294967296.0
+  }
+
+  fn int(&mut self, min: i64, max: i64) -> i64 {
+    min + (self.next() * (max - min + 1) as f64).floor() as i64
+  }
+
+  fn pick(&mut self, len: usize) -> usize {
+    self.int(0, len as i64 - 1) as usize
+  }
+
+  fn float(&mut self) -> f64 {
+    (self.next() * 10000.0).round() / 100.0
+  }
+
+  fn iso_time(&mut self) -> String {
+    let seconds = self.int(TIME_RANGE.0, TIME_RANGE.1);
+    iso_time(seconds)
+  }
+
+  fn uuid(&mut self) -> String {
+    let hex: String = (0..4)
+      .map(|_| format!("{:08x}", self.int(0, 0xffffffff)))
+      .collect();
+    format!(
+      "{}-{}-{}-{}-{}",
+      &hex[0..8],
+      &hex[8..12],
+      &hex[12..16],
+      &hex[16..20],
+      &hex[20..]
+    )
+  }
+
+  fn optional(&mut self) -> bool {
+    self.depth < self.options.max_depth && self.next() < self.options.optional_probability
+  }
+
+  fn size(&self) -> usize {
+    match self.depth < self.options.max_depth {
+      true => self.options.list_size,
+      false => 0,
+    }
+  }
+}
+
+struct Sampler<'a> {
+  schema: &'a Schema,
+  options: &'a CodegenOptions,
+  mock: Mock<'a>,
+}
+
+impl<'a> Sampler<'a> {
+  /// The type of the ref, looked up in its namespace, which is the namespace of the field when the
+  /// ref is local.
+  fn resolve(&self, namespace: &str, r: &TypeRef) -> Option<(&'a str, &'a Definition)> {
+    let namespace = match r.namespace.is_empty() {
+      true => namespace.to_string(),
+      false => r.namespace.join("."),
+    };
+    let (namespace, module) = self.schema.modules.get_key_value(&namespace)?;
+    module
+      .definitions
+      .iter()
+      .find(|d| is_type(d) && d.name() == r.name)
+      .map(|d| (namespace.as_str(), d))
+  }
+
+  /// The value of the type, `name` is the name of the field or the type it is mocked for.
+  fn value(&mut self, namespace: &str, r#type: &Type, name: &str) -> Json {
+    let hint = Hint::of(name);
+    match r#type {
+      Type::Int8
+      | Type::Int16
+      | Type::Int32
+      | Type::Int64
+      | Type::UInt8
+      | Type::UInt16
+      | Type::UInt32
+      | Type::UInt64 => {
+        let i = match Hint::of_integer(name, r#type) {
+          Hint::Id => self.mock.int(ID_RANGE.0, ID_RANGE.1),
+          Hint::Time => self.mock.int(TIME_RANGE.0, TIME_RANGE.1),
+          Hint::Name | Hint::Plain => self.mock.int(0, 100),
+        };
+        match r#type {
+          Type::Int64 | Type::UInt64 if self.options.int64_json == Int64Json::String => {
+            Json::from(i.to_string())
+          }
+          _ => Json::from(i),
+        }
+      }
+      Type::Float32 | Type::Float64 => number(self.mock.float()),
+      Type::Bool => Json::from(self.mock.next() < 0.5),
+      Type::String => Json::from(match hint {
+        Hint::Id => self.mock.int(ID_RANGE.0, ID_RANGE.1).to_string(),
+        Hint::Name => NAMES[self.mock.pick(NAMES.len())].to_string(),
+        Hint::Time => self.mock.iso_time(),
+        Hint::Plain => format!("{}_{}", name, self.mock.int(1, 999)),
+      }),
+      Type::Bytes => Json::from("bW9jaw=="),
+      Type::Timestamp => Json::from(self.mock.iso_time()),
+      Type::Date => Json::from(&self.mock.iso_time()[0..10]),
+      Type::Time => Json::from(&self.mock.iso_time()[11..19]),
+      Type::Duration => Json::from(format!("{}s", self.mock.int(1, 3600))),
+      Type::Uuid => Json::from(self.mock.uuid()),
+      Type::Decimal => Json::from(format!("{:.2}", self.mock.float())),
+      Type::Any | Type::Void => Json::Null,
+      Type::List(item) | Type::Set(item) => {
+        let items = (0..self.mock.size())
+          .map(|_| self.value(namespace, item, name))
+          .collect();
+        Json::Array(items)
+      }
+      Type::Map(key, value) => {
+        let mut map = Map::new();
+        for _ in 0..self.mock.size() {
+          let key = match self.value(namespace, key, name) {
+            Json::String(s) => s,
+            key => key.to_string(),
+          };
+          map.insert(key, self.value(namespace, value, name));
+        }
+        Json::Object(map)
+      }
+      Type::Ref(r) => match self.resolve(namespace, r) {
+        Some((namespace, definition)) => self.definition(namespace, definition),
+        None => Json::Null,
+      },
+    }
+  }
+
+  /// The value of a type, as its factory of the ts mocks makes it.
+  fn definition(&mut self, namespace: &str, definition: &Definition) -> Json {
+    match definition {
+      Definition::Record(r) => {
+        self.mock.depth += 1;
+        let mut object = Map::new();
+        for f in &r.fields {
+          if !f.optional || self.mock.optional() {
+            let value = self.value(namespace, &f.r#type, &f.name);
+            object.insert(f.name.clone(), value);
+          }
+        }
+        self.mock.depth -= 1;
+        Json::Object(object)
+      }
+      Definition::Enum(e) => match e.variants.get(self.mock.pick(e.variants.len())) {
+        Some(v) => match &v.value {
+          EnumValue::Int(i) => Json::from(*i),
+          EnumValue::String(s) => Json::from(s.clone()),
+        },
+        None => Json::Null,
+      },
+      Definition::Union(u) if u.variants.is_empty() => Json::Null,
+      Definition::Union(u) => {
+        let t = &u.variants[self.mock.pick(u.variants.len())];
+        self.value(namespace, t, &u.name)
+      }
+      Definition::Alias(a) => self.value(namespace, &a.r#type, &a.name),
+      Definition::Constant(_) | Definition::Service(_) => Json::Null,
+    }
+  }
+}
+
+/// The constants and the services have no values to mock.
+pub fn is_type(definition: &Definition) -> bool {
+  !matches!(definition, Definition::Constant(_) | Definition::Service(_))
+}
+
+/// Mock `count` values of the type `name`, which may be qualified by its namespace, e.g.
+/// `ek.course.Course`. The samples are the ones the ts factories mock with the same options.
+pub fn mock_samples(
+  schema: &Schema,
+  name: &str,
+  count: usize,
+  mock_options: &MockOptions,
+  options: &CodegenOptions,
+) -> Result<Vec<Json>, String> {
+  let (namespace, local_name) = match name.rsplit_once('.') {
+    Some((namespace, local_name)) => (Some(namespace), local_name),
+    None => (None, name),
+  };
+  let found: Vec<(&str, &Definition)> = schema
+    .modules
+    .iter()
+    .filter(|(n, _)| namespace.is_none_or(|namespace| namespace == n.as_str()))
+    .flat_map(|(n, m)| m.definitions.iter().map(move |d| (n.as_str(), d)))
+    .filter(|(_, d)| is_type(d) && d.name() == local_name)
+    .collect();
+  let (namespace, definition) = match found[..] {
+    [found] => found,
+    [] => return Err(format!("type `{}` is not found", name)),
+    _ => {
+      return Err(format!(
+        "type `{}` is in more than one namespace, qualify it like `{}.{}`",
+        name, found[0].0, name
+      ))
+    }
+  };
+  let mut sampler = Sampler {
+    schema,
+    options,
+    mock: Mock {
+      state: mock_options.seed,
+      depth: 0,
+      options: mock_options,
+    },
+  };
+  Ok(
+    (0..count)
+      .map(|_| sampler.definition(namespace, definition))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use super::{iso_time, mock_samples, Hint, MockOptions};
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Schema};
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_hint() {
+    assert_eq!(Hint::of("course_id"), Hint::Id);
+    assert_eq!(Hint::of("teacherIds"), Hint::Id);
+    assert_eq!(Hint::of("valid"), Hint::Plain);
+    assert_eq!(Hint::of("name"), Hint::Name);
+    assert_eq!(Hint::of("student_name"), Hint::Name);
+    assert_eq!(Hint::of("start_time"), Hint::Time);
+    assert_eq!(Hint::of("created_at"), Hint::Time);
+    assert_eq!(Hint::of("status"), Hint::Plain);
+    // the integers too narrow for the ids or the times are plain
+    assert_eq!(Hint::of_integer("user_id", &Type::Int8), Hint::Plain);
+    assert_eq!(Hint::of_integer("user_id", &Type::UInt32), Hint::Id);
+    assert_eq!(Hint::of_integer("start_time", &Type::Int16), Hint::Plain);
+    assert_eq!(Hint::of_integer("start_time", &Type::Int32), Hint::Time);
+    assert_eq!(Hint::of_integer("start_time", &Type::Int64), Hint::Time);
+  }
+
+  #[test]
+  fn test_iso_time() {
+    assert_eq!(iso_time(1577836800), "2020-01-01T00:00:00.000Z");
+    assert_eq!(iso_time(1709251199), "2024-02-29T23:59:59.000Z");
+  }
+
+  #[test]
+  fn test_mock_samples() {
+    let mut schema = Schema::default();
+    schema.modules.insert(
+      "ek.course".to_string(),
+      Module {
+        namespace: "ek.course".to_string(),
+        definitions: vec![
+          Definition::Record(Record {
+            name: "Lesson".to_string(),
+            fields: vec![
+              Field::new("lesson_id", Type::Int64, false),
+              Field::new("teacher_name", Type::String, false),
+              Field::new("start_time", Type::Int32, false),
+              Field::new("status", Type::local("Status"), false),
+              Field::new("weeks", Type::list(Type::Int32), false),
+              Field::new("next", Type::local("Lesson"), true),
+              Field::new("room_id", Type::Int8, false),
+              Field::new("end_time", Type::Int16, false),
+            ],
+            ..Record::default()
+          }),
+          Definition::Enum(Enum {
+            name: "Status".to_string(),
+            variants: [("Todo", 0), ("Done", 1)]
+              .map(|(name, value)| EnumVariant {
+                name: name.to_string(),
+                value: EnumValue::Int(value),
+              })
+              .to_vec(),
+            ..Enum::default()
+          }),
+        ],
+      },
+    );
+    let options = MockOptions {
+      optional_probability: 1.0,
+      ..MockOptions::default()
+    };
+    let samples = mock_samples(&schema, "Lesson", 2, &options, &CodegenOptions::default()).unwrap();
+    assert_eq!(samples.len(), 2);
+    // the same seed mocks the same values, and the next samples differ
+    let again = mock_samples(
+      &schema,
+      "ek.course.Lesson",
+      2,
+      &options,
+      &CodegenOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(samples, again);
+    assert_ne!(samples[0], samples[1]);
+
+    let lesson = &samples[0];
+    let id: i64 = lesson["lesson_id"].as_str().unwrap().parse().unwrap();
+    assert!((1..=99999).contains(&id));
+    assert!(super::NAMES.contains(&lesson["teacher_name"].as_str().unwrap()));
+    assert!((1577836800..=1893455999).contains(&lesson["start_time"].as_i64().unwrap()));
+    assert!([0, 1].contains(&lesson["status"].as_i64().unwrap()));
+    assert_eq!(lesson["weeks"].as_array().unwrap().len(), 2);
+    assert!((0..=100).contains(&lesson["room_id"].as_i64().unwrap()));
+    assert!((0..=100).contains(&lesson["end_time"].as_i64().unwrap()));
+    // the recursion ends at the max depth
+    let innermost = &lesson["next"]["next"];
+    assert!(innermost.get("next").is_none());
+    assert_eq!(innermost["weeks"].as_array().unwrap().len(), 0);
+
+    assert_eq!(
+      mock_samples(&schema, "Course", 1, &options, &CodegenOptions::default()),
+      Err("type `Course` is not found".to_string())
+    );
+  }
+
+  #[test]
+  fn test_mock_refs() {
+    let record = |name: &str, fields: Vec<Field>| {
+      Definition::Record(Record {
+        name: name.to_string(),
+        fields,
+        ..Record::default()
+      })
+    };
+    let mut schema = Schema::default();
+    for (namespace, definitions) in [
+      (
+        "ek.course",
+        vec![record(
+          "Course",
+          vec![
+            Field::new(
+              "teacher",
+              Type::refer("User", vec!["ek".into(), "user".into()]),
+              false,
+            ),
+            Field::new("base", Type::refer("Base", vec!["base".into()]), false),
+          ],
+        )],
+      ),
+      (
+        "ek.user",
+        vec![record(
+          "User",
+          vec![Field::new("user_id", Type::Int32, false)],
+        )],
+      ),
+      (
+        "ek.common",
+        vec![
+          record("User", vec![Field::new("title", Type::String, false)]),
+          record("Base", vec![Field::new("title", Type::String, false)]),
+        ],
+      ),
+    ] {
+      schema.modules.insert(
+        namespace.to_string(),
+        Module {
+          namespace: namespace.to_string(),
+          definitions,
+        },
+      );
+    }
+    let samples = mock_samples(
+      &schema,
+      "Course",
+      1,
+      &MockOptions::default(),
+      &CodegenOptions::default(),
+    )
+    .unwrap();
+    // the refs are looked up in their namespaces only, the types of the same names elsewhere are
+    // not taken for them
+    let course = samples[0].as_object().unwrap();
+    assert!(course["teacher"]["user_id"].is_i64());
+    assert!(course["base"].is_null());
+  }
+}
@@ -0,0 +1,404 @@
+use serde_json::{json, Map, Value as Json};
+
+use ridl_ir::schema::{Definition, Field, Method, Module, Record, Service};
+use ridl_ir::types::Type;
+
+use crate::json_schema::{definition_schemas, type_schema, Defs};
+use crate::CodegenOptions;
+
+const DEFS: Defs = Defs {
+  pointer: "components/schemas",
+  extension: ".openapi.json",
+};
+
+/// The http methods of the route annotations, e.g. `api.get = "/course/:id"`.
+const HTTP_METHODS: [&str; 7] = ["get", "post", "put", "delete", "patch", "head", "options"];
+
+/// The path of openapi, the params `:id` and `*path` of the route are `{id}` and `{path}`.
+fn openapi_path(route: &str) -> String {
+  route
+    .split('/')
+    .map(|segment| match segment.strip_prefix([':', '*']) {
+      Some(param) => format!("{{{}}}", param),
+      None => segment.to_string(),
+    })
+    .collect::<Vec<String>>()
+    .join("/")
+}
+
+/// Where the value of a field of the request is, by its `api.*` annotation. The fields without one
+/// are in the query of the methods without body, or else in the body.
+enum Location {
+  Param(&'static str, String),
+  Body(&'static str, String),
+}
+
+fn location_of(field: &Field, http_method: &str) -> Location {
+  let annotation = |key: &str| field.annotations.get(key).cloned();
+  if let Some(name) = annotation("api.path") {
+    Location::Param("path", name)
+  } else if let Some(name) = annotation("api.header") {
+    Location::Param("header", name)
+  } else if let Some(name) = annotation("api.query") {
+    Location::Param("query", name)
+  } else if let Some(name) = annotation("api.cookie") {
+    Location::Param("cookie", name)
+  } else if let Some(name) = annotation("api.form") {
+    Location::Body("application/x-www-form-urlencoded", name)
+  } else if let Some(name) = annotation("api.body") {
+    Location::Body("application/json", name)
+  } else if ["get", "head", "delete", "options"].contains(&http_method) {
+    Location::Param("query", field.name.clone())
+  } else {
+    Location::Body("application/json", field.name.clone())
+  }
+}
+
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+  fn schema(&self, r#type: &Type) -> Json {
+    type_schema(r#type, &self.module.namespace, &DEFS, self.options)
+  }
+
+  fn local_record(&self, r#type: &Type) -> Option<&Record> {
+    let Type::Ref(r) = r#type else {
+      return None;
+    };
+    if !r.namespace.is_empty() && r.namespace.join(".") != self.module.namespace {
+      return None;
+    }
+    self.module.definitions.iter().find_map(|d| match d {
+      Definition::Record(record) if record.name == r.name => Some(record),
+      _ => None,
+    })
+  }
+
+  /// Split the fields of the request into the params and the body by their locations.
+  fn split_request(&self, fields: &[Field], http_method: &str, operation: &mut Map<String, Json>) {
+    let mut params = Vec::new();
+    let mut bodies: Map<String, Json> = Map::new();
+    for f in fields {
+      let mut schema = self.schema(&f.r#type);
+      if let (Some(doc), Some(object)) = (&f.doc, schema.as_object_mut()) {
+        object.insert("description".to_string(), json!(doc));
+      }
+      match location_of(f, http_method) {
+        Location::Param(r#in, name) => params.push(json!({
+          "name": name,
+          "in": r#in,
+          "required": r#in == "path" || !f.optional,
+          "schema": schema
+        })),
+        Location::Body(content_type, name) => {
+          let body = bodies
+            .entry(content_type)
+            .or_insert_with(|| json!({"type": "object", "properties": {}}));
+          body["properties"][&name] = schema;
+          if !f.optional {
+            match body["required"].as_array_mut() {
+              Some(required) => required.push(json!(name)),
+              None => body["required"] = json!([name]),
+            }
+          }
+        }
+      }
+    }
+    if !params.is_empty() {
+      operation.insert("parameters".to_string(), Json::Array(params));
+    }
+    if !bodies.is_empty() {
+      let content: Map<String, Json> = bodies
+        .into_iter()
+        .map(|(content_type, schema)| (content_type, json!({ "schema": schema })))
+        .collect();
+      operation.insert(
+        "requestBody".to_string(),
+        json!({"required": true, "content": content}),
+      );
+    }
+  }
+
+  fn gen_operation(&mut self, service: &Service, method: &Method, http_method: &str) -> Json {
+    let mut operation = Map::new();
+    operation.insert("operationId".to_string(), json!(method.name));
+    operation.insert("tags".to_string(), json!([service.name]));
+    if let Some(doc) = &method.doc {
+      operation.insert("summary".to_string(), json!(doc));
+    }
+
+    // the request is a struct whose fields are split, e.g. `Resp Get(1: Req req)`
+    match method.params.as_slice() {
+      [] => {}
+      [param] if self.local_record(&param.r#type).is_some() => {
+        let record = self.local_record(&param.r#type).unwrap();
+        self.split_request(&record.fields, http_method, &mut operation);
+      }
+      params => {
+        if params.iter().any(|p| matches!(p.r#type, Type::Ref(_))) {
+          self.warnings.push(format!(
+            "the params of `{}.{}` are not a struct of the namespace, they are not split by the annotations",
+            service.name, method.name
+          ));
+        }
+        self.split_request(params, http_method, &mut operation);
+      }
+    }
+
+    let mut responses = Map::new();
+    responses.insert(
+      "200".to_string(),
+      match method.result {
+        Type::Void => json!({"description": "OK"}),
+        _ => json!({
+          "description": "OK",
+          "content": {"application/json": {"schema": self.schema(&method.result)}}
+        }),
+      },
+    );
+    if !method.throws.is_empty() {
+      let errors: Vec<Json> = method
+        .throws
+        .iter()
+        .map(|f| self.schema(&f.r#type))
+        .collect();
+      let schema = match <[Json; 1]>::try_from(errors) {
+        Ok([error]) => error,
+        Err(errors) => json!({ "oneOf": errors }),
+      };
+      responses.insert(
+        "default".to_string(),
+        json!({
+          "description": "Error",
+          "content": {"application/json": {"schema": schema}}
+        }),
+      );
+    }
+    operation.insert("responses".to_string(), Json::Object(responses));
+    Json::Object(operation)
+  }
+}
+
+/// Generate the openapi 3.1 document of a module. Every method with a route annotation is an
+/// operation, whose params and body are split from the fields of the request by the `api.*`
+/// annotations, and the types are the schemas of the components.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    options,
+    warnings: Vec::new(),
+  };
+  let mut paths: Map<String, Json> = Map::new();
+  for d in &module.definitions {
+    let Definition::Service(service) = d else {
+      continue;
+    };
+    for method in &service.methods {
+      let routes: Vec<(&str, &String)> = HTTP_METHODS
+        .iter()
+        .filter_map(|m| {
+          method
+            .annotations
+            .get(&format!("api.{}", m))
+            .map(|r| (*m, r))
+        })
+        .collect();
+      if routes.is_empty() {
+        generator.warnings.push(format!(
+          "`{}.{}` has no route annotation, it is not an operation",
+          service.name, method.name
+        ));
+      }
+      for (http_method, route) in routes {
+        let operation = generator.gen_operation(service, method, http_method);
+        let path = paths
+          .entry(openapi_path(route))
+          .or_insert_with(|| json!({}));
+        path[http_method] = operation;
+      }
+    }
+  }
+  warnings.append(&mut generator.warnings);
+
+  let title = if module.namespace.is_empty() {
+    "index"
+  } else {
+    module.namespace.as_str()
+  };
+  let document = json!({
+    "openapi": "3.1.0",
+    "info": {"title": title, "version": "1.0.0"},
+    "paths": paths,
+    "components": {"schemas": definition_schemas(module, &DEFS, options)}
+  });
+  format!("{}\n", serde_json::to_string_pretty(&document).unwrap())
+}
+
+#[test]
+fn test_openapi_path() {
+  assert_eq!(openapi_path("/course/:id"), "/course/{id}");
+  assert_eq!(openapi_path("/static/*path"), "/static/{path}");
+  assert_eq!(openapi_path("/courses"), "/courses");
+}
+
+#[test]
+fn test_codegen() {
+  use ridl_ir::schema::Annotations;
+
+  let annotated = |field: Field, key: &str, value: &str| Field {
+    annotations: Annotations::from([(key.to_string(), value.to_string())]),
+    ..field
+  };
+  let method = |name: &str, req: &str, result: Type, route: Option<(&str, &str)>| Method {
+    name: name.to_string(),
+    params: vec![Field::new("req", Type::local(req), false)],
+    result,
+    throws: Vec::new(),
+    doc: None,
+    annotations: route
+      .map(|(key, value)| Annotations::from([(key.to_string(), value.to_string())]))
+      .unwrap_or_default(),
+  };
+  let module = Module {
+    namespace: "course".to_string(),
+    definitions: vec![
+      Definition::Record(Record {
+        name: "GetCourseReq".to_string(),
+        fields: vec![
+          annotated(Field::new("id", Type::Int64, false), "api.path", "id"),
+          annotated(Field::new("lang", Type::String, true), "api.query", "lang"),
+          annotated(
+            Field::new("org_id", Type::Int64, true),
+            "api.header",
+            "X-Org-Id",
+          ),
+        ],
+        ..Record::default()
+      }),
+      Definition::Record(Record {
+        name: "UpdateCourseReq".to_string(),
+        fields: vec![
+          annotated(Field::new("id", Type::Int64, false), "api.path", "id"),
+          annotated(Field::new("name", Type::String, false), "api.body", "name"),
+          Field::new("tags", Type::list(Type::String), true),
+        ],
+        ..Record::default()
+      }),
+      Definition::Record(Record {
+        name: "Course".to_string(),
+        fields: vec![
+          Field::new("id", Type::Int64, false),
+          Field::new("name", Type::String, false),
+        ],
+        ..Record::default()
+      }),
+      Definition::Service(Service {
+        name: "CourseService".to_string(),
+        methods: vec![
+          method(
+            "GetCourse",
+            "GetCourseReq",
+            Type::local("Course"),
+            Some(("api.get", "/course/:id")),
+          ),
+          method(
+            "UpdateCourse",
+            "UpdateCourseReq",
+            Type::refer("BaseResp", vec!["base".to_string()]),
+            Some(("api.put", "/course/:id")),
+          ),
+          Method {
+            params: Vec::new(),
+            ..method("Ping", "", Type::Void, None)
+          },
+        ],
+        doc: None,
+        annotations: Annotations::new(),
+      }),
+    ],
+  };
+  let mut warnings = Vec::new();
+  let document: Json =
+    serde_json::from_str(&codegen(&module, &CodegenOptions::default(), &mut warnings)).unwrap();
+  assert_eq!(
+    warnings,
+    ["`CourseService.Ping` has no route annotation, it is not an operation"]
+  );
+  assert_eq!(document["openapi"], "3.1.0");
+  let paths = &document["paths"];
+  assert_eq!(
+    paths.as_object().unwrap().keys().collect::<Vec<&String>>(),
+    ["/course/{id}"]
+  );
+
+  let get = &paths["/course/{id}"]["get"];
+  assert_eq!(get["operationId"], "GetCourse");
+  assert_eq!(get["tags"], json!(["CourseService"]));
+  let params: Vec<(&str, &str, bool)> = get["parameters"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|p| {
+      (
+        p["name"].as_str().unwrap(),
+        p["in"].as_str().unwrap(),
+        p["required"].as_bool().unwrap(),
+      )
+    })
+    .collect();
+  assert_eq!(
+    params,
+    [
+      ("id", "path", true),
+      ("lang", "query", false),
+      ("X-Org-Id", "header", false)
+    ]
+  );
+  assert_eq!(
+    get["parameters"][0]["schema"],
+    json!({"type": "string", "pattern": "^-?[0-9]+$"})
+  );
+  assert!(get.get("requestBody").is_none());
+  assert_eq!(
+    get["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+    "#/components/schemas/Course"
+  );
+
+  let put = &paths["/course/{id}"]["put"];
+  assert_eq!(put["parameters"].as_array().unwrap().len(), 1);
+  let body = &put["requestBody"]["content"]["application/json"]["schema"];
+  assert_eq!(
+    body["properties"]
+      .as_object()
+      .unwrap()
+      .keys()
+      .collect::<Vec<&String>>(),
+    ["name", "tags"]
+  );
+  assert_eq!(body["required"], json!(["name"]));
+  assert_eq!(
+    put["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+    "base.openapi.json#/components/schemas/BaseResp"
+  );
+
+  let schemas = document["components"]["schemas"].as_object().unwrap();
+  assert_eq!(
+    schemas.keys().collect::<Vec<&String>>(),
+    ["GetCourseReq", "UpdateCourseReq", "Course"]
+  );
+
+  // the 64-bit integers are numbers by `--i64 number`
+  let options = CodegenOptions {
+    int64_json: crate::Int64Json::Number,
+    ..CodegenOptions::default()
+  };
+  let document: Json = serde_json::from_str(&codegen(&module, &options, &mut Vec::new())).unwrap();
+  assert_eq!(
+    document["paths"]["/course/{id}"]["get"]["parameters"][0]["schema"],
+    json!({"type": "integer", "minimum": i64::MIN, "maximum": i64::MAX})
+  );
+}
@@ -0,0 +1,618 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use ridl_ir::schema::{Definition, Enum, EnumValue, Field, Method, Module, Record, Service, Union};
+use ridl_ir::types::Type;
+
+use crate::case::{snake_case, upper_camel_case, upper_snake_case};
+use crate::CodegenOptions;
+
+/// The field numbers reserved by protobuf itself.
+const RESERVED_NUMBERS: std::ops::RangeInclusive<i32> = 19000..=19999;
+const MAX_NUMBER: i32 = (1 << 29) - 1;
+
+/// Generates the proto3 of a module, the constructs proto3 can not present are reported as warnings
+/// and emitted as their closest equivalent.
+struct Generator<'a> {
+  module: &'a Module,
+  aliases: HashMap<&'a str, &'a Type>,
+  imports: BTreeSet<String>,
+  /// The enum values declared so far and their enums.
+  enum_values: BTreeMap<String, String>,
+  warnings: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+  fn new(module: &'a Module) -> Self {
+    let aliases = module
+      .definitions
+      .iter()
+      .filter_map(|d| match d {
+        Definition::Alias(a) => Some((a.name.as_str(), &a.r#type)),
+        _ => None,
+      })
+      .collect();
+    Generator {
+      module,
+      aliases,
+      imports: BTreeSet::new(),
+      enum_values: BTreeMap::new(),
+      warnings: Vec::new(),
+    }
+  }
+
+  fn warn(&mut self, message: String) {
+    self.warnings.push(message);
+  }
+
+  /// Proto3 has no alias, the local aliases are replaced by the types they stand for.
+  fn resolve<'t>(&self, r#type: &'t Type) -> &'t Type
+  where
+    'a: 't,
+  {
+    let mut r#type = r#type;
+    // the depth limit stops the cyclic aliases
+    for _ in 0..self.aliases.len() {
+      match r#type {
+        Type::Ref(r) if r.namespace.is_empty() => match self.aliases.get(r.name.as_str()) {
+          Some(t) => r#type = t,
+          None => break,
+        },
+        _ => break,
+      }
+    }
+    r#type
+  }
+
+  fn is_local_enum(&self, name: &str) -> bool {
+    self
+      .module
+      .definitions
+      .iter()
+      .any(|d| matches!(d, Definition::Enum(e) if e.name == name))
+  }
+
+  fn well_known(&mut self, name: &str, file: &str) -> String {
+    self
+      .imports
+      .insert(format!("google/protobuf/{}.proto", file));
+    format!("google.protobuf.{}", name)
+  }
+
+  /// The type of a singular value, `context` names the place of the type in the warnings.
+  fn type_str(&mut self, r#type: &Type, context: &str) -> String {
+    match self.resolve(r#type) {
+      Type::Bool => "bool".to_string(),
+      Type::Int8 | Type::Int16 | Type::Int32 => "int32".to_string(),
+      Type::Int64 => "int64".to_string(),
+      Type::UInt8 | Type::UInt16 | Type::UInt32 => "uint32".to_string(),
+      Type::UInt64 => "uint64".to_string(),
+      Type::Float32 => "float".to_string(),
+      Type::Float64 => "double".to_string(),
+      Type::String | Type::Date | Type::Time | Type::Uuid | Type::Decimal => "string".to_string(),
+      Type::Bytes => "bytes".to_string(),
+      Type::Timestamp => self.well_known("Timestamp", "timestamp"),
+      Type::Duration => self.well_known("Duration", "duration"),
+      Type::Any => self.well_known("Value", "struct"),
+      Type::Void => self.well_known("Empty", "empty"),
+      Type::Ref(r) => {
+        if r.namespace.is_empty() {
+          r.name.clone()
+        } else {
+          let namespace = r.namespace.join(".");
+          if namespace != self.module.namespace {
+            self.imports.insert(format!("{}.proto", namespace));
+          }
+          format!("{}.{}", namespace, r.name)
+        }
+      }
+      t @ (Type::List(_) | Type::Set(_) | Type::Map(..)) => {
+        self.warn(format!(
+          "`{}` is `{}` that proto3 can not nest here, it is emitted as bytes",
+          context, t
+        ));
+        "bytes".to_string()
+      }
+    }
+  }
+
+  /// The label and the type of a field, lists and sets are repeated.
+  fn field_type_str(&mut self, r#type: &Type, context: &str) -> String {
+    match self.resolve(r#type) {
+      Type::List(item) => format!("repeated {}", self.type_str(item, context)),
+      Type::Set(item) => {
+        self.warn(format!(
+          "`{}` is a set, it is emitted as repeated and the uniqueness is not kept",
+          context
+        ));
+        format!("repeated {}", self.type_str(item, context))
+      }
+      Type::Map(key, value) => {
+        let key = match self.resolve(key) {
+          Type::Bool
+          | Type::Int8
+          | Type::Int16
+          | Type::Int32
+          | Type::Int64
+          | Type::UInt8
+          | Type::UInt16
+          | Type::UInt32
+          | Type::UInt64
+          | Type::String => self.type_str(key, context),
+          k => {
+            self.warn(format!(
+              "`{}` has map keys of `{}`, proto3 map keys are integers or strings and they are emitted as string",
+              context, k
+            ));
+            "string".to_string()
+          }
+        };
+        format!("map<{}, {}>", key, self.type_str(value, context))
+      }
+      _ => self.type_str(r#type, context),
+    }
+  }
+
+  /// The numbers of the fields, the ids are kept and the fields without a valid id take the numbers
+  /// following the largest one.
+  fn field_numbers(&mut self, owner: &str, fields: &[Field]) -> Vec<i32> {
+    let valid = |id: &Option<i32>| {
+      id.filter(|id| (1..=MAX_NUMBER).contains(id) && !RESERVED_NUMBERS.contains(id))
+    };
+    let mut next = fields
+      .iter()
+      .filter_map(|f| valid(&f.id))
+      .max()
+      .unwrap_or(0)
+      + 1;
+    let mut numbers = Vec::new();
+    for f in fields {
+      let number = match valid(&f.id) {
+        Some(id) => id,
+        None => {
+          if let Some(id) = f.id {
+            self.warn(format!(
+              "`{}.{}` has the id {} that is not a valid field number, it is numbered {}",
+              owner, f.name, id, next
+            ));
+          }
+          next += 1;
+          next - 1
+        }
+      };
+      numbers.push(number);
+    }
+    numbers
+  }
+
+  fn gen_fields(&mut self, owner: &str, fields: &[Field], indent: &str) -> String {
+    let numbers = self.field_numbers(owner, fields);
+    let mut code = String::new();
+    for (f, number) in fields.iter().zip(numbers) {
+      let context = format!("{}.{}", owner, f.name);
+      if f.required {
+        self.warn(format!(
+          "`{}` is required, proto3 has no required fields and it is emitted as a singular field",
+          context
+        ));
+      }
+      let r#type = self.field_type_str(&f.r#type, &context);
+      let label = if f.optional && !r#type.starts_with("repeated ") && !r#type.starts_with("map<") {
+        "optional "
+      } else {
+        ""
+      };
+      code.push_str(&gen_doc(&f.doc, indent));
+      code.push_str(&format!(
+        "{}{}{} {} = {};\n",
+        indent, label, r#type, f.name, number
+      ));
+    }
+    code
+  }
+
+  fn gen_message(&mut self, record: &Record) -> String {
+    let mut code = gen_doc(&record.doc, "");
+    code.push_str(&format!("message {} {{\n", record.name));
+    code.push_str(&self.gen_fields(&record.name, &record.fields, "  "));
+    code.push_str("}\n");
+    code
+  }
+
+  /// The values of proto3 enums start with zero, which is added as `<ENUM>_UNSPECIFIED` if absent.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    // the values share the scope of the package, so they are prefixed by the enum, e.g.
+    // `STATUS_ACTIVE` of `Active` of `Status`
+    let prefix = format!("{}_", upper_snake_case(&e.name));
+    let prefixed = |name: &str| {
+      let name = upper_snake_case(name);
+      match name.starts_with(&prefix) {
+        true => name,
+        false => format!("{}{}", prefix, name),
+      }
+    };
+    let mut variants: Vec<(String, i64)> = e
+      .variants
+      .iter()
+      .enumerate()
+      .map(|(i, v)| match &v.value {
+        EnumValue::Int(value) => (prefixed(&v.name), *value),
+        // the string values are numbered by their order, after the zero value
+        EnumValue::String(_) => (prefixed(&v.name), i as i64 + 1),
+      })
+      .collect();
+    match variants.iter().position(|(_, value)| *value == 0) {
+      Some(i) => {
+        let zero = variants.remove(i);
+        variants.insert(0, zero);
+      }
+      None => variants.insert(0, (prefixed("UNSPECIFIED"), 0)),
+    }
+
+    let mut code = gen_doc(&e.doc, "");
+    code.push_str(&format!("enum {} {{\n", e.name));
+    let values: BTreeSet<i64> = variants.iter().map(|(_, value)| *value).collect();
+    if values.len() < variants.len() {
+      code.push_str("  option allow_alias = true;\n");
+    }
+    for (name, value) in variants {
+      if let Some(other) = self.enum_values.insert(name.clone(), e.name.clone()) {
+        self.warn(format!(
+          "enum value `{}` of `{}` is also declared by `{}`, the values of proto3 enums share the scope of the package",
+          name, e.name, other
+        ));
+      }
+      code.push_str(&format!("  {} = {};\n", name, value));
+    }
+    code.push_str("}\n");
+    code
+  }
+
+  fn gen_oneof(&mut self, union: &Union) -> String {
+    let mut code = gen_doc(&union.doc, "");
+    code.push_str(&format!("message {} {{\n  oneof value {{\n", union.name));
+    for (i, variant) in union.variants.iter().enumerate() {
+      let name = match variant {
+        Type::Ref(r) => snake_case(&r.name),
+        t => format!("{}_value", snake_case(&t.to_string())),
+      };
+      let context = format!("{}.{}", union.name, name);
+      let r#type = self.type_str(variant, &context);
+      code.push_str(&format!("    {} {} = {};\n", r#type, name, i + 1));
+    }
+    code.push_str("  }\n}\n");
+    code
+  }
+
+  /// The request or the response of a method, a single message is used as is and the others are
+  /// wrapped into a message named after the method.
+  fn gen_rpc_message(
+    &mut self,
+    messages: &mut Vec<String>,
+    name: String,
+    fields: &[Field],
+  ) -> String {
+    if fields.is_empty() {
+      return self.well_known("Empty", "empty");
+    }
+    if let [field] = fields {
+      match self.resolve(&field.r#type) {
+        Type::Void => return self.well_known("Empty", "empty"),
+        Type::Ref(r) if !(r.namespace.is_empty() && self.is_local_enum(&r.name)) => {
+          return self.type_str(&field.r#type, &name);
+        }
+        _ => {}
+      }
+    }
+    let mut code = format!("message {} {{\n", name);
+    code.push_str(&self.gen_fields(&name, fields, "  "));
+    code.push_str("}\n");
+    messages.push(code);
+    name
+  }
+
+  fn gen_rpc(&mut self, service: &Service, method: &Method, messages: &mut Vec<String>) -> String {
+    let context = format!("{}.{}", service.name, method.name);
+    if !method.throws.is_empty() {
+      self.warn(format!(
+        "`{}` throws exceptions, proto3 reports the errors by the status of the rpc and they are dropped",
+        context
+      ));
+    }
+    let method_name = upper_camel_case(&method.name);
+    let request = self.gen_rpc_message(messages, format!("{}Request", method_name), &method.params);
+    let result = Field {
+      id: Some(1),
+      ..Field::new("result", method.result.clone(), false)
+    };
+    let response = self.gen_rpc_message(messages, format!("{}Response", method_name), &[result]);
+    let mut code = gen_doc(&method.doc, "  ");
+    code.push_str(&format!(
+      "  rpc {}({}) returns ({});\n",
+      method_name, request, response
+    ));
+    code
+  }
+
+  fn gen_service(&mut self, service: &Service) -> String {
+    let mut messages = Vec::new();
+    let mut rpcs = String::new();
+    for method in &service.methods {
+      rpcs.push_str(&self.gen_rpc(service, method, &mut messages));
+    }
+    let mut code = messages
+      .into_iter()
+      .map(|m| format!("{}\n", m))
+      .collect::<String>();
+    code.push_str(&gen_doc(&service.doc, ""));
+    code.push_str(&format!("service {} {{\n{}}}\n", service.name, rpcs));
+    code
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  doc
+    .iter()
+    .flat_map(|d| d.lines())
+    .map(|line| format!("{}// {}\n", indent, line).replace("// \n", "//\n"))
+    .collect()
+}
+
+/// Generate the proto3 of a module, the namespace is the package and the referred namespaces are
+/// imported from their own files.
+pub fn codegen(module: &Module, _options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut blocks = Vec::new();
+  let mut generator = Generator::new(module);
+  for d in &module.definitions {
+    match d {
+      Definition::Record(r) => blocks.push(generator.gen_message(r)),
+      Definition::Enum(e) => blocks.push(generator.gen_enum(e)),
+      Definition::Union(u) => blocks.push(generator.gen_oneof(u)),
+      Definition::Service(s) => blocks.push(generator.gen_service(s)),
+      Definition::Alias(_) => {}
+      Definition::Constant(c) => generator.warn(format!(
+        "constant `{}` has no proto3 equivalent, it is skipped",
+        c.name
+      )),
+    }
+  }
+  warnings.append(&mut generator.warnings);
+
+  let mut code = "syntax = \"proto3\";\n\n".to_string();
+  if !module.namespace.is_empty() {
+    code.push_str(&format!("package {};\n\n", module.namespace));
+  }
+  if !generator.imports.is_empty() {
+    for import in &generator.imports {
+      code.push_str(&format!("import \"{}\";\n", import));
+    }
+    code.push('\n');
+  }
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{
+    Alias, Constant, Definition, Enum, EnumValue, EnumVariant, Field, Method, Module, Record,
+    Service, Value,
+  };
+  use ridl_ir::types::Type;
+
+  fn field(id: i32, name: &str, r#type: Type) -> Field {
+    Field {
+      id: Some(id),
+      ..Field::new(name, r#type, false)
+    }
+  }
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "foo.bar".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "LessonPlan".to_string(),
+          fields: vec![
+            Field {
+              required: true,
+              ..field(1, "start_date", Type::Int64)
+            },
+            Field {
+              optional: true,
+              ..field(3, "loop_mode", Type::local("LoopMode"))
+            },
+            field(5, "times", Type::local("Times")),
+            field(-1, "weeks", Type::set(Type::Int16)),
+            field(6, "extra", Type::map(Type::String, Type::Any)),
+            field(
+              7,
+              "by_mode",
+              Type::map(Type::local("LoopMode"), Type::list(Type::Int32)),
+            ),
+            field(8, "base", Type::refer("Base", vec!["base".to_string()])),
+          ],
+          doc: Some("A plan.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "LoopMode".to_string(),
+          variants: [("Week", 1), ("Month", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          ..Enum::default()
+        }),
+        Definition::Alias(Alias {
+          name: "Times".to_string(),
+          r#type: Type::list(Type::Timestamp),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "MAX_WEEKS".to_string(),
+          r#type: Type::Int32,
+          value: Value::Int(52),
+          doc: None,
+        }),
+        Definition::Service(Service {
+          name: "PlanService".to_string(),
+          methods: vec![
+            Method {
+              name: "getPlan".to_string(),
+              params: vec![field(1, "id", Type::Int64)],
+              result: Type::local("LessonPlan"),
+              throws: vec![field(1, "err", Type::local("Error"))],
+              doc: None,
+              annotations: Default::default(),
+            },
+            Method {
+              name: "ping".to_string(),
+              params: Vec::new(),
+              result: Type::Void,
+              throws: Vec::new(),
+              doc: None,
+              annotations: Default::default(),
+            },
+          ],
+          ..Service::default()
+        }),
+      ],
+    };
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut warnings),
+      r#"syntax = "proto3";
+
+package foo.bar;
+
+import "base.proto";
+import "google/protobuf/empty.proto";
+import "google/protobuf/struct.proto";
+import "google/protobuf/timestamp.proto";
+
+// A plan.
+message LessonPlan {
+  int64 start_date = 1;
+  optional LoopMode loop_mode = 3;
+  repeated google.protobuf.Timestamp times = 5;
+  repeated int32 weeks = 9;
+  map<string, google.protobuf.Value> extra = 6;
+  map<string, bytes> by_mode = 7;
+  base.Base base = 8;
+}
+
+enum LoopMode {
+  LOOP_MODE_UNSPECIFIED = 0;
+  LOOP_MODE_WEEK = 1;
+  LOOP_MODE_MONTH = 2;
+}
+
+message GetPlanRequest {
+  int64 id = 1;
+}
+
+service PlanService {
+  rpc GetPlan(GetPlanRequest) returns (LessonPlan);
+  rpc Ping(google.protobuf.Empty) returns (google.protobuf.Empty);
+}
+"#
+    );
+    assert_eq!(
+      warnings,
+      [
+        "`LessonPlan.weeks` has the id -1 that is not a valid field number, it is numbered 9",
+        "`LessonPlan.start_date` is required, proto3 has no required fields and it is emitted as a singular field",
+        "`LessonPlan.weeks` is a set, it is emitted as repeated and the uniqueness is not kept",
+        "`LessonPlan.by_mode` has map keys of `LoopMode`, proto3 map keys are integers or strings and they are emitted as string",
+        "`LessonPlan.by_mode` is `list<i32>` that proto3 can not nest here, it is emitted as bytes",
+        "constant `MAX_WEEKS` has no proto3 equivalent, it is skipped",
+        "`PlanService.getPlan` throws exceptions, proto3 reports the errors by the status of the rpc and they are dropped",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_enum_zero_value() {
+    let variants = |values: &[(&str, i64)]| -> Vec<EnumVariant> {
+      values
+        .iter()
+        .map(|(name, value)| EnumVariant {
+          name: name.to_string(),
+          value: EnumValue::Int(*value),
+        })
+        .collect()
+    };
+    let module = Module {
+      namespace: String::new(),
+      definitions: vec![
+        Definition::Enum(Enum {
+          name: "A".to_string(),
+          variants: variants(&[("One", 1), ("None", 0), ("Uno", 1)]),
+          ..Enum::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: variants(&[("Active", 1), ("STATUS_INACTIVE", 2)]),
+          ..Enum::default()
+        }),
+        Definition::Enum(Enum {
+          name: "A_None".to_string(),
+          variants: variants(&[("Uno", 1)]),
+          ..Enum::default()
+        }),
+      ],
+    };
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut warnings),
+      r#"syntax = "proto3";
+
+enum A {
+  option allow_alias = true;
+  A_NONE = 0;
+  A_ONE = 1;
+  A_UNO = 1;
+}
+
+enum Status {
+  STATUS_UNSPECIFIED = 0;
+  STATUS_ACTIVE = 1;
+  STATUS_INACTIVE = 2;
+}
+
+enum A_None {
+  A_NONE_UNSPECIFIED = 0;
+  A_NONE_UNO = 1;
+}
+"#
+    );
+    assert!(warnings.is_empty());
+
+    // the prefixed values may still collide
+    let module = Module {
+      namespace: String::new(),
+      definitions: vec![
+        Definition::Enum(Enum {
+          name: "A".to_string(),
+          variants: variants(&[("None", 0), ("B_C", 1)]),
+          ..Enum::default()
+        }),
+        Definition::Enum(Enum {
+          name: "A_B".to_string(),
+          variants: variants(&[("C", 1)]),
+          ..Enum::default()
+        }),
+      ],
+    };
+    let mut warnings = Vec::new();
+    codegen(&module, &CodegenOptions::default(), &mut warnings);
+    assert_eq!(
+      warnings,
+      ["enum value `A_B_C` of `A_B` is also declared by `A`, the values of proto3 enums share the scope of the package"]
+    );
+  }
+}
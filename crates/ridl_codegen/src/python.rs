@@ -0,0 +1,536 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ridl_ir::schema::{
+  Alias, Constant, Definition, Enum, EnumValue, Field, Module, Record, Union, Value,
+};
+use ridl_ir::types::Type;
+
+use crate::case::snake_case;
+use crate::{CodegenOptions, Int64Json, PythonStyle};
+
+const KEYWORDS: &[&str] = &[
+  "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+  "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+  "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+  "yield",
+];
+
+/// The module of a namespace, which names its file in the package of all namespaces, e.g.
+/// `ek_course` of `ek.course`.
+pub fn module_name(namespace: &str) -> String {
+  match snake_case(namespace) {
+    name if name.is_empty() => "index".to_string(),
+    name => name,
+  }
+}
+
+/// The `__init__.py` making the modules of the namespaces a package, so they import each other
+/// relatively.
+pub fn gen_init<'a>(namespaces: impl Iterator<Item = &'a String>) -> String {
+  let modules: Vec<String> = namespaces
+    .map(|n| format!("{:?}", module_name(n)))
+    .collect();
+  format!("__all__ = [{}]\n", modules.join(", "))
+}
+
+/// The identifier of a name, the keywords get a `_` suffix.
+fn ident(name: &str) -> String {
+  match KEYWORDS.contains(&name) {
+    true => format!("{}_", name),
+    false => name.to_string(),
+  }
+}
+
+fn gen_docstring(doc: &Option<String>, indent: &str) -> String {
+  match doc.as_deref().map(|d| d.lines().collect::<Vec<&str>>()) {
+    None => String::new(),
+    Some(lines) if lines.len() == 1 => format!("{}\"\"\"{}\"\"\"\n", indent, lines[0]),
+    Some(lines) => {
+      let mut code = format!("{}\"\"\"\n", indent);
+      for line in lines {
+        code.push_str(&format!("{}{}\n", indent, line).replace(" \n", "\n"));
+      }
+      code.push_str(&format!("{}\"\"\"\n", indent));
+      code
+    }
+  }
+}
+
+fn gen_comment(doc: &Option<String>, indent: &str) -> String {
+  doc
+    .iter()
+    .flat_map(|d| d.lines())
+    .map(|line| format!("{}# {}\n", indent, line).replace("# \n", "#\n"))
+    .collect()
+}
+
+/// Generates the python of a module, the imports the code needs are collected along.
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  /// The names imported from the modules, e.g. `Optional` from `typing`.
+  froms: BTreeMap<&'static str, BTreeSet<&'static str>>,
+  /// The modules of other namespaces.
+  relatives: BTreeSet<String>,
+  warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+  fn import(&mut self, module: &'static str, name: &'static str) {
+    self.froms.entry(module).or_default().insert(name);
+  }
+
+  /// The type by the json mapping. The local types are quoted in the expressions evaluated when
+  /// the module is loaded, e.g. the unions, as they may be defined later.
+  fn type_str(&mut self, r#type: &Type, quote: bool) -> String {
+    match r#type {
+      Type::Int64 | Type::UInt64 if self.options.int64_json == Int64Json::String => {
+        "str".to_string()
+      }
+      Type::Bool => "bool".to_string(),
+      Type::Int8
+      | Type::Int16
+      | Type::Int32
+      | Type::Int64
+      | Type::UInt8
+      | Type::UInt16
+      | Type::UInt32
+      | Type::UInt64 => "int".to_string(),
+      Type::Float32 | Type::Float64 => "float".to_string(),
+      Type::String
+      | Type::Bytes
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => "str".to_string(),
+      Type::Any => {
+        self.import("typing", "Any");
+        "Any".to_string()
+      }
+      Type::Void => "None".to_string(),
+      Type::List(item) | Type::Set(item) => format!("list[{}]", self.type_str(item, quote)),
+      Type::Map(key, value) => format!(
+        "dict[{}, {}]",
+        self.type_str(key, quote),
+        self.type_str(value, quote)
+      ),
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          match quote {
+            true => format!("{:?}", r.name),
+            false => r.name.clone(),
+          }
+        } else {
+          let module = module_name(&namespace);
+          self.relatives.insert(module.clone());
+          format!("{}.{}", module, r.name)
+        }
+      }
+    }
+  }
+
+  /// The optional fields may be absent or null, they default to `None` but in the typed dicts.
+  fn gen_field(&mut self, owner: &str, f: &Field) -> String {
+    let mut r#type = self.type_str(&f.r#type, false);
+    if f.optional {
+      self.import("typing", "Optional");
+      r#type = format!("Optional[{}]", r#type);
+    }
+    let name = ident(&f.name);
+    let code = match self.options.python_style {
+      PythonStyle::TypedDict if f.optional => {
+        self.import("typing", "NotRequired");
+        format!("{}: NotRequired[{}]", name, r#type)
+      }
+      PythonStyle::TypedDict => format!("{}: {}", name, r#type),
+      PythonStyle::Pydantic if name != f.name => {
+        self.import("pydantic", "Field");
+        let default = if f.optional { "None, " } else { "" };
+        format!(
+          "{}: {} = Field({}alias={})",
+          name,
+          r#type,
+          default,
+          python_string(&f.name)
+        )
+      }
+      PythonStyle::Dataclass if name != f.name => {
+        self.warnings.push(format!(
+          "`{}.{}` is a keyword of python, it is renamed to `{}`",
+          owner, f.name, name
+        ));
+        format!("{}: {}", name, r#type) + if f.optional { " = None" } else { "" }
+      }
+      _ if f.optional => format!("{}: {} = None", name, r#type),
+      _ => format!("{}: {}", name, r#type),
+    };
+    format!("{}    {}\n", gen_comment(&f.doc, "    "), code)
+  }
+
+  fn gen_class(&mut self, record: &Record) -> String {
+    let is_keyword = |f: &Field| ident(&f.name) != f.name;
+    // the keys of a typed dict which are keywords are only given by the functional syntax
+    if self.options.python_style == PythonStyle::TypedDict && record.fields.iter().any(is_keyword) {
+      self.import("typing", "TypedDict");
+      let mut fields = String::new();
+      for f in &record.fields {
+        let mut r#type = self.type_str(&f.r#type, true);
+        if f.optional {
+          self.import("typing", "NotRequired");
+          self.import("typing", "Optional");
+          r#type = format!("NotRequired[Optional[{}]]", r#type);
+        }
+        fields.push_str(&format!("    {}: {},\n", python_string(&f.name), r#type));
+      }
+      return format!(
+        "{}{} = TypedDict({:?}, {{\n{}}})\n",
+        gen_comment(&record.doc, ""),
+        record.name,
+        record.name,
+        fields
+      );
+    }
+
+    let header = match self.options.python_style {
+      PythonStyle::TypedDict => {
+        self.import("typing", "TypedDict");
+        format!("class {}(TypedDict):\n", record.name)
+      }
+      PythonStyle::Dataclass => {
+        self.import("dataclasses", "dataclass");
+        format!("@dataclass(kw_only=True)\nclass {}:\n", record.name)
+      }
+      PythonStyle::Pydantic => {
+        self.import("pydantic", "BaseModel");
+        format!("class {}(BaseModel):\n", record.name)
+      }
+    };
+    let mut body = gen_docstring(&record.doc, "    ");
+    for f in &record.fields {
+      body.push_str(&self.gen_field(&record.name, f));
+    }
+    if body.is_empty() {
+      body.push_str("    pass\n");
+    }
+    format!("{}{}", header, body)
+  }
+
+  /// The int enums are `IntEnum`, the others are `str` enums of their values.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    let is_int = e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(_)));
+    let base = match is_int {
+      true => "IntEnum",
+      false => "str, Enum",
+    };
+    self.import("enum", if is_int { "IntEnum" } else { "Enum" });
+    let mut code = format!("class {}({}):\n", e.name, base);
+    code.push_str(&gen_docstring(&e.doc, "    "));
+    for v in &e.variants {
+      let value = match &v.value {
+        EnumValue::Int(value) if is_int => value.to_string(),
+        EnumValue::Int(_) => format!("{:?}", v.name),
+        EnumValue::String(value) => python_string(value),
+      };
+      code.push_str(&format!("    {} = {}\n", ident(&v.name), value));
+    }
+    if e.variants.is_empty() {
+      code.push_str("    pass\n");
+    }
+    code
+  }
+
+  fn gen_union(&mut self, union: &Union) -> String {
+    self.import("typing", "Union");
+    let variants: Vec<String> = union
+      .variants
+      .iter()
+      .map(|t| self.type_str(t, true))
+      .collect();
+    format!(
+      "{}{} = Union[{}]\n",
+      gen_comment(&union.doc, ""),
+      union.name,
+      variants.join(", ")
+    )
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    format!(
+      "{}{} = {}\n",
+      gen_comment(&alias.doc, ""),
+      alias.name,
+      self.type_str(&alias.r#type, true)
+    )
+  }
+
+  fn gen_constant(&mut self, constant: &Constant) -> String {
+    self.import("typing", "Final");
+    format!(
+      "{}{}: Final = {}\n",
+      gen_comment(&constant.doc, ""),
+      constant.name,
+      value_str(&constant.value)
+    )
+  }
+}
+
+/// The string literal of python, e.g. `"a\tb"`.
+fn python_string(s: &str) -> String {
+  let mut literal = String::from('"');
+  for c in s.chars() {
+    match c {
+      '"' => literal.push_str("\\\""),
+      '\\' => literal.push_str("\\\\"),
+      '\n' => literal.push_str("\\n"),
+      '\r' => literal.push_str("\\r"),
+      '\t' => literal.push_str("\\t"),
+      c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+      c => literal.push(c),
+    }
+  }
+  literal.push('"');
+  literal
+}
+
+fn value_str(value: &Value) -> String {
+  match value {
+    Value::Bool(true) => "True".to_string(),
+    Value::Bool(false) => "False".to_string(),
+    Value::Int(i) => i.to_string(),
+    Value::Float(f) => format!("{:?}", f),
+    Value::String(s) => python_string(s),
+    Value::List(items) => {
+      let items: Vec<String> = items.iter().map(value_str).collect();
+      format!("[{}]", items.join(", "))
+    }
+    Value::Map(entries) => {
+      let entries: Vec<String> = entries
+        .iter()
+        .map(|(k, v)| format!("{}: {}", value_str(k), value_str(v)))
+        .collect();
+      format!("{{{}}}", entries.join(", "))
+    }
+    Value::Ref(r) => r.clone(),
+  }
+}
+
+/// Generate the python classes of a module in the style of the options, the typed dicts, the
+/// dataclasses or the pydantic models. The modules of other namespaces are imported relatively from
+/// the same package.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    options,
+    froms: BTreeMap::new(),
+    relatives: BTreeSet::new(),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(generator.gen_class(r)),
+      Definition::Enum(e) => Some(generator.gen_enum(e)),
+      Definition::Union(u) => Some(generator.gen_union(u)),
+      Definition::Alias(a) => Some(generator.gen_alias(a)),
+      Definition::Constant(c) => Some(generator.gen_constant(c)),
+      Definition::Service(_) => None,
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+
+  let mut code = format!(
+    "# namespace {}\n\nfrom __future__ import annotations\n\n",
+    module.namespace
+  );
+  // the standard library, the third party and the relative imports are grouped in order
+  let (third_party, standard): (Vec<_>, Vec<_>) = generator
+    .froms
+    .iter()
+    .map(|(module, names)| {
+      let names: Vec<&str> = names.iter().copied().collect();
+      (
+        *module,
+        format!("from {} import {}\n", module, names.join(", ")),
+      )
+    })
+    .partition(|(module, _)| *module == "pydantic");
+  let groups: Vec<String> = [
+    standard.into_iter().map(|(_, i)| i).collect::<String>(),
+    third_party.into_iter().map(|(_, i)| i).collect(),
+    generator
+      .relatives
+      .iter()
+      .map(|module| format!("from . import {}\n", module))
+      .collect(),
+  ]
+  .into_iter()
+  .filter(|group| !group.is_empty())
+  .collect();
+  if !groups.is_empty() {
+    code.push_str(&groups.join("\n"));
+    code.push_str("\n\n");
+  }
+  code.push_str(&blocks.join("\n\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::{CodegenOptions, PythonStyle};
+  use ridl_ir::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union, Value,
+  };
+  use ridl_ir::types::Type;
+
+  fn module() -> Module {
+    Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The title.".to_string()),
+              ..Field::new("title", Type::String, false)
+            },
+            Field::new("status", Type::local("Status"), true),
+            Field::new(
+              "teacher",
+              Type::refer("Teacher", vec!["ek".to_string(), "user".to_string()]),
+              false,
+            ),
+          ],
+          doc: Some("A course.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Record(Record {
+          name: "Range".to_string(),
+          fields: vec![
+            Field::new("from", Type::Int32, false),
+            Field::new("to", Type::Int32, true),
+          ],
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("NOT_STARTED", 0), ("FINISHED", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Union(Union {
+          name: "Id".to_string(),
+          variants: vec![Type::Int32, Type::local("Course")],
+          ..Union::default()
+        }),
+        Definition::Constant(Constant {
+          name: "GRADES".to_string(),
+          r#type: Type::map(Type::Int32, Type::Bool),
+          value: Value::Map(vec![(Value::Int(1), Value::Bool(true))]),
+          doc: None,
+        }),
+      ],
+    }
+  }
+
+  #[test]
+  fn test_codegen() {
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module(), &CodegenOptions::default(), &mut warnings),
+      r#"# namespace ek.course
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import IntEnum
+from typing import Final, Optional, Union
+
+from . import ek_user
+
+
+@dataclass(kw_only=True)
+class Course:
+    """A course."""
+    id: str
+    # The title.
+    title: str
+    status: Optional[Status] = None
+    teacher: ek_user.Teacher
+
+
+@dataclass(kw_only=True)
+class Range:
+    from_: int
+    to: Optional[int] = None
+
+
+class Status(IntEnum):
+    NOT_STARTED = 0
+    FINISHED = 2
+
+
+Id = Union[int, "Course"]
+
+
+GRADES: Final = {1: True}
+"#
+    );
+    assert_eq!(
+      warnings,
+      ["`Range.from` is a keyword of python, it is renamed to `from_`"]
+    );
+  }
+
+  #[test]
+  fn test_codegen_styles() {
+    let options = |python_style| CodegenOptions {
+      python_style,
+      ..CodegenOptions::default()
+    };
+    let code = codegen(&module(), &options(PythonStyle::TypedDict), &mut Vec::new());
+    assert!(code.contains("from typing import Final, NotRequired, Optional, TypedDict, Union\n"));
+    assert!(code.contains("class Course(TypedDict):\n"));
+    assert!(code.contains("    status: NotRequired[Optional[Status]]\n"));
+    assert!(code.contains(
+      "Range = TypedDict(\"Range\", {\n    \"from\": int,\n    \"to\": NotRequired[Optional[int]],\n})\n"
+    ));
+
+    let code = codegen(&module(), &options(PythonStyle::Pydantic), &mut Vec::new());
+    assert!(code.contains("from pydantic import BaseModel, Field\n"));
+    assert!(code.contains("class Course(BaseModel):\n"));
+    assert!(code.contains("    from_: int = Field(alias=\"from\")\n"));
+    assert!(code.contains("    to: Optional[int] = None\n"));
+  }
+
+  #[test]
+  fn test_codegen_string_constant() {
+    let module = Module {
+      namespace: "foo".to_string(),
+      definitions: vec![Definition::Constant(Constant {
+        name: "GREETING".to_string(),
+        r#type: Type::String,
+        value: Value::String("h\u{e9}llo \u{4e16}\u{754c}\u{1}\u{85}\n\"$\\".to_string()),
+        doc: None,
+      })],
+    };
+    let code = codegen(&module, &CodegenOptions::default(), &mut Vec::new());
+    assert!(
+      code.contains(r#""héllo 世界\u0001\u0085\n\"$\\""#),
+      "{}",
+      code
+    );
+  }
+}
@@ -0,0 +1,664 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use ridl_ir::schema::{
+  Alias, Constant, Definition, Enum, EnumValue, Field, Module, Record, Union, Value,
+};
+use ridl_ir::types::Type;
+
+use crate::case::{snake_case, upper_camel_case, upper_snake_case};
+use crate::cycle::{local_type, recursive_records};
+use crate::{CodegenOptions, Int64Json};
+
+const KEYWORDS: &[&str] = &[
+  "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+  "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+  "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+  "static", "struct", "trait", "true", "try", "type", "typeof", "union", "unsafe", "unsized",
+  "use", "virtual", "where", "while", "yield",
+];
+
+/// The module of a namespace, which names its file and the paths of the types referred by other
+/// namespaces, e.g. `super::ek_ecp::Course`.
+pub fn module_name(namespace: &str) -> String {
+  match snake_case(namespace) {
+    name if name.is_empty() => "index".to_string(),
+    name => name,
+  }
+}
+
+/// The `mod.rs` declaring the modules of the namespaces.
+pub fn gen_mod<'a>(namespaces: impl Iterator<Item = &'a String>) -> String {
+  namespaces
+    .map(|namespace| format!("pub mod {};\n", module_name(namespace)))
+    .collect()
+}
+
+/// The identifier of a field, the keywords are raw identifiers.
+fn field_ident(name: &str) -> String {
+  let ident = snake_case(name);
+  match ident.as_str() {
+    "self" | "super" | "crate" | "" => format!("{}_", ident),
+    i if KEYWORDS.contains(&i) => format!("r#{}", i),
+    i if i.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", i),
+    _ => ident,
+  }
+}
+
+fn type_ident(name: &str) -> String {
+  upper_camel_case(name)
+}
+
+/// The same wire name is kept by renaming the fields or variants whose identifiers differ.
+fn rename_of(ident: &str, wire_name: &str) -> Option<String> {
+  match ident.strip_prefix("r#").unwrap_or(ident) == wire_name {
+    true => None,
+    false => Some(format!("rename = {:?}", wire_name)),
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  doc
+    .iter()
+    .flat_map(|d| d.lines())
+    .map(|line| format!("{}/// {}\n", indent, line).replace("/// \n", "///\n"))
+    .collect()
+}
+
+/// Generates the rust of a module, the uses the code needs are collected along.
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  aliases: HashMap<&'a str, &'a Type>,
+  /// The records holding themselves, the fields referring them are boxed, see `recursive_records`.
+  recursive: HashSet<String>,
+  uses: BTreeSet<&'static str>,
+  /// The items used from `serde_with`, which are used by a single `use`.
+  serde_with: BTreeSet<&'static str>,
+  warnings: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+  fn resolve<'t>(&self, r#type: &'t Type) -> &'t Type
+  where
+    'a: 't,
+  {
+    let mut r#type = r#type;
+    for _ in 0..self.aliases.len() {
+      match r#type {
+        Type::Ref(r) if r.namespace.is_empty() => match self.aliases.get(r.name.as_str()) {
+          Some(t) => r#type = t,
+          None => break,
+        },
+        _ => break,
+      }
+    }
+    r#type
+  }
+
+  fn type_str(&mut self, r#type: &Type) -> String {
+    match r#type {
+      Type::Bool => "bool".to_string(),
+      Type::Int8 => "i8".to_string(),
+      Type::Int16 => "i16".to_string(),
+      Type::Int32 => "i32".to_string(),
+      Type::Int64 => "i64".to_string(),
+      Type::UInt8 => "u8".to_string(),
+      Type::UInt16 => "u16".to_string(),
+      Type::UInt32 => "u32".to_string(),
+      Type::UInt64 => "u64".to_string(),
+      Type::Float32 => "f32".to_string(),
+      Type::Float64 => "f64".to_string(),
+      Type::String
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => "String".to_string(),
+      Type::Bytes => "Vec<u8>".to_string(),
+      Type::Any => "serde_json::Value".to_string(),
+      Type::Void => "()".to_string(),
+      Type::List(item) | Type::Set(item) => format!("Vec<{}>", self.type_str(item)),
+      Type::Map(key, value) => {
+        self.uses.insert("std::collections::BTreeMap");
+        format!("BTreeMap<{}, {}>", self.type_str(key), self.type_str(value))
+      }
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          type_ident(&r.name)
+        } else {
+          format!(
+            "super::{}::{}",
+            module_name(&namespace),
+            type_ident(&r.name)
+          )
+        }
+      }
+    }
+  }
+
+  /// The `serde_as` type of the types holding 64-bit integers presented as json strings or bytes
+  /// presented as base64 strings, e.g. `Vec<DisplayFromStr>` of `list<i64>`.
+  fn serde_as_str(&mut self, r#type: &Type) -> Option<String> {
+    match self.resolve(r#type) {
+      Type::Int64 | Type::UInt64 if self.options.int64_json == Int64Json::String => {
+        self.serde_with.insert("DisplayFromStr");
+        Some("DisplayFromStr".to_string())
+      }
+      Type::Bytes => {
+        self.serde_with.insert("base64::Base64");
+        Some("Base64".to_string())
+      }
+      Type::List(item) | Type::Set(item) => self.serde_as_str(item).map(|a| format!("Vec<{}>", a)),
+      Type::Map(_, value) => self
+        .serde_as_str(value)
+        .map(|a| format!("BTreeMap<_, {}>", a)),
+      _ => None,
+    }
+  }
+
+  fn gen_field(&mut self, f: &Field) -> (String, bool) {
+    let ident = field_ident(&f.name);
+    let mut r#type = self.type_str(&f.r#type);
+    // the structs holding each other are boxed to have a size
+    if local_type(self.module, &f.r#type).is_some_and(|name| self.recursive.contains(name)) {
+      r#type = format!("Box<{}>", r#type);
+    }
+    let mut serde_as = self.serde_as_str(&f.r#type);
+    let mut serde: Vec<String> = rename_of(&ident, &f.name).into_iter().collect();
+    if f.optional {
+      r#type = format!("Option<{}>", r#type);
+      serde_as = serde_as.map(|a| format!("Option<{}>", a));
+      serde.push("default".to_string());
+      serde.push("skip_serializing_if = \"Option::is_none\"".to_string());
+    }
+
+    let mut code = gen_doc(&f.doc, "  ");
+    if let Some(serde_as) = &serde_as {
+      code.push_str(&format!("  #[serde_as(as = {:?})]\n", serde_as));
+    }
+    if !serde.is_empty() {
+      code.push_str(&format!("  #[serde({})]\n", serde.join(", ")));
+    }
+    code.push_str(&format!("  pub {}: {},\n", ident, r#type));
+    (code, serde_as.is_some())
+  }
+
+  fn derives(&mut self, serde_as: bool) -> String {
+    self.uses.insert("serde::{Deserialize, Serialize}");
+    let mut code = String::new();
+    if serde_as {
+      self.serde_with.insert("serde_as");
+      code.push_str("#[serde_as]\n");
+    }
+    code.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+    code
+  }
+
+  fn gen_struct(&mut self, record: &Record) -> String {
+    let name = type_ident(&record.name);
+    let mut fields = String::new();
+    let mut serde_as = false;
+    for f in &record.fields {
+      let (code, field_serde_as) = self.gen_field(f);
+      fields.push_str(&code);
+      serde_as |= field_serde_as;
+    }
+    format!(
+      "{}{}pub struct {} {{\n{}}}\n",
+      gen_doc(&record.doc, ""),
+      self.derives(serde_as),
+      name,
+      fields
+    )
+  }
+
+  /// The int values are the discriminants serialized by `serde_repr`, the string values are the
+  /// names of the variants.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    let name = type_ident(&e.name);
+    let mut code = gen_doc(&e.doc, "");
+    let is_int = e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(_)));
+    if is_int {
+      self
+        .uses
+        .insert("serde_repr::{Deserialize_repr, Serialize_repr}");
+      let fits_i32 = e
+        .variants
+        .iter()
+        .all(|v| matches!(v.value, EnumValue::Int(i) if i32::try_from(i).is_ok()));
+      code.push_str(
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr, Deserialize_repr)]\n",
+      );
+      code.push_str(if fits_i32 {
+        "#[repr(i32)]\n"
+      } else {
+        "#[repr(i64)]\n"
+      });
+    } else {
+      self.uses.insert("serde::{Deserialize, Serialize}");
+      code.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]\n");
+    }
+    code.push_str(&format!("pub enum {} {{\n", name));
+    let mut values = BTreeSet::new();
+    for v in &e.variants {
+      let ident = type_ident(&v.name);
+      match &v.value {
+        EnumValue::Int(value) => {
+          // the discriminants are unique, the aliases of a value are dropped
+          if !values.insert(*value) {
+            self.warnings.push(format!(
+              "`{}.{}` is an alias of the value {}, it is skipped",
+              e.name, v.name, value
+            ));
+            continue;
+          }
+          code.push_str(&format!("  {} = {},\n", ident, value));
+        }
+        EnumValue::String(value) => {
+          if let Some(rename) = rename_of(&ident, value) {
+            code.push_str(&format!("  #[serde({})]\n", rename));
+          }
+          code.push_str(&format!("  {},\n", ident));
+        }
+      }
+    }
+    code.push_str("}\n");
+    code
+  }
+
+  /// The variants of a union are untagged, they are named after their types.
+  fn gen_union(&mut self, union: &Union) -> String {
+    let mut variants = String::new();
+    let mut serde_as = false;
+    for t in &union.variants {
+      let name = match t {
+        Type::Ref(r) => type_ident(&r.name),
+        t => upper_camel_case(&t.to_string()),
+      };
+      let attr = match self.serde_as_str(t) {
+        Some(a) => {
+          serde_as = true;
+          format!("#[serde_as(as = {:?})] ", a)
+        }
+        None => String::new(),
+      };
+      let r#type = self.type_str(t);
+      variants.push_str(&format!("  {}({}{}),\n", name, attr, r#type));
+    }
+    format!(
+      "{}{}#[serde(untagged)]\npub enum {} {{\n{}}}\n",
+      gen_doc(&union.doc, ""),
+      self.derives(serde_as),
+      type_ident(&union.name),
+      variants
+    )
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    format!(
+      "{}pub type {} = {};\n",
+      gen_doc(&alias.doc, ""),
+      type_ident(&alias.name),
+      self.type_str(&alias.r#type)
+    )
+  }
+
+  /// The constants of the scalars and the lists of scalars, the others are not const expressions.
+  fn gen_constant(&mut self, constant: &Constant) -> Option<String> {
+    let scalar = |t: &Type| match t {
+      Type::String => Some("&str".to_string()),
+      Type::Bool
+      | Type::Int8
+      | Type::Int16
+      | Type::Int32
+      | Type::Int64
+      | Type::UInt8
+      | Type::UInt16
+      | Type::UInt32
+      | Type::UInt64
+      | Type::Float32
+      | Type::Float64 => Some(self.scalar_str(t)),
+      _ => None,
+    };
+    let r#type = match self.resolve(&constant.r#type) {
+      Type::List(item) | Type::Set(item) => scalar(self.resolve(item)).map(|t| format!("&[{}]", t)),
+      t => scalar(t),
+    };
+    let value = value_str(&constant.value);
+    match (r#type, value) {
+      (Some(r#type), Some(value)) => Some(format!(
+        "{}pub const {}: {} = {};\n",
+        gen_doc(&constant.doc, ""),
+        upper_snake_case(&constant.name),
+        r#type,
+        value
+      )),
+      _ => {
+        self.warnings.push(format!(
+          "constant `{}` of `{}` is not a const expression of rust, it is skipped",
+          constant.name, constant.r#type
+        ));
+        None
+      }
+    }
+  }
+
+  /// The type of the scalars, which need no uses.
+  fn scalar_str(&self, r#type: &Type) -> String {
+    match r#type {
+      Type::Bool => "bool",
+      Type::Int8 => "i8",
+      Type::Int16 => "i16",
+      Type::Int32 => "i32",
+      Type::Int64 => "i64",
+      Type::UInt8 => "u8",
+      Type::UInt16 => "u16",
+      Type::UInt32 => "u32",
+      Type::UInt64 => "u64",
+      Type::Float32 => "f32",
+      _ => "f64",
+    }
+    .to_string()
+  }
+}
+
+fn value_str(value: &Value) -> Option<String> {
+  match value {
+    Value::Bool(b) => Some(b.to_string()),
+    Value::Int(i) => Some(i.to_string()),
+    Value::Float(f) => Some(format!("{:?}", f)),
+    Value::String(s) => Some(format!("{:?}", s)),
+    Value::List(items) => {
+      let items: Option<Vec<String>> = items.iter().map(value_str).collect();
+      items.map(|items| format!("&[{}]", items.join(", ")))
+    }
+    Value::Map(_) | Value::Ref(_) => None,
+  }
+}
+
+/// Generate the serde types of a module, the optional fields are `Option` skipped when absent and
+/// the fields keep their wire names. The types of other namespaces are referred by the paths of
+/// their sibling modules declared in `mod.rs`.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    options,
+    aliases: module
+      .definitions
+      .iter()
+      .filter_map(|d| match d {
+        Definition::Alias(a) => Some((a.name.as_str(), &a.r#type)),
+        _ => None,
+      })
+      .collect(),
+    recursive: recursive_records(module),
+    uses: BTreeSet::new(),
+    serde_with: BTreeSet::new(),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(generator.gen_struct(r)),
+      Definition::Enum(e) => Some(generator.gen_enum(e)),
+      Definition::Union(u) => Some(generator.gen_union(u)),
+      Definition::Alias(a) => Some(generator.gen_alias(a)),
+      Definition::Constant(c) => generator.gen_constant(c),
+      Definition::Service(_) => None,
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+
+  let mut uses: BTreeSet<String> = generator.uses.iter().map(|u| u.to_string()).collect();
+  if !generator.serde_with.is_empty() {
+    // the modules and macros go before the types, as rustfmt orders them
+    let mut items: Vec<&str> = generator.serde_with.iter().copied().collect();
+    items.sort_by_key(|i| (i.starts_with(|c: char| c.is_ascii_uppercase()), *i));
+    uses.insert(format!("serde_with::{{{}}}", items.join(", ")));
+  }
+  let mut code = format!("// namespace {}\n\n", module.namespace);
+  if !uses.is_empty() {
+    for r#use in &uses {
+      code.push_str(&format!("use {};\n", r#use));
+    }
+    code.push('\n');
+  }
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::{CodegenOptions, Int64Json};
+  use ridl_ir::schema::{
+    Alias, Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union, Value,
+  };
+  use ridl_ir::types::Type;
+
+  fn module() -> Module {
+    Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The title.".to_string()),
+              ..Field::new("courseTitle", Type::String, false)
+            },
+            Field::new("type", Type::local("Status"), true),
+            Field::new("lessonIds", Type::list(Type::Int64), true),
+            Field::new("scores", Type::map(Type::String, Type::Float64), false),
+            Field::new("parent", Type::local("Course"), true),
+            Field::new(
+              "teacher",
+              Type::refer("Teacher", vec!["ek".to_string(), "user".to_string()]),
+              false,
+            ),
+          ],
+          doc: Some("A course.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("NOT_STARTED", 0), ("FINISHED", 2), ("DONE", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Enum(Enum {
+          name: "Level".to_string(),
+          variants: [("High", "high"), ("Low", "Low")]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::String(value.to_string()),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Union(Union {
+          name: "Id".to_string(),
+          variants: vec![Type::Int64, Type::local("Course")],
+          ..Union::default()
+        }),
+        Definition::Alias(Alias {
+          name: "Ids".to_string(),
+          r#type: Type::list(Type::Int32),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "maxCount".to_string(),
+          r#type: Type::Int32,
+          value: Value::Int(10),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "GRADES".to_string(),
+          r#type: Type::map(Type::Int32, Type::String),
+          value: Value::Map(vec![(Value::Int(1), Value::String("one".to_string()))]),
+          doc: None,
+        }),
+      ],
+    }
+  }
+
+  #[test]
+  fn test_codegen() {
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module(), &CodegenOptions::default(), &mut warnings),
+      r#"// namespace ek.course
+
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_with::{serde_as, DisplayFromStr};
+use std::collections::BTreeMap;
+
+/// A course.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Course {
+  #[serde_as(as = "DisplayFromStr")]
+  pub id: i64,
+  /// The title.
+  #[serde(rename = "courseTitle")]
+  pub course_title: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub r#type: Option<Status>,
+  #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
+  #[serde(rename = "lessonIds", default, skip_serializing_if = "Option::is_none")]
+  pub lesson_ids: Option<Vec<i64>>,
+  pub scores: BTreeMap<String, f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub parent: Option<Box<Course>>,
+  pub teacher: super::ek_user::Teacher,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr, Deserialize_repr)]
+#[repr(i32)]
+pub enum Status {
+  NotStarted = 0,
+  Finished = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Level {
+  #[serde(rename = "high")]
+  High,
+  Low,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Id {
+  I64(#[serde_as(as = "DisplayFromStr")] i64),
+  Course(Course),
+}
+
+pub type Ids = Vec<i32>;
+
+pub const MAX_COUNT: i32 = 10;
+"#
+    );
+    assert_eq!(
+      warnings,
+      [
+        "`Status.DONE` is an alias of the value 2, it is skipped",
+        "constant `GRADES` of `map<i32, string>` is not a const expression of rust, it is skipped"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_codegen_int64_number() {
+    let options = CodegenOptions {
+      int64_json: Int64Json::Number,
+      ..CodegenOptions::default()
+    };
+    let code = codegen(&module(), &options, &mut Vec::new());
+    assert!(!code.contains("serde_as"));
+    assert!(code.contains("  pub id: i64,\n"));
+  }
+
+  #[test]
+  fn test_codegen_recursive_and_bytes() {
+    let record = |name: &str, fields: Vec<Field>| {
+      Definition::Record(Record {
+        name: name.to_string(),
+        fields,
+        ..Record::default()
+      })
+    };
+    let module = Module {
+      namespace: "foo".to_string(),
+      definitions: vec![
+        record(
+          "A",
+          vec![
+            Field::new("b", Type::local("B"), false),
+            Field::new("data", Type::Bytes, false),
+            Field::new("chunks", Type::list(Type::Bytes), true),
+          ],
+        ),
+        record(
+          "B",
+          vec![
+            Field::new("a", Type::local("A"), true),
+            Field::new("c", Type::local("C"), false),
+          ],
+        ),
+        record(
+          "C",
+          vec![Field::new("bs", Type::list(Type::local("B")), false)],
+        ),
+      ],
+    };
+    let options = CodegenOptions {
+      int64_json: Int64Json::Number,
+      ..CodegenOptions::default()
+    };
+    assert_eq!(
+      codegen(&module, &options, &mut Vec::new()),
+      r#"// namespace foo
+
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as};
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct A {
+  pub b: Box<B>,
+  #[serde_as(as = "Base64")]
+  pub data: Vec<u8>,
+  #[serde_as(as = "Option<Vec<Base64>>")]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub chunks: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct B {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub a: Option<Box<A>>,
+  pub c: C,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct C {
+  pub bs: Vec<B>,
+}
+"#
+    );
+  }
+}
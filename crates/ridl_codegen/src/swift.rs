@@ -0,0 +1,646 @@
+use std::collections::{BTreeMap, HashSet};
+
+use ridl_ir::schema::{Alias, Constant, Definition, Enum, EnumValue, Module, Record, Union, Value};
+use ridl_ir::types::Type;
+
+use crate::case::{lower_camel_case, upper_camel_case};
+use crate::cycle::recursive_records;
+use crate::{CodegenOptions, Int64Json};
+
+const KEYWORDS: &[&str] = &[
+  "Any",
+  "Self",
+  "as",
+  "associatedtype",
+  "break",
+  "case",
+  "catch",
+  "class",
+  "continue",
+  "default",
+  "defer",
+  "deinit",
+  "do",
+  "else",
+  "enum",
+  "extension",
+  "fallthrough",
+  "false",
+  "fileprivate",
+  "for",
+  "func",
+  "guard",
+  "if",
+  "import",
+  "in",
+  "init",
+  "inout",
+  "internal",
+  "is",
+  "let",
+  "nil",
+  "open",
+  "operator",
+  "private",
+  "protocol",
+  "public",
+  "repeat",
+  "rethrows",
+  "return",
+  "self",
+  "static",
+  "struct",
+  "subscript",
+  "super",
+  "switch",
+  "throw",
+  "throws",
+  "true",
+  "try",
+  "typealias",
+  "var",
+  "where",
+  "while",
+];
+
+/// The 64-bit integers presented as json strings, e.g. `StringInteger<Int64>`, declared in
+/// `StringInteger.swift` under `--i64 string`.
+const STRING_INTEGER: &str = r#"import Foundation
+
+/// An integer presented as a json string, e.g. `"9007199254740993"`.
+struct StringInteger<Value: FixedWidthInteger & Codable & Hashable>: Codable, Hashable, ExpressibleByIntegerLiteral {
+    let value: Value
+
+    init(_ value: Value) {
+        self.value = value
+    }
+
+    init(integerLiteral value: Value.IntegerLiteralType) {
+        self.value = Value(integerLiteral: value)
+    }
+
+    init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        let string = try container.decode(String.self)
+        guard let value = Value(string) else {
+            throw DecodingError.dataCorruptedError(in: container, debugDescription: "\(string) is not an integer")
+        }
+        self.value = value
+    }
+
+    func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        try container.encode(String(value))
+    }
+}
+"#;
+
+/// The `StringInteger.swift` declaring the wrapper of the 64-bit integers presented as strings.
+pub fn gen_string_integer() -> String {
+  STRING_INTEGER.to_string()
+}
+
+/// The caseless enum of a namespace, nested by the dots, e.g. `Ek.Course` of `ek.course`. The types
+/// of a namespace are declared in its extension.
+pub fn namespace_path(namespace: &str) -> String {
+  namespace
+    .split('.')
+    .map(upper_camel_case)
+    .collect::<Vec<String>>()
+    .join(".")
+}
+
+/// The file of a namespace, e.g. `EkCourse` of `ek.course`.
+pub fn file_stem(namespace: &str) -> String {
+  match upper_camel_case(namespace) {
+    stem if stem.is_empty() => "Index".to_string(),
+    stem => stem,
+  }
+}
+
+/// The `Namespaces.swift` declaring the nested enums of the namespaces, which are extended by the
+/// files of the namespaces.
+pub fn gen_namespaces<'a>(namespaces: impl Iterator<Item = &'a String>) -> String {
+  #[derive(Default)]
+  struct Node(BTreeMap<String, Node>);
+
+  fn gen(node: &Node, indent: &str, code: &mut String) {
+    for (name, child) in &node.0 {
+      match child.0.is_empty() {
+        true => code.push_str(&format!("{}enum {} {{}}\n", indent, name)),
+        false => {
+          code.push_str(&format!("{}enum {} {{\n", indent, name));
+          gen(child, &format!("{}    ", indent), code);
+          code.push_str(&format!("{}}}\n", indent));
+        }
+      }
+    }
+  }
+
+  let mut root = Node::default();
+  for namespace in namespaces.filter(|n| !n.is_empty()) {
+    let mut node = &mut root;
+    for name in namespace_path(namespace).split('.') {
+      node = node.0.entry(name.to_string()).or_default();
+    }
+  }
+  let mut code = String::new();
+  gen(&root, "", &mut code);
+  code
+}
+
+fn ident(name: &str) -> String {
+  let ident = lower_camel_case(name);
+  match KEYWORDS.contains(&ident.as_str()) {
+    true => format!("`{}`", ident),
+    false => ident,
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  doc
+    .iter()
+    .flat_map(|d| d.lines())
+    .map(|line| format!("{}/// {}\n", indent, line).replace("/// \n", "///\n"))
+    .collect()
+}
+
+/// Generates the swift of a module, the definitions are indented in the extension of the namespace.
+struct Generator<'a> {
+  module: &'a Module,
+  options: &'a CodegenOptions,
+  indent: &'static str,
+  /// The records declared as final classes, see `recursive_records`.
+  classes: HashSet<String>,
+  warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+  fn type_str(&mut self, r#type: &Type, context: &str) -> String {
+    match r#type {
+      Type::Int64 if self.options.int64_json == Int64Json::String => {
+        "StringInteger<Int64>".to_string()
+      }
+      Type::UInt64 if self.options.int64_json == Int64Json::String => {
+        "StringInteger<UInt64>".to_string()
+      }
+      Type::Bool => "Bool".to_string(),
+      Type::Int8 => "Int8".to_string(),
+      Type::Int16 => "Int16".to_string(),
+      Type::Int32 => "Int32".to_string(),
+      Type::Int64 => "Int64".to_string(),
+      Type::UInt8 => "UInt8".to_string(),
+      Type::UInt16 => "UInt16".to_string(),
+      Type::UInt32 => "UInt32".to_string(),
+      Type::UInt64 => "UInt64".to_string(),
+      Type::Float32 => "Float".to_string(),
+      Type::Float64 => "Double".to_string(),
+      Type::String
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => "String".to_string(),
+      Type::Bytes => "Data".to_string(),
+      Type::Any => {
+        self.warnings.push(format!(
+          "`{}` may be any value, swift has no codable value of any json and it is a string",
+          context
+        ));
+        "String".to_string()
+      }
+      Type::Void => "Never?".to_string(),
+      Type::List(item) | Type::Set(item) => format!("[{}]", self.type_str(item, context)),
+      Type::Map(key, value) => format!(
+        "[{}: {}]",
+        self.type_str(key, context),
+        self.type_str(value, context)
+      ),
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          upper_camel_case(&r.name)
+        } else {
+          format!(
+            "{}.{}",
+            namespace_path(&namespace),
+            upper_camel_case(&r.name)
+          )
+        }
+      }
+    }
+  }
+
+  /// The structs keep the wire names of their properties by `CodingKeys`. The records holding
+  /// themselves, directly or not, are final classes, as the structs can not be recursive.
+  fn gen_struct(&mut self, record: &Record) -> String {
+    let i = self.indent;
+    let name = upper_camel_case(&record.name);
+    let keyword = match self.classes.contains(&record.name) {
+      true => "final class",
+      false => "struct",
+    };
+    let mut code = gen_doc(&record.doc, i);
+    code.push_str(&format!("{}{} {}: Codable {{\n", i, keyword, name));
+    let mut keys = Vec::new();
+    for f in &record.fields {
+      let context = format!("{}.{}", record.name, f.name);
+      let property = ident(&f.name);
+      let optional = if f.optional { "?" } else { "" };
+      code.push_str(&gen_doc(&f.doc, &format!("{}    ", i)));
+      code.push_str(&format!(
+        "{}    let {}: {}{}\n",
+        i,
+        property,
+        self.type_str(&f.r#type, &context),
+        optional
+      ));
+      keys.push(match property.trim_matches('`') == f.name {
+        true => format!("{}        case {}\n", i, property),
+        false => format!("{}        case {} = {:?}\n", i, property, f.name),
+      });
+    }
+    if keys.iter().any(|k| k.contains(" = ")) {
+      code.push_str(&format!(
+        "\n{}    enum CodingKeys: String, CodingKey {{\n{}{}    }}\n",
+        i,
+        keys.concat(),
+        i
+      ));
+    }
+    code.push_str(&format!("{}}}\n", i));
+    code
+  }
+
+  /// The enums have the raw values of the idl, the duplicated raw values are not allowed.
+  fn gen_enum(&mut self, e: &Enum) -> String {
+    let i = self.indent;
+    let is_int = e
+      .variants
+      .iter()
+      .all(|v| matches!(v.value, EnumValue::Int(_)));
+    let raw = match is_int {
+      true
+        if e
+          .variants
+          .iter()
+          .all(|v| matches!(v.value, EnumValue::Int(i) if i32::try_from(i).is_ok())) =>
+      {
+        "Int32"
+      }
+      true => "Int64",
+      false => "String",
+    };
+    let mut code = gen_doc(&e.doc, i);
+    code.push_str(&format!(
+      "{}enum {}: {}, Codable {{\n",
+      i,
+      upper_camel_case(&e.name),
+      raw
+    ));
+    let mut values = HashSet::new();
+    for v in &e.variants {
+      let value = match &v.value {
+        EnumValue::Int(value) if is_int => value.to_string(),
+        EnumValue::Int(_) => format!("{:?}", v.name),
+        EnumValue::String(value) => format!("{:?}", value),
+      };
+      if !values.insert(value.clone()) {
+        self.warnings.push(format!(
+          "`{}.{}` is an alias of the value {}, it is skipped",
+          e.name, v.name, value
+        ));
+        continue;
+      }
+      code.push_str(&format!("{}    case {} = {}\n", i, ident(&v.name), value));
+    }
+    code.push_str(&format!("{}}}\n", i));
+    code
+  }
+
+  /// The unions are enums of the variants, which decode the first variant the value is of.
+  fn gen_union(&mut self, union: &Union) -> String {
+    let i = self.indent;
+    let name = upper_camel_case(&union.name);
+    let variants: Vec<(String, String)> = union
+      .variants
+      .iter()
+      .map(|t| {
+        let case = match t {
+          Type::Ref(r) => ident(&r.name),
+          t => ident(&t.to_string()),
+        };
+        (case, self.type_str(t, &union.name))
+      })
+      .collect();
+    let mut code = gen_doc(&union.doc, i);
+    code.push_str(&format!("{}enum {}: Codable {{\n", i, name));
+    for (case, r#type) in &variants {
+      code.push_str(&format!("{}    case {}({})\n", i, case, r#type));
+    }
+    code.push_str(&format!(
+      "\n{i}    init(from decoder: Decoder) throws {{\n{i}        let container = try decoder.singleValueContainer()\n"
+    ));
+    for (case, r#type) in &variants {
+      code.push_str(&format!(
+        "{i}        if let value = try? container.decode({}.self) {{\n{i}            self = .{}(value)\n{i}            return\n{i}        }}\n",
+        r#type, case
+      ));
+    }
+    code.push_str(&format!(
+      "{i}        throw DecodingError.dataCorruptedError(in: container, debugDescription: \"no variant of {name} matches\")\n{i}    }}\n"
+    ));
+    code.push_str(&format!(
+      "\n{i}    func encode(to encoder: Encoder) throws {{\n{i}        var container = encoder.singleValueContainer()\n{i}        switch self {{\n"
+    ));
+    for (case, _) in &variants {
+      code.push_str(&format!(
+        "{i}        case .{}(let value):\n{i}            try container.encode(value)\n",
+        case
+      ));
+    }
+    code.push_str(&format!("{i}        }}\n{i}    }}\n{i}}}\n"));
+    code
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    let r#type = self.type_str(&alias.r#type, &alias.name);
+    format!(
+      "{}{}typealias {} = {}\n",
+      gen_doc(&alias.doc, self.indent),
+      self.indent,
+      upper_camel_case(&alias.name),
+      r#type
+    )
+  }
+
+  /// The constants are static in the extension of the namespace.
+  fn gen_constant(&mut self, constant: &Constant) -> Option<String> {
+    let Some(value) = value_str(&constant.value) else {
+      self.warnings.push(format!(
+        "constant `{}` refers to other values, it is skipped",
+        constant.name
+      ));
+      return None;
+    };
+    let keyword = match self.indent {
+      "" => "let",
+      _ => "static let",
+    };
+    let r#type = self.type_str(&constant.r#type, &constant.name);
+    Some(format!(
+      "{}{}{} {}: {} = {}\n",
+      gen_doc(&constant.doc, self.indent),
+      self.indent,
+      keyword,
+      ident(&constant.name),
+      r#type,
+      value
+    ))
+  }
+}
+
+fn value_str(value: &Value) -> Option<String> {
+  match value {
+    Value::Bool(b) => Some(b.to_string()),
+    Value::Int(i) => Some(i.to_string()),
+    Value::Float(f) => Some(format!("{:?}", f)),
+    Value::String(s) => Some(format!("{:?}", s)),
+    Value::List(items) => {
+      let items: Option<Vec<String>> = items.iter().map(value_str).collect();
+      items.map(|items| format!("[{}]", items.join(", ")))
+    }
+    Value::Map(entries) if entries.is_empty() => Some("[:]".to_string()),
+    Value::Map(entries) => {
+      let entries: Option<Vec<String>> = entries
+        .iter()
+        .map(|(k, v)| Some(format!("{}: {}", value_str(k)?, value_str(v)?)))
+        .collect();
+      entries.map(|entries| format!("[{}]", entries.join(", ")))
+    }
+    Value::Ref(_) => None,
+  }
+}
+
+/// Generate the codable types of a module in the extension of the enum of its namespace, which is
+/// declared in `Namespaces.swift`. The types of other namespaces are referred by their paths, and
+/// the 64-bit integers presented as strings are wrapped by `StringInteger`.
+pub fn codegen(module: &Module, options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    options,
+    indent: if module.namespace.is_empty() {
+      ""
+    } else {
+      "    "
+    },
+    classes: recursive_records(module),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .filter_map(|d| match d {
+      Definition::Record(r) => Some(generator.gen_struct(r)),
+      Definition::Enum(e) => Some(generator.gen_enum(e)),
+      Definition::Union(u) => Some(generator.gen_union(u)),
+      Definition::Alias(a) => Some(generator.gen_alias(a)),
+      Definition::Constant(c) => generator.gen_constant(c),
+      Definition::Service(_) => None,
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+
+  let mut code = format!("// namespace {}\n\nimport Foundation\n\n", module.namespace);
+  match module.namespace.is_empty() {
+    true => code.push_str(&blocks.join("\n")),
+    false => code.push_str(&format!(
+      "extension {} {{\n{}}}\n",
+      namespace_path(&module.namespace),
+      blocks.join("\n")
+    )),
+  }
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::{codegen, gen_namespaces};
+  use crate::{CodegenOptions, Int64Json};
+  use ridl_ir::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field {
+              doc: Some("The title.".to_string()),
+              ..Field::new("course_title", Type::String, true)
+            },
+            Field::new("scores", Type::map(Type::String, Type::Float64), false),
+            Field::new(
+              "teacher",
+              Type::refer("Teacher", vec!["ek".to_string(), "user".to_string()]),
+              false,
+            ),
+          ],
+          doc: Some("A course.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("NOT_STARTED", 0), ("FINISHED", 2), ("DONE", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          doc: None,
+        }),
+        Definition::Union(Union {
+          name: "Id".to_string(),
+          variants: vec![Type::Int32, Type::local("Course")],
+          ..Union::default()
+        }),
+        Definition::Constant(Constant {
+          name: "MAX_COUNT".to_string(),
+          r#type: Type::Int32,
+          value: Value::Int(10),
+          doc: None,
+        }),
+      ],
+    };
+    let options = CodegenOptions {
+      int64_json: Int64Json::Number,
+      ..CodegenOptions::default()
+    };
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module, &options, &mut warnings),
+      r#"// namespace ek.course
+
+import Foundation
+
+extension Ek.Course {
+    /// A course.
+    struct Course: Codable {
+        let id: Int64
+        /// The title.
+        let courseTitle: String?
+        let scores: [String: Double]
+        let teacher: Ek.User.Teacher
+
+        enum CodingKeys: String, CodingKey {
+            case id
+            case courseTitle = "course_title"
+            case scores
+            case teacher
+        }
+    }
+
+    enum Status: Int32, Codable {
+        case notStarted = 0
+        case finished = 2
+    }
+
+    enum Id: Codable {
+        case i32(Int32)
+        case course(Course)
+
+        init(from decoder: Decoder) throws {
+            let container = try decoder.singleValueContainer()
+            if let value = try? container.decode(Int32.self) {
+                self = .i32(value)
+                return
+            }
+            if let value = try? container.decode(Course.self) {
+                self = .course(value)
+                return
+            }
+            throw DecodingError.dataCorruptedError(in: container, debugDescription: "no variant of Id matches")
+        }
+
+        func encode(to encoder: Encoder) throws {
+            var container = encoder.singleValueContainer()
+            switch self {
+            case .i32(let value):
+                try container.encode(value)
+            case .course(let value):
+                try container.encode(value)
+            }
+        }
+    }
+
+    static let maxCount: Int32 = 10
+}
+"#
+    );
+    assert_eq!(
+      warnings,
+      ["`Status.DONE` is an alias of the value 2, it is skipped"]
+    );
+    assert_eq!(
+      gen_namespaces(
+        ["ek.course", "ek.user", "base", ""]
+          .map(String::from)
+          .iter()
+      ),
+      "enum Base {}\nenum Ek {\n    enum Course {}\n    enum User {}\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_codegen_int64_string_and_recursion() {
+    let record = |name: &str, fields: Vec<Field>| {
+      Definition::Record(Record {
+        name: name.to_string(),
+        fields,
+        ..Record::default()
+      })
+    };
+    let module = Module {
+      namespace: String::new(),
+      definitions: vec![
+        record(
+          "Lesson",
+          vec![
+            Field::new("id", Type::Int64, false),
+            Field::new("ids", Type::list(Type::UInt64), true),
+            Field::new("course", Type::local("Course"), true),
+          ],
+        ),
+        record(
+          "Course",
+          vec![
+            Field::new("lesson", Type::local("Lesson"), false),
+            Field::new("lessons", Type::list(Type::local("Lesson")), false),
+          ],
+        ),
+        record(
+          "Teacher",
+          vec![Field::new(
+            "courses",
+            Type::list(Type::local("Course")),
+            false,
+          )],
+        ),
+      ],
+    };
+    let code = codegen(&module, &CodegenOptions::default(), &mut Vec::new());
+    // the records holding each other are classes, the lists of them do not count
+    assert!(code.contains(
+      "final class Lesson: Codable {\n    let id: StringInteger<Int64>\n    let ids: [StringInteger<UInt64>]?\n    let course: Course?\n}\n"
+    ));
+    assert!(code.contains("final class Course: Codable {\n"));
+    assert!(code.contains("struct Teacher: Codable {\n"));
+  }
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeSet;
+
+use ridl_ir::schema::{
+  Alias, Constant, Definition, Enum, EnumValue, Field, Module, Record, Service, Union, Value,
+};
+use ridl_ir::types::Type;
+
+use crate::case::snake_case;
+use crate::CodegenOptions;
+
+/// The thrift program of a namespace, which names its file and prefixes its types when included.
+/// The dots are not allowed in the name of a program.
+pub fn program_name(namespace: &str) -> String {
+  namespace.replace('.', "_")
+}
+
+/// Generates the thrift of a module, the referred namespaces are collected to be included.
+struct Generator<'a> {
+  module: &'a Module,
+  includes: BTreeSet<String>,
+  warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+  fn type_str(&mut self, r#type: &Type, context: &str) -> String {
+    match r#type {
+      Type::Bool => "bool".to_string(),
+      Type::Int8 => "byte".to_string(),
+      Type::Int16 | Type::UInt8 => "i16".to_string(),
+      Type::Int32 | Type::UInt16 => "i32".to_string(),
+      Type::Int64 | Type::UInt32 => "i64".to_string(),
+      Type::UInt64 => {
+        self.warnings.push(format!(
+          "`{}` is u64, thrift has no unsigned integers and it is emitted as i64",
+          context
+        ));
+        "i64".to_string()
+      }
+      Type::Float32 | Type::Float64 => "double".to_string(),
+      Type::String
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => "string".to_string(),
+      Type::Bytes => "binary".to_string(),
+      Type::Any => {
+        self.warnings.push(format!(
+          "`{}` may be any value, thrift has no dynamic type and it is emitted as binary",
+          context
+        ));
+        "binary".to_string()
+      }
+      Type::Void => "void".to_string(),
+      Type::List(item) => format!("list<{}>", self.type_str(item, context)),
+      Type::Set(item) => format!("set<{}>", self.type_str(item, context)),
+      Type::Map(key, value) => format!(
+        "map<{}, {}>",
+        self.type_str(key, context),
+        self.type_str(value, context)
+      ),
+      Type::Ref(r) => {
+        let namespace = r.namespace.join(".");
+        if r.namespace.is_empty() || namespace == self.module.namespace {
+          r.name.clone()
+        } else {
+          let program = program_name(&namespace);
+          self.includes.insert(program.clone());
+          format!("{}.{}", program, r.name)
+        }
+      }
+    }
+  }
+
+  /// The numbered fields, the fields without id take the ids following the largest one.
+  fn gen_fields(&mut self, owner: &str, fields: &[Field], indent: &str, sep: &str) -> Vec<String> {
+    let mut next = fields.iter().filter_map(|f| f.id).max().unwrap_or(0) + 1;
+    fields
+      .iter()
+      .map(|f| {
+        let id = f.id.unwrap_or_else(|| {
+          next += 1;
+          next - 1
+        });
+        let requiredness = if f.required {
+          "required "
+        } else if f.optional {
+          "optional "
+        } else {
+          ""
+        };
+        let context = format!("{}.{}", owner, f.name);
+        format!(
+          "{}{}{}: {}{} {}{}",
+          gen_doc(&f.doc, indent),
+          indent,
+          id,
+          requiredness,
+          self.type_str(&f.r#type, &context),
+          f.name,
+          sep
+        )
+      })
+      .collect()
+  }
+
+  fn gen_struct(
+    &mut self,
+    keyword: &str,
+    name: &str,
+    fields: &[Field],
+    doc: &Option<String>,
+  ) -> String {
+    let fields = self.gen_fields(name, fields, "  ", "\n");
+    format!(
+      "{}{} {} {{\n{}}}\n",
+      gen_doc(doc, ""),
+      keyword,
+      name,
+      fields.concat()
+    )
+  }
+
+  fn gen_record(&mut self, record: &Record) -> String {
+    self.gen_struct("struct", &record.name, &record.fields, &record.doc)
+  }
+
+  /// The variants of a union are named after their types, e.g. `course` of `Course`.
+  fn gen_union(&mut self, union: &Union) -> String {
+    let fields: Vec<Field> = union
+      .variants
+      .iter()
+      .map(|t| {
+        let name = match t {
+          Type::Ref(r) => snake_case(&r.name),
+          t => format!("{}_value", snake_case(&t.to_string())),
+        };
+        Field::new(&name, t.clone(), false)
+      })
+      .collect();
+    self.gen_struct("union", &union.name, &fields, &union.doc)
+  }
+
+  fn gen_alias(&mut self, alias: &Alias) -> String {
+    format!(
+      "{}typedef {} {}\n",
+      gen_doc(&alias.doc, ""),
+      self.type_str(&alias.r#type, &alias.name),
+      alias.name
+    )
+  }
+
+  fn gen_constant(&mut self, constant: &Constant) -> String {
+    format!(
+      "{}const {} {} = {}\n",
+      gen_doc(&constant.doc, ""),
+      self.type_str(&constant.r#type, &constant.name),
+      constant.name,
+      value_str(&constant.value)
+    )
+  }
+
+  fn gen_service(&mut self, service: &Service) -> String {
+    let mut code = gen_doc(&service.doc, "");
+    code.push_str(&format!("service {} {{\n", service.name));
+    for m in &service.methods {
+      let context = format!("{}.{}", service.name, m.name);
+      let params = self.gen_fields(&context, &m.params, "", "");
+      let throws = self.gen_fields(&context, &m.throws, "", "");
+      let throws = if throws.is_empty() {
+        String::new()
+      } else {
+        format!(" throws ({})", throws.join(", "))
+      };
+      code.push_str(&gen_doc(&m.doc, "  "));
+      code.push_str(&format!(
+        "  {} {}({}){}\n",
+        self.type_str(&m.result, &context),
+        m.name,
+        params.join(", "),
+        throws
+      ));
+    }
+    code.push_str("}\n");
+    code
+  }
+}
+
+fn gen_doc(doc: &Option<String>, indent: &str) -> String {
+  match doc.as_deref().map(|d| d.lines().collect::<Vec<&str>>()) {
+    None => String::new(),
+    Some(lines) if lines.len() == 1 => format!("{}/** {} */\n", indent, lines[0]),
+    Some(lines) => {
+      let mut code = format!("{}/**\n", indent);
+      for line in lines {
+        code.push_str(&format!("{} * {}\n", indent, line).replace(" * \n", " *\n"));
+      }
+      code.push_str(&format!("{} */\n", indent));
+      code
+    }
+  }
+}
+
+/// The values of an enum are i32, the string values are numbered by their order.
+fn gen_enum(e: &Enum) -> String {
+  let mut code = gen_doc(&e.doc, "");
+  code.push_str(&format!("enum {} {{\n", e.name));
+  for (i, v) in e.variants.iter().enumerate() {
+    let value = match &v.value {
+      EnumValue::Int(value) => *value,
+      EnumValue::String(_) => i as i64,
+    };
+    code.push_str(&format!("  {} = {},\n", v.name, value));
+  }
+  code.push_str("}\n");
+  code
+}
+
+fn value_str(value: &Value) -> String {
+  match value {
+    Value::Bool(b) => b.to_string(),
+    Value::Int(i) => i.to_string(),
+    Value::Float(f) => format!("{:?}", f),
+    Value::String(s) => format!("{:?}", s),
+    Value::List(items) => {
+      let items: Vec<String> = items.iter().map(value_str).collect();
+      format!("[{}]", items.join(", "))
+    }
+    Value::Map(entries) => {
+      let entries: Vec<String> = entries
+        .iter()
+        .map(|(k, v)| format!("{}: {}", value_str(k), value_str(v)))
+        .collect();
+      format!("{{{}}}", entries.join(", "))
+    }
+    Value::Ref(r) => r.clone(),
+  }
+}
+
+/// Generate the thrift of a module, the namespace is the go namespace read by the thrift front end
+/// and the referred namespaces are included from their own files.
+pub fn codegen(module: &Module, _options: &CodegenOptions, warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    module,
+    includes: BTreeSet::new(),
+    warnings: Vec::new(),
+  };
+  let blocks: Vec<String> = module
+    .definitions
+    .iter()
+    .map(|d| match d {
+      Definition::Record(r) => generator.gen_record(r),
+      Definition::Enum(e) => gen_enum(e),
+      Definition::Union(u) => generator.gen_union(u),
+      Definition::Alias(a) => generator.gen_alias(a),
+      Definition::Constant(c) => generator.gen_constant(c),
+      Definition::Service(s) => generator.gen_service(s),
+    })
+    .collect();
+  warnings.append(&mut generator.warnings);
+
+  let mut code = String::new();
+  for include in &generator.includes {
+    code.push_str(&format!("include \"{}.thrift\"\n", include));
+  }
+  if !generator.includes.is_empty() {
+    code.push('\n');
+  }
+  if !module.namespace.is_empty() {
+    code.push_str(&format!("namespace go {}\n\n", module.namespace));
+  }
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Method, Module, Record, Service,
+    Union, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let field = |id: i32, name: &str, r#type: Type| Field {
+      id: Some(id),
+      ..Field::new(name, r#type, false)
+    };
+    let module = Module {
+      namespace: "foo.bar".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Lesson".to_string(),
+          fields: vec![
+            Field {
+              required: true,
+              ..field(1, "id", Type::Int64)
+            },
+            Field {
+              optional: true,
+              ..field(3, "status", Type::local("Status"))
+            },
+            Field::new("times", Type::list(Type::UInt64), false),
+            field(4, "base", Type::refer("Base", vec!["base".to_string()])),
+            field(5, "scores", Type::map(Type::String, Type::Float32)),
+            field(
+              7,
+              "teacher",
+              Type::refer("Teacher", vec!["common".to_string(), "user".to_string()]),
+            ),
+          ],
+          doc: Some("A lesson\nof a class.".to_string()),
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("NOT_STARTED", 0), ("FINISHED", 2)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          doc: Some("The status.".to_string()),
+        }),
+        Definition::Union(Union {
+          name: "Teacher".to_string(),
+          variants: vec![Type::Int32, Type::local("Lesson")],
+          ..Union::default()
+        }),
+        Definition::Constant(Constant {
+          name: "GRADES".to_string(),
+          r#type: Type::map(Type::Int32, Type::String),
+          value: Value::Map(vec![(Value::Int(1), Value::String("one".to_string()))]),
+          doc: None,
+        }),
+        Definition::Service(Service {
+          name: "LessonService".to_string(),
+          methods: vec![Method {
+            name: "getLesson".to_string(),
+            params: vec![field(1, "id", Type::Int64)],
+            result: Type::local("Lesson"),
+            throws: vec![field(
+              1,
+              "err",
+              Type::refer("Error", vec!["base".to_string()]),
+            )],
+            doc: None,
+            annotations: Default::default(),
+          }],
+          ..Service::default()
+        }),
+      ],
+    };
+    let mut warnings = Vec::new();
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut warnings),
+      r#"include "base.thrift"
+include "common_user.thrift"
+
+namespace go foo.bar
+
+/**
+ * A lesson
+ * of a class.
+ */
+struct Lesson {
+  1: required i64 id
+  3: optional Status status
+  8: list<i64> times
+  4: base.Base base
+  5: map<string, double> scores
+  7: common_user.Teacher teacher
+}
+
+/** The status. */
+enum Status {
+  NOT_STARTED = 0,
+  FINISHED = 2,
+}
+
+union Teacher {
+  1: i32 i32_value
+  2: Lesson lesson
+}
+
+const map<i32, string> GRADES = {1: "one"}
+
+service LessonService {
+  Lesson getLesson(1: i64 id) throws (1: base.Error err)
+}
+"#
+    );
+    assert_eq!(
+      warnings,
+      ["`Lesson.times` is u64, thrift has no unsigned integers and it is emitted as i64"]
+    );
+  }
+}
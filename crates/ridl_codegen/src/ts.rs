@@ -0,0 +1,480 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ridl_ir::schema::{Alias, Constant, Definition, Enum, EnumValue, Module, Record, Union, Value};
+use ridl_ir::types::{Type, TypeRef};
+
+use crate::ts_guard::{gen_guard, IS_OBJECT};
+use crate::{CodegenOptions, Int64Json};
+
+/// The types referred from the files of other namespaces, e.g. `import type { Base } from
+/// "./base"`. A type is imported under an alias prefixed by its namespace, e.g. `base_Base`, when
+/// its name is declared by the module or imported from another namespace as well.
+pub struct TsImports {
+  /// The local names of the imported types, keyed by their namespaces and names.
+  names: BTreeMap<(String, String), String>,
+}
+
+impl TsImports {
+  pub fn of(module: &Module) -> TsImports {
+    let mut refs: BTreeSet<(String, String)> = BTreeSet::new();
+    for d in &module.definitions {
+      let types: Vec<&Type> = match d {
+        Definition::Record(r) => r.fields.iter().map(|f| &f.r#type).collect(),
+        Definition::Union(u) => u.variants.iter().collect(),
+        Definition::Alias(a) => vec![&a.r#type],
+        Definition::Constant(c) => vec![&c.r#type],
+        Definition::Enum(_) | Definition::Service(_) => Vec::new(),
+      };
+      for r in types.iter().flat_map(|t| t.refs()) {
+        if !r.namespace.is_empty() {
+          refs.insert((r.namespace.join("."), r.name.clone()));
+        }
+      }
+    }
+    let names = refs
+      .iter()
+      .map(|(namespace, name)| {
+        let is_declared = module.definitions.iter().any(|d| d.name() == name);
+        let is_shared = refs.iter().filter(|(_, n)| n == name).count() > 1;
+        let local = match is_declared || is_shared {
+          true => format!("{}_{}", namespace.replace('.', "_"), name),
+          false => name.clone(),
+        };
+        ((namespace.clone(), name.clone()), local)
+      })
+      .collect();
+    TsImports { names }
+  }
+
+  /// The name the type is referred by in the module.
+  pub fn local_name(&self, r: &TypeRef) -> String {
+    match self.names.get(&(r.namespace.join("."), r.name.clone())) {
+      Some(local) => local.clone(),
+      None => r.name.clone(),
+    }
+  }
+
+  /// The imports from the files of the namespaces, `suffix` follows the namespace in the path, e.g.
+  /// `.mock`. `exports` are the names imported of a type, e.g. `type Base` and `isBase` of `Base`,
+  /// and the imports are type-only when they all are.
+  pub fn gen(&self, suffix: &str, exports: impl Fn(&str) -> Vec<String>) -> String {
+    let mut namespaces: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for ((namespace, name), local) in &self.names {
+      let names = namespaces.entry(namespace).or_default();
+      for (export, local) in exports(name).into_iter().zip(exports(local)) {
+        names.push(match export == local {
+          true => export,
+          false => format!("{} as {}", export, local.trim_start_matches("type ")),
+        });
+      }
+    }
+    let mut code = String::new();
+    for (namespace, names) in namespaces {
+      let import = match names.iter().all(|n| n.starts_with("type ")) {
+        true => {
+          let names: Vec<&str> = names.iter().map(|n| &n["type ".len()..]).collect();
+          format!("import type {{ {} }}", names.join(", "))
+        }
+        false => format!("import {{ {} }}", names.join(", ")),
+      };
+      code.push_str(&format!("{} from \"./{}{}\";\n", import, namespace, suffix));
+    }
+    code
+  }
+}
+
+/// Present the type by the json mapping, the 64-bit integers are strings unless they are configured
+/// to be numbers, as they may overflow the numbers of js.
+fn type_str(r#type: &Type, options: &CodegenOptions, imports: &TsImports) -> String {
+  match r#type {
+    Type::Int64 | Type::UInt64 if options.int64_json == Int64Json::Number => "number".to_string(),
+    Type::Bool => "boolean".to_string(),
+    Type::Int8
+    | Type::Int16
+    | Type::Int32
+    | Type::UInt8
+    | Type::UInt16
+    | Type::UInt32
+    | Type::Float32
+    | Type::Float64 => "number".to_string(),
+    Type::Int64
+    | Type::UInt64
+    | Type::String
+    | Type::Bytes
+    | Type::Timestamp
+    | Type::Date
+    | Type::Time
+    | Type::Duration
+    | Type::Uuid
+    | Type::Decimal => "string".to_string(),
+    Type::Any => "unknown".to_string(),
+    Type::Void => "null".to_string(),
+    Type::List(item) | Type::Set(item) => format!("{}[]", type_str(item, options, imports)),
+    Type::Map(key, value) => format!(
+      "Record<{}, {}>",
+      type_str(key, options, imports),
+      type_str(value, options, imports)
+    ),
+    Type::Ref(r) => imports.local_name(r),
+  }
+}
+
+/// The value of a constant of the type, the 64-bit integers are quoted when they are presented as
+/// strings.
+fn value_str(value: &Value, r#type: &Type, options: &CodegenOptions) -> String {
+  let (item, key) = match r#type {
+    Type::List(item) | Type::Set(item) => (item.as_ref(), &Type::Any),
+    Type::Map(key, value) => (value.as_ref(), key.as_ref()),
+    t => (t, t),
+  };
+  match value {
+    Value::Bool(b) => b.to_string(),
+    Value::Int(i) => match r#type {
+      Type::Int64 | Type::UInt64 if options.int64_json == Int64Json::String => {
+        format!("\"{}\"", i)
+      }
+      _ => i.to_string(),
+    },
+    Value::Float(f) => f.to_string(),
+    Value::String(s) => format!("{:?}", s),
+    Value::List(items) => {
+      let items: Vec<String> = items.iter().map(|v| value_str(v, item, options)).collect();
+      format!("[{}]", items.join(", "))
+    }
+    Value::Map(entries) => {
+      let entries: Vec<String> = entries
+        .iter()
+        .map(|(k, v)| {
+          format!(
+            "[{}]: {}",
+            value_str(k, key, options),
+            value_str(v, item, options)
+          )
+        })
+        .collect();
+      format!("{{ {} }}", entries.join(", "))
+    }
+    Value::Ref(r) => r.clone(),
+  }
+}
+
+/// Whether the name is an identifier of js, so that it names a property without quotes.
+pub fn is_ident(name: &str) -> bool {
+  name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The key of a property in an interface or an object literal, quoted if the name is not an
+/// identifier, e.g. `"pet-id"`.
+pub fn property_key(name: &str) -> String {
+  match is_ident(name) {
+    true => name.to_string(),
+    false => format!("{:?}", name),
+  }
+}
+
+fn gen_interface(record: &Record, options: &CodegenOptions, imports: &TsImports) -> String {
+  let mut code = format!("export interface {} {{\n", record.name);
+  for f in &record.fields {
+    let optional = if f.optional { "?" } else { "" };
+    code.push_str(&format!(
+      "  {}{}: {};\n",
+      property_key(&f.name),
+      optional,
+      type_str(&f.r#type, options, imports)
+    ));
+  }
+  code.push_str("}\n");
+  code
+}
+
+fn gen_enum(e: &Enum) -> String {
+  let mut code = format!("export enum {} {{\n", e.name);
+  for v in &e.variants {
+    let value = match &v.value {
+      EnumValue::Int(i) => i.to_string(),
+      EnumValue::String(s) => format!("{:?}", s),
+    };
+    code.push_str(&format!("  {} = {},\n", v.name, value));
+  }
+  code.push_str("}\n");
+  code
+}
+
+fn gen_union(union: &Union, options: &CodegenOptions, imports: &TsImports) -> String {
+  let types: Vec<String> = union
+    .variants
+    .iter()
+    .map(|t| type_str(t, options, imports))
+    .collect();
+  let types = if types.is_empty() {
+    "unknown".to_string()
+  } else {
+    types.join(" | ")
+  };
+  format!("export type {} = {};\n", union.name, types)
+}
+
+fn gen_alias(alias: &Alias, options: &CodegenOptions, imports: &TsImports) -> String {
+  format!(
+    "export type {} = {};\n",
+    alias.name,
+    type_str(&alias.r#type, options, imports)
+  )
+}
+
+fn gen_constant(constant: &Constant, options: &CodegenOptions, imports: &TsImports) -> String {
+  format!(
+    "export const {}: {} = {};\n",
+    constant.name,
+    type_str(&constant.r#type, options, imports),
+    value_str(&constant.value, &constant.r#type, options)
+  )
+}
+
+/// The ts code of a definition, the services have no types to present.
+pub fn gen_definition(
+  definition: &Definition,
+  options: &CodegenOptions,
+  imports: &TsImports,
+) -> Option<String> {
+  match definition {
+    Definition::Record(r) => Some(gen_interface(r, options, imports)),
+    Definition::Enum(e) => Some(gen_enum(e)),
+    Definition::Union(u) => Some(gen_union(u, options, imports)),
+    Definition::Alias(a) => Some(gen_alias(a, options, imports)),
+    Definition::Constant(c) => Some(gen_constant(c, options, imports)),
+    Definition::Service(_) => None,
+  }
+}
+
+/// Generate the ts code of a module, the types are emitted in the order they are declared, each
+/// followed by its guard if they are enabled. The types of other namespaces are imported from
+/// their files.
+pub fn codegen(module: &Module, options: &CodegenOptions, _warnings: &mut Vec<String>) -> String {
+  let imports = TsImports::of(module);
+  let mut blocks: Vec<String> = Vec::new();
+  for d in &module.definitions {
+    blocks.extend(gen_definition(d, options, &imports));
+    if options.ts_guards {
+      blocks.extend(gen_guard(d, options, &imports));
+    }
+  }
+  if blocks.iter().any(|b| b.contains("isObject(")) {
+    blocks.push(IS_OBJECT.to_string());
+  }
+  let imports = imports.gen("", |name| match options.ts_guards {
+    true => vec![format!("type {}", name), format!("is{}", name)],
+    false => vec![format!("type {}", name)],
+  });
+  match imports.is_empty() {
+    true => format!("// namespace {}\n\n{}", module.namespace, blocks.join("\n")),
+    false => format!(
+      "// namespace {}\n\n{}\n{}",
+      module.namespace,
+      imports,
+      blocks.join("\n")
+    ),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::{CodegenOptions, Int64Json};
+  use ridl_ir::schema::{
+    Alias, Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "foo".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "LessonPlan".to_string(),
+          fields: vec![
+            Field::new("start_date", Type::Int64, false),
+            Field::new("loop_mode", Type::local("LoopMode"), true),
+            Field::new("weeks", Type::set(Type::Int32), false),
+            Field::new("extra", Type::map(Type::String, Type::Any), false),
+          ],
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "LoopMode".to_string(),
+          variants: [("Null", 0), ("Week", 1)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          ..Enum::default()
+        }),
+        Definition::Alias(Alias {
+          name: "Plans".to_string(),
+          r#type: Type::list(Type::local("LessonPlan")),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "MAX_WEEKS".to_string(),
+          r#type: Type::Int32,
+          value: Value::Int(52),
+          doc: None,
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r#"// namespace foo
+
+export interface LessonPlan {
+  start_date: string;
+  loop_mode?: LoopMode;
+  weeks: number[];
+  extra: Record<string, unknown>;
+}
+
+export enum LoopMode {
+  Null = 0,
+  Week = 1,
+}
+
+export type Plans = LessonPlan[];
+
+export const MAX_WEEKS: number = 52;
+"#
+    );
+  }
+  #[test]
+  fn test_codegen_imports() {
+    let module = Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Course".to_string(),
+          fields: vec![
+            Field::new("base", Type::refer("Base", vec!["base".into()]), true),
+            Field::new(
+              "teacher",
+              Type::refer("User", vec!["ek".into(), "user".into()]),
+              false,
+            ),
+            Field::new(
+              "status",
+              Type::refer("Status", vec!["ek".into(), "user".into()]),
+              false,
+            ),
+            Field::new("lesson", Type::local("Status"), false),
+          ],
+          ..Record::default()
+        }),
+        Definition::Alias(Alias {
+          name: "Status".to_string(),
+          r#type: Type::map(Type::String, Type::refer("Base", vec!["common".into()])),
+          doc: None,
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r#"// namespace ek.course
+
+import type { Base as base_Base } from "./base";
+import type { Base as common_Base } from "./common";
+import type { Status as ek_user_Status, User } from "./ek.user";
+
+export interface Course {
+  base?: base_Base;
+  teacher: User;
+  status: ek_user_Status;
+  lesson: Status;
+}
+
+export type Status = Record<string, common_Base>;
+"#
+    );
+  }
+
+  #[test]
+  fn test_codegen_property_keys() {
+    let module = Module {
+      namespace: "pet".to_string(),
+      definitions: vec![Definition::Record(Record {
+        name: "Pet".to_string(),
+        fields: vec![
+          Field::new("pet-id", Type::String, false),
+          Field::new("@type", Type::String, true),
+          Field::new("$name", Type::String, false),
+        ],
+        ..Record::default()
+      })],
+    };
+    let options = CodegenOptions::default();
+    assert_eq!(
+      codegen(&module, &options, &mut Vec::new()),
+      r#"// namespace pet
+
+export interface Pet {
+  "pet-id": string;
+  "@type"?: string;
+  $name: string;
+}
+"#
+    );
+    // the object literals of the validators and the mocks quote them as well
+    let schema = crate::validator::codegen(&module, &options, &mut Vec::new());
+    assert!(schema.contains("  \"pet-id\": z.string(),\n"));
+    assert!(schema.contains("  $name: z.string(),\n"));
+    let mock = crate::ts_mock::codegen(&module, &options, &mut Vec::new());
+    assert!(mock.contains("    \"pet-id\": String(mock.id()),\n"));
+    assert!(mock.contains("    \"@type\": mock.optional() ? "));
+  }
+
+  #[test]
+  fn test_codegen_int64_constants() {
+    let module = Module {
+      namespace: "limits".to_string(),
+      definitions: vec![
+        Definition::Constant(Constant {
+          name: "BIG".to_string(),
+          r#type: Type::Int64,
+          value: Value::Int(9007199254740993),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "IDS".to_string(),
+          r#type: Type::map(Type::Int64, Type::list(Type::Int64)),
+          value: Value::Map(vec![(Value::Int(1), Value::List(vec![Value::Int(2)]))]),
+          doc: None,
+        }),
+        Definition::Constant(Constant {
+          name: "SMALL".to_string(),
+          r#type: Type::Int32,
+          value: Value::Int(1),
+          doc: None,
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r#"// namespace limits
+
+export const BIG: string = "9007199254740993";
+
+export const IDS: Record<string, string[]> = { ["1"]: ["2"] };
+
+export const SMALL: number = 1;
+"#
+    );
+    let options = CodegenOptions {
+      int64_json: Int64Json::Number,
+      ..CodegenOptions::default()
+    };
+    let code = codegen(&module, &options, &mut Vec::new());
+    assert!(code.contains("export const BIG: number = 9007199254740993;\n"));
+  }
+}
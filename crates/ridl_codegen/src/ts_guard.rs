@@ -0,0 +1,267 @@
+use ridl_ir::schema::{Definition, Record};
+use ridl_ir::types::Type;
+
+use crate::ts::{is_ident, TsImports};
+use crate::{CodegenOptions, Int64Json};
+
+/// The helper narrowing the objects of the guards, it is not exported and is dropped with them when
+/// they are not used.
+pub const IS_OBJECT: &str = r#"function isObject(x: unknown): x is Record<string, unknown> {
+  return typeof x === "object" && x !== null && !Array.isArray(x);
+}
+"#;
+
+/// The property of an object, by the dot if the name is an identifier.
+fn property(object: &str, name: &str) -> String {
+  match is_ident(name) {
+    true => format!("{}.{}", object, name),
+    false => format!("{}[{:?}]", object, name),
+  }
+}
+
+/// The condition that the value `e` is of the type, `None` if every value is. The refs are checked
+/// by their own guards, which are hoisted and so may refer to each other recursively, the guards of
+/// other namespaces are imported along with their types.
+fn check(e: &str, r#type: &Type, options: &CodegenOptions, imports: &TsImports) -> Option<String> {
+  match r#type {
+    Type::Int64 | Type::UInt64 if options.int64_json == Int64Json::String => {
+      Some(format!("typeof {} === \"string\"", e))
+    }
+    Type::Bool => Some(format!("typeof {} === \"boolean\"", e)),
+    Type::Int8
+    | Type::Int16
+    | Type::Int32
+    | Type::Int64
+    | Type::UInt8
+    | Type::UInt16
+    | Type::UInt32
+    | Type::UInt64 => Some(format!("Number.isInteger({})", e)),
+    Type::Float32 | Type::Float64 => Some(format!("typeof {} === \"number\"", e)),
+    Type::String
+    | Type::Bytes
+    | Type::Timestamp
+    | Type::Date
+    | Type::Time
+    | Type::Duration
+    | Type::Uuid
+    | Type::Decimal => Some(format!("typeof {} === \"string\"", e)),
+    Type::Any => None,
+    Type::Void => Some(format!("{} === null", e)),
+    Type::List(item) | Type::Set(item) => Some(match check("e", item, options, imports) {
+      Some(item) => format!("Array.isArray({0}) && {0}.every((e) => {1})", e, item),
+      None => format!("Array.isArray({})", e),
+    }),
+    Type::Map(_, value) => Some(match check("v", value, options, imports) {
+      Some(value) => format!(
+        "isObject({0}) && Object.values({0}).every((v) => {1})",
+        e, value
+      ),
+      None => format!("isObject({})", e),
+    }),
+    Type::Ref(r) => Some(format!("is{}({})", imports.local_name(r), e)),
+  }
+}
+
+/// The conditions joined by the operator, a line each if there are many.
+fn join(conditions: &[String], operator: &str, empty: &str) -> String {
+  match conditions {
+    [] => empty.to_string(),
+    [condition] => condition.clone(),
+    conditions => {
+      let conditions: Vec<String> = conditions
+        .iter()
+        .map(|c| match operator == "||" && c.contains(" && ") {
+          true => format!("({})", c),
+          false => c.clone(),
+        })
+        .collect();
+      format!(
+        "(\n    {}\n  )",
+        conditions.join(&format!(" {}\n    ", operator))
+      )
+    }
+  }
+}
+
+fn gen_function(name: &str, body: &str) -> String {
+  format!(
+    "export function is{0}(x: unknown): x is {0} {{\n  return {1};\n}}\n",
+    name, body
+  )
+}
+
+/// The required fields are checked to be present, the optional ones may be absent.
+fn gen_record_guard(record: &Record, options: &CodegenOptions, imports: &TsImports) -> String {
+  let mut conditions = vec!["isObject(x)".to_string()];
+  for f in &record.fields {
+    let value = property("x", &f.name);
+    match (check(&value, &f.r#type, options, imports), f.optional) {
+      (Some(condition), false) => conditions.push(condition),
+      (Some(condition), true) => {
+        conditions.push(format!("({} === undefined || {})", value, condition))
+      }
+      (None, false) => conditions.push(format!("{} !== undefined", value)),
+      (None, true) => {}
+    }
+  }
+  gen_function(&record.name, &join(&conditions, "&&", "true"))
+}
+
+/// The guard `is<Name>(x: unknown): x is <Name>` of a type, the constants and the services have
+/// none.
+pub fn gen_guard(
+  definition: &Definition,
+  options: &CodegenOptions,
+  imports: &TsImports,
+) -> Option<String> {
+  match definition {
+    Definition::Record(r) => Some(gen_record_guard(r, options, imports)),
+    Definition::Enum(e) => {
+      let values: Vec<String> = e
+        .variants
+        .iter()
+        .map(|v| format!("x === {}.{}", e.name, v.name))
+        .collect();
+      Some(gen_function(&e.name, &join(&values, "||", "false")))
+    }
+    Definition::Union(u) => {
+      let body = match u
+        .variants
+        .iter()
+        .map(|t| check("x", t, options, imports))
+        .collect::<Option<Vec<String>>>()
+      {
+        Some(checks) => join(&checks, "||", "false"),
+        None => "true".to_string(),
+      };
+      Some(gen_function(&u.name, &body))
+    }
+    Definition::Alias(a) => {
+      let body = check("x", &a.r#type, options, imports).unwrap_or("true".to_string());
+      Some(gen_function(&a.name, &body))
+    }
+    Definition::Constant(_) | Definition::Service(_) => None,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::gen_guard;
+  use crate::ts::{codegen, TsImports};
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union};
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_gen_guard() {
+    let options = CodegenOptions::default();
+    let imports = TsImports::of(&Module::default());
+    let record = Definition::Record(Record {
+      name: "Lesson".to_string(),
+      fields: vec![
+        Field::new("id", Type::Int64, false),
+        Field::new("title", Type::String, false),
+        Field::new("status", Type::local("Status"), true),
+        Field::new("weeks", Type::list(Type::Int32), false),
+        Field::new("scores", Type::map(Type::String, Type::Float64), false),
+        Field::new("extra", Type::Any, false),
+        Field::new("next", Type::local("Lesson"), true),
+      ],
+      ..Record::default()
+    });
+    assert_eq!(
+      gen_guard(&record, &options, &imports).unwrap(),
+      r#"export function isLesson(x: unknown): x is Lesson {
+  return (
+    isObject(x) &&
+    typeof x.id === "string" &&
+    typeof x.title === "string" &&
+    (x.status === undefined || isStatus(x.status)) &&
+    Array.isArray(x.weeks) && x.weeks.every((e) => Number.isInteger(e)) &&
+    isObject(x.scores) && Object.values(x.scores).every((v) => typeof v === "number") &&
+    x.extra !== undefined &&
+    (x.next === undefined || isLesson(x.next))
+  );
+}
+"#
+    );
+    let r#enum = Definition::Enum(Enum {
+      name: "Status".to_string(),
+      variants: [("Todo", 0), ("Done", 1)]
+        .map(|(name, value)| EnumVariant {
+          name: name.to_string(),
+          value: EnumValue::Int(value),
+        })
+        .to_vec(),
+      ..Enum::default()
+    });
+    assert_eq!(
+      gen_guard(&r#enum, &options, &imports).unwrap(),
+      r#"export function isStatus(x: unknown): x is Status {
+  return (
+    x === Status.Todo ||
+    x === Status.Done
+  );
+}
+"#
+    );
+    let union = Definition::Union(Union {
+      name: "Id".to_string(),
+      variants: vec![Type::String, Type::list(Type::Int32)],
+      ..Union::default()
+    });
+    assert_eq!(
+      gen_guard(&union, &options, &imports).unwrap(),
+      r#"export function isId(x: unknown): x is Id {
+  return (
+    typeof x === "string" ||
+    (Array.isArray(x) && x.every((e) => Number.isInteger(e)))
+  );
+}
+"#
+    );
+  }
+  #[test]
+  fn test_guard_imports() {
+    let module = Module {
+      namespace: "course".to_string(),
+      definitions: vec![Definition::Record(Record {
+        name: "Course".to_string(),
+        fields: vec![
+          Field::new("base", Type::refer("Base", vec!["base".into()]), false),
+          Field::new("common", Type::refer("Base", vec!["common".into()]), true),
+        ],
+        ..Record::default()
+      })],
+    };
+    let options = CodegenOptions {
+      ts_guards: true,
+      ..CodegenOptions::default()
+    };
+    assert_eq!(
+      codegen(&module, &options, &mut Vec::new()),
+      r#"// namespace course
+
+import { type Base as base_Base, isBase as isbase_Base } from "./base";
+import { type Base as common_Base, isBase as iscommon_Base } from "./common";
+
+export interface Course {
+  base: base_Base;
+  common?: common_Base;
+}
+
+export function isCourse(x: unknown): x is Course {
+  return (
+    isObject(x) &&
+    isbase_Base(x.base) &&
+    (x.common === undefined || iscommon_Base(x.common))
+  );
+}
+
+function isObject(x: unknown): x is Record<string, unknown> {
+  return typeof x === "object" && x !== null && !Array.isArray(x);
+}
+"#
+    );
+  }
+}
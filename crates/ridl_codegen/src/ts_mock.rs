@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ridl_ir::schema::{Definition, Module, Record};
+use ridl_ir::types::Type;
+
+use crate::mock::{is_type, Hint};
+use crate::ts::property_key;
+use crate::{CodegenOptions, Int64Json};
+
+struct Generator<'a> {
+  options: &'a CodegenOptions,
+  /// The factories called from the files of other namespaces.
+  imports: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Generator<'_> {
+  /// The expression mocking a value of the type, as `mock_samples` does, `name` is the name of the
+  /// field or the type it is mocked for.
+  fn expr(&mut self, r#type: &Type, name: &str) -> String {
+    let hint = Hint::of(name);
+    match r#type {
+      Type::Int8
+      | Type::Int16
+      | Type::Int32
+      | Type::Int64
+      | Type::UInt8
+      | Type::UInt16
+      | Type::UInt32
+      | Type::UInt64 => {
+        let expr = match Hint::of_integer(name, r#type) {
+          Hint::Id => "mock.id()",
+          Hint::Time => "mock.time()",
+          Hint::Name | Hint::Plain => "mock.int(0, 100)",
+        };
+        match r#type {
+          Type::Int64 | Type::UInt64 if self.options.int64_json == Int64Json::String => {
+            format!("String({})", expr)
+          }
+          _ => expr.to_string(),
+        }
+      }
+      Type::Float32 | Type::Float64 => "mock.float()".to_string(),
+      Type::Bool => "mock.bool()".to_string(),
+      Type::String => match hint {
+        Hint::Id => "String(mock.id())".to_string(),
+        Hint::Name => "mock.name()".to_string(),
+        Hint::Time => "mock.isoTime()".to_string(),
+        Hint::Plain => format!("mock.string({:?})", name),
+      },
+      Type::Bytes => "\"bW9jaw==\"".to_string(),
+      Type::Timestamp => "mock.isoTime()".to_string(),
+      Type::Date => "mock.isoTime().slice(0, 10)".to_string(),
+      Type::Time => "mock.isoTime().slice(11, 19)".to_string(),
+      Type::Duration => "`${mock.int(1, 3600)}s`".to_string(),
+      Type::Uuid => "mock.uuid()".to_string(),
+      Type::Decimal => "mock.float().toFixed(2)".to_string(),
+      Type::Any | Type::Void => "null".to_string(),
+      Type::List(item) | Type::Set(item) => format!("mock.list(() => {})", self.expr(item, name)),
+      Type::Map(key, value) => format!(
+        "mock.map(() => {}, () => {})",
+        self.expr(key, name),
+        self.expr(value, name)
+      ),
+      Type::Ref(r) => {
+        if !r.namespace.is_empty() {
+          self
+            .imports
+            .entry(r.namespace.join("."))
+            .or_default()
+            .insert(format!("mock{}", r.name));
+        }
+        format!("mock{}(mock)", r.name)
+      }
+    }
+  }
+
+  fn gen_record(&mut self, record: &Record) -> String {
+    let mut fields = String::new();
+    for f in &record.fields {
+      let expr = self.expr(&f.r#type, &f.name);
+      let expr = match f.optional {
+        true => format!("mock.optional() ? {} : undefined", expr),
+        false => expr,
+      };
+      fields.push_str(&format!("    {}: {},\n", property_key(&f.name), expr));
+    }
+    match fields.is_empty() {
+      true => "mock.nested(() => ({}))".to_string(),
+      false => format!("mock.nested(() => ({{\n{}  }}))", fields),
+    }
+  }
+
+  /// The factory `mock<Name>` of a type, the records are mocked one level deeper than the
+  /// factories calling them.
+  fn gen_factory(&mut self, definition: &Definition) -> String {
+    let body = match definition {
+      Definition::Record(r) => self.gen_record(r),
+      Definition::Enum(e) => {
+        let values: Vec<String> = e
+          .variants
+          .iter()
+          .map(|v| format!("{}.{}", e.name, v.name))
+          .collect();
+        format!("mock.pick([{}])", values.join(", "))
+      }
+      Definition::Union(u) if u.variants.is_empty() => "null".to_string(),
+      Definition::Union(u) => {
+        let variants: Vec<String> = u
+          .variants
+          .iter()
+          .map(|t| format!("() => {}", self.expr(t, &u.name)))
+          .collect();
+        format!("mock.oneOf<{}>([{}])", u.name, variants.join(", "))
+      }
+      Definition::Alias(a) => self.expr(&a.r#type, &a.name),
+      Definition::Constant(_) | Definition::Service(_) => unreachable!(),
+    };
+    format!(
+      "export function mock{0}(mock: Mock = new Mock()): {0} {{\n  return {1};\n}}\n",
+      definition.name(),
+      body
+    )
+  }
+}
+
+/// Generate the factories mocking the types of a module, e.g. `mockLesson(new Mock({ seed: 7 }))`.
+/// The types are imported from the file of the ts target, which is expected in the same folder.
+pub fn codegen(module: &Module, options: &CodegenOptions, _warnings: &mut Vec<String>) -> String {
+  let mut generator = Generator {
+    options,
+    imports: BTreeMap::new(),
+  };
+  let types: Vec<&Definition> = module.definitions.iter().filter(|d| is_type(d)).collect();
+  let blocks: Vec<String> = types.iter().map(|d| generator.gen_factory(d)).collect();
+
+  let file = match module.namespace.is_empty() {
+    true => "index",
+    false => &module.namespace,
+  };
+  let (enums, others): (Vec<&Definition>, Vec<&Definition>) =
+    types.iter().partition(|d| matches!(d, Definition::Enum(_)));
+  let mut code = format!(
+    "// namespace {}\n\nimport {{ Mock }} from \"./mock.runtime\";\n",
+    module.namespace
+  );
+  if !others.is_empty() {
+    let names: Vec<&str> = others.iter().map(|d| d.name()).collect();
+    code.push_str(&format!(
+      "import type {{ {} }} from \"./{}\";\n",
+      names.join(", "),
+      file
+    ));
+  }
+  if !enums.is_empty() {
+    let names: Vec<&str> = enums.iter().map(|d| d.name()).collect();
+    code.push_str(&format!(
+      "import {{ {} }} from \"./{}\";\n",
+      names.join(", "),
+      file
+    ));
+  }
+  for (namespace, names) in &generator.imports {
+    let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    code.push_str(&format!(
+      "import {{ {} }} from \"./{}.mock\";\n",
+      names.join(", "),
+      namespace
+    ));
+  }
+  code.push('\n');
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::CodegenOptions;
+  use ridl_ir::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union, Value,
+  };
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_codegen() {
+    let module = Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Lesson".to_string(),
+          fields: vec![
+            Field::new("lesson_id", Type::Int64, false),
+            Field::new("teacher_name", Type::String, false),
+            Field::new("start_time", Type::Timestamp, false),
+            Field::new("status", Type::local("Status"), false),
+            Field::new("scores", Type::map(Type::String, Type::Float64), false),
+            Field::new("title", Type::String, false),
+            Field::new(
+              "teacher",
+              Type::refer("User", vec!["ek".into(), "user".into()]),
+              true,
+            ),
+            Field::new("next", Type::local("Lesson"), true),
+            Field::new("room_id", Type::Int8, false),
+          ],
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("Todo", 0), ("Done", 1)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          ..Enum::default()
+        }),
+        Definition::Union(Union {
+          name: "Score".to_string(),
+          variants: vec![Type::Int32, Type::list(Type::Bool)],
+          ..Union::default()
+        }),
+        Definition::Constant(Constant {
+          name: "MAX_WEEKS".to_string(),
+          r#type: Type::Int32,
+          value: Value::Int(52),
+          doc: None,
+        }),
+      ],
+    };
+    assert_eq!(
+      codegen(&module, &CodegenOptions::default(), &mut Vec::new()),
+      r#"// namespace ek.course
+
+import { Mock } from "./mock.runtime";
+import type { Lesson, Score } from "./ek.course";
+import { Status } from "./ek.course";
+import { mockUser } from "./ek.user.mock";
+
+export function mockLesson(mock: Mock = new Mock()): Lesson {
+  return mock.nested(() => ({
+    lesson_id: String(mock.id()),
+    teacher_name: mock.name(),
+    start_time: mock.isoTime(),
+    status: mockStatus(mock),
+    scores: mock.map(() => mock.string("scores"), () => mock.float()),
+    title: mock.string("title"),
+    teacher: mock.optional() ? mockUser(mock) : undefined,
+    next: mock.optional() ? mockLesson(mock) : undefined,
+    room_id: mock.int(0, 100),
+  }));
+}
+
+export function mockStatus(mock: Mock = new Mock()): Status {
+  return mock.pick([Status.Todo, Status.Done]);
+}
+
+export function mockScore(mock: Mock = new Mock()): Score {
+  return mock.oneOf<Score>([() => mock.int(0, 100), () => mock.list(() => mock.bool())]);
+}
+"#
+    );
+  }
+}
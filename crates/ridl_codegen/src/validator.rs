@@ -0,0 +1,317 @@
+use ridl_ir::schema::{Definition, Enum, Module, Record};
+use ridl_ir::types::Type;
+
+use crate::ts::{gen_definition, property_key, TsImports};
+use crate::validator_io_ts::IoTs;
+use crate::validator_valibot::Valibot;
+use crate::validator_zod::Zod;
+use crate::{CodegenOptions, Int64Json, TsValidator};
+
+/// A field of an object schema, whose schema is in the syntax of the library already.
+pub struct ObjectField {
+  pub name: String,
+  pub schema: String,
+  pub optional: bool,
+}
+
+/// The syntax of a validator library. The traversal of the definitions is shared by the libraries,
+/// which only tell how their schemas are written.
+pub trait ValidatorSyntax {
+  /// The import of the library.
+  fn import(&self) -> &'static str;
+  /// The type annotating the schema of the ts type `name`.
+  fn schema_type(&self, name: &str) -> String;
+  fn boolean(&self) -> &'static str;
+  fn integer(&self) -> &'static str;
+  fn number(&self) -> &'static str;
+  fn string(&self) -> &'static str;
+  fn unknown(&self) -> &'static str;
+  fn null(&self) -> &'static str;
+  fn array(&self, item: &str) -> String;
+  /// The object of string keys, the json keys of maps are strings whatever the idl says.
+  fn record(&self, value: &str) -> String;
+  fn object(&self, fields: &[ObjectField]) -> String;
+  /// The enum `name` of the ts target, whose values are given as `Enum.Variant`.
+  fn enumeration(&self, name: &str, values: &[String]) -> String;
+  /// The union of more than one schema.
+  fn union(&self, variants: &[String]) -> String;
+  /// Refer to a schema, which may be declared later or be the schema referring to it.
+  fn reference(&self, schema: &str) -> String;
+  /// The schema declared for the type `name`.
+  fn definition(&self, _name: &str, schema: &str) -> String {
+    schema.to_string()
+  }
+}
+
+/// The fields of an object literal, a line each.
+pub fn object_literal<'a>(fields: impl Iterator<Item = (&'a str, String)>) -> String {
+  let fields: Vec<String> = fields
+    .map(|(name, schema)| format!("  {}: {},\n", property_key(name), schema))
+    .collect();
+  match fields.is_empty() {
+    true => "{}".to_string(),
+    false => format!("{{\n{}}}", fields.concat()),
+  }
+}
+
+/// The schema of the type `name` is named `<name>Schema`.
+fn schema_name(name: &str) -> String {
+  format!("{}Schema", name)
+}
+
+struct Generator<'a> {
+  syntax: &'a dyn ValidatorSyntax,
+  options: &'a CodegenOptions,
+  imports: &'a TsImports,
+}
+
+impl Generator<'_> {
+  /// The schema of the type by the json mapping of the ts target.
+  fn schema(&mut self, r#type: &Type) -> String {
+    let syntax = self.syntax;
+    match r#type {
+      Type::Int64 | Type::UInt64 if self.options.int64_json == Int64Json::String => {
+        syntax.string().to_string()
+      }
+      Type::Bool => syntax.boolean().to_string(),
+      Type::Int8
+      | Type::Int16
+      | Type::Int32
+      | Type::Int64
+      | Type::UInt8
+      | Type::UInt16
+      | Type::UInt32
+      | Type::UInt64 => syntax.integer().to_string(),
+      Type::Float32 | Type::Float64 => syntax.number().to_string(),
+      Type::String
+      | Type::Bytes
+      | Type::Timestamp
+      | Type::Date
+      | Type::Time
+      | Type::Duration
+      | Type::Uuid
+      | Type::Decimal => syntax.string().to_string(),
+      Type::Any => syntax.unknown().to_string(),
+      Type::Void => syntax.null().to_string(),
+      Type::List(item) | Type::Set(item) => {
+        let item = self.schema(item);
+        syntax.array(&item)
+      }
+      Type::Map(_, value) => {
+        let value = self.schema(value);
+        syntax.record(&value)
+      }
+      Type::Ref(r) => syntax.reference(&schema_name(&self.imports.local_name(r))),
+    }
+  }
+
+  fn gen_object(&mut self, record: &Record) -> String {
+    let fields: Vec<ObjectField> = record
+      .fields
+      .iter()
+      .map(|f| ObjectField {
+        name: f.name.clone(),
+        schema: self.schema(&f.r#type),
+        optional: f.optional,
+      })
+      .collect();
+    self.syntax.object(&fields)
+  }
+
+  fn gen_enum(&self, e: &Enum) -> String {
+    let values: Vec<String> = e
+      .variants
+      .iter()
+      .map(|v| format!("{}.{}", e.name, v.name))
+      .collect();
+    self.syntax.enumeration(&e.name, &values)
+  }
+
+  /// The schema declared along with the ts type of a definition, the constants and the services
+  /// have none.
+  fn gen_schema(&mut self, definition: &Definition) -> Option<String> {
+    let (name, schema) = match definition {
+      Definition::Record(r) => (&r.name, self.gen_object(r)),
+      Definition::Enum(e) => (&e.name, self.gen_enum(e)),
+      Definition::Union(u) => {
+        let mut variants: Vec<String> = u.variants.iter().map(|t| self.schema(t)).collect();
+        let schema = match variants.len() {
+          0 => self.syntax.unknown().to_string(),
+          1 => variants.remove(0),
+          _ => self.syntax.union(&variants),
+        };
+        (&u.name, schema)
+      }
+      Definition::Alias(a) => (&a.name, self.schema(&a.r#type)),
+      Definition::Constant(_) | Definition::Service(_) => return None,
+    };
+    Some(format!(
+      "export const {}: {} = {};\n",
+      schema_name(name),
+      self.syntax.schema_type(name),
+      self.syntax.definition(name, &schema)
+    ))
+  }
+}
+
+/// Generate the ts types of a module along with their schemas of the validator library, e.g.
+/// `LessonSchema` of `Lesson`. The types and schemas of other namespaces are imported from their
+/// files.
+pub fn codegen(module: &Module, options: &CodegenOptions, _warnings: &mut Vec<String>) -> String {
+  let syntax: &dyn ValidatorSyntax = match options.validator {
+    TsValidator::Zod => &Zod,
+    TsValidator::IoTs => &IoTs,
+    TsValidator::Valibot => &Valibot,
+  };
+  let imports = TsImports::of(module);
+  let mut generator = Generator {
+    syntax,
+    options,
+    imports: &imports,
+  };
+  let mut blocks: Vec<String> = Vec::new();
+  for d in &module.definitions {
+    blocks.extend(gen_definition(d, options, &imports));
+    blocks.extend(generator.gen_schema(d));
+  }
+
+  let mut code = format!("// namespace {}\n\n{}\n", module.namespace, syntax.import());
+  code.push_str(&imports.gen("", |name| vec![format!("type {}", name), schema_name(name)]));
+  code.push('\n');
+  code.push_str(&blocks.join("\n"));
+  code
+}
+
+#[cfg(test)]
+mod test {
+  use super::codegen;
+  use crate::{CodegenOptions, TsValidator};
+  use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union};
+  use ridl_ir::types::Type;
+
+  fn module() -> Module {
+    Module {
+      namespace: "ek.course".to_string(),
+      definitions: vec![
+        Definition::Record(Record {
+          name: "Lesson".to_string(),
+          fields: vec![
+            Field::new("id", Type::Int64, false),
+            Field::new("status", Type::local("Status"), false),
+            Field::new("weeks", Type::list(Type::Int32), false),
+            Field::new(
+              "teacher",
+              Type::refer("User", vec!["ek".into(), "user".into()]),
+              true,
+            ),
+            Field::new("next", Type::local("Lesson"), true),
+          ],
+          ..Record::default()
+        }),
+        Definition::Enum(Enum {
+          name: "Status".to_string(),
+          variants: [("Todo", 0), ("Done", 1)]
+            .map(|(name, value)| EnumVariant {
+              name: name.to_string(),
+              value: EnumValue::Int(value),
+            })
+            .to_vec(),
+          ..Enum::default()
+        }),
+        Definition::Union(Union {
+          name: "Score".to_string(),
+          variants: vec![Type::Float64, Type::map(Type::String, Type::Bool)],
+          ..Union::default()
+        }),
+      ],
+    }
+  }
+
+  fn gen(validator: TsValidator) -> String {
+    let options = CodegenOptions {
+      validator,
+      ..CodegenOptions::default()
+    };
+    codegen(&module(), &options, &mut Vec::new())
+  }
+
+  #[test]
+  fn test_codegen() {
+    assert_eq!(
+      gen(TsValidator::Zod),
+      r#"// namespace ek.course
+
+import { z } from "zod";
+import { type User, UserSchema } from "./ek.user";
+
+export interface Lesson {
+  id: string;
+  status: Status;
+  weeks: number[];
+  teacher?: User;
+  next?: Lesson;
+}
+
+export const LessonSchema: z.ZodType<Lesson> = z.object({
+  id: z.string(),
+  status: z.lazy(() => StatusSchema),
+  weeks: z.array(z.number().int()),
+  teacher: z.lazy(() => UserSchema).optional(),
+  next: z.lazy(() => LessonSchema).optional(),
+});
+
+export enum Status {
+  Todo = 0,
+  Done = 1,
+}
+
+export const StatusSchema: z.ZodType<Status> = z.nativeEnum(Status);
+
+export type Score = number | Record<string, boolean>;
+
+export const ScoreSchema: z.ZodType<Score> = z.union([z.number(), z.record(z.string(), z.boolean())]);
+"#
+    );
+  }
+
+  #[test]
+  fn test_codegen_io_ts() {
+    let code = gen(TsValidator::IoTs);
+    assert!(code.contains("import * as t from \"io-ts\";\n"));
+    assert!(code.contains(
+      r#"export const LessonSchema: t.Type<Lesson> = t.recursion<Lesson>("Lesson", () => t.intersection([
+  t.type({
+    id: t.string,
+    status: StatusSchema,
+    weeks: t.array(t.number),
+  }),
+  t.partial({
+    teacher: UserSchema,
+    next: LessonSchema,
+  }),
+]));
+"#
+    ));
+    assert!(code.contains(
+      r#"export const StatusSchema: t.Type<Status> = t.recursion<Status>("Status", () => t.union([t.literal(Status.Todo), t.literal(Status.Done)]));
+"#
+    ));
+  }
+
+  #[test]
+  fn test_codegen_valibot() {
+    let code = gen(TsValidator::Valibot);
+    assert!(code.contains("import * as v from \"valibot\";\n"));
+    assert!(code.contains(
+      r#"export const LessonSchema: v.GenericSchema<Lesson> = v.object({
+  id: v.string(),
+  status: v.lazy(() => StatusSchema),
+  weeks: v.array(v.pipe(v.number(), v.integer())),
+  teacher: v.optional(v.lazy(() => UserSchema)),
+  next: v.optional(v.lazy(() => LessonSchema)),
+});
+"#
+    ));
+    assert!(code.contains("export const StatusSchema: v.GenericSchema<Status> = v.enum(Status);\n"));
+  }
+}
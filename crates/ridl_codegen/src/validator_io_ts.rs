@@ -0,0 +1,87 @@
+use crate::validator::{object_literal, ObjectField, ValidatorSyntax};
+
+/// The codecs of io-ts, e.g. `t.type({ id: t.string })`. The optional fields are a `t.partial`
+/// intersected with the required ones, and the integers are plain numbers as `t.Int` is branded.
+pub struct IoTs;
+
+impl ValidatorSyntax for IoTs {
+  fn import(&self) -> &'static str {
+    "import * as t from \"io-ts\";"
+  }
+
+  fn schema_type(&self, name: &str) -> String {
+    format!("t.Type<{}>", name)
+  }
+
+  fn boolean(&self) -> &'static str {
+    "t.boolean"
+  }
+
+  fn integer(&self) -> &'static str {
+    "t.number"
+  }
+
+  fn number(&self) -> &'static str {
+    "t.number"
+  }
+
+  fn string(&self) -> &'static str {
+    "t.string"
+  }
+
+  fn unknown(&self) -> &'static str {
+    "t.unknown"
+  }
+
+  fn null(&self) -> &'static str {
+    "t.null"
+  }
+
+  fn array(&self, item: &str) -> String {
+    format!("t.array({})", item)
+  }
+
+  fn record(&self, value: &str) -> String {
+    format!("t.record(t.string, {})", value)
+  }
+
+  fn object(&self, fields: &[ObjectField]) -> String {
+    let (optional, required): (Vec<&ObjectField>, Vec<&ObjectField>) =
+      fields.iter().partition(|f| f.optional);
+    let literal = |fields: Vec<&ObjectField>| {
+      object_literal(fields.iter().map(|f| (f.name.as_str(), f.schema.clone())))
+    };
+    match (required.is_empty(), optional.is_empty()) {
+      (_, true) => format!("t.type({})", literal(required)),
+      (true, false) => format!("t.partial({})", literal(optional)),
+      (false, false) => format!(
+        "t.intersection([\n  t.type({}),\n  t.partial({}),\n])",
+        literal(required).replace('\n', "\n  "),
+        literal(optional).replace('\n', "\n  ")
+      ),
+    }
+  }
+
+  fn enumeration(&self, _name: &str, values: &[String]) -> String {
+    let literals: Vec<String> = values.iter().map(|v| format!("t.literal({})", v)).collect();
+    match literals.len() {
+      0 => "t.never".to_string(),
+      1 => literals[0].clone(),
+      _ => self.union(&literals),
+    }
+  }
+
+  fn union(&self, variants: &[String]) -> String {
+    format!("t.union([{}])", variants.join(", "))
+  }
+
+  /// The codecs are declared recursive, so they may be referred to directly before they are
+  /// initialized.
+  fn reference(&self, schema: &str) -> String {
+    schema.to_string()
+  }
+
+  fn definition(&self, name: &str, schema: &str) -> String {
+    format!("t.recursion<{0}>({0:?}, () => {1})", name, schema)
+  }
+}
@@ -0,0 +1,66 @@
+use crate::validator::{object_literal, ObjectField, ValidatorSyntax};
+
+/// The schemas of valibot, e.g. `v.object({ id: v.string() })`.
+pub struct Valibot;
+
+impl ValidatorSyntax for Valibot {
+  fn import(&self) -> &'static str {
+    "import * as v from \"valibot\";"
+  }
+
+  fn schema_type(&self, name: &str) -> String {
+    format!("v.GenericSchema<{}>", name)
+  }
+
+  fn boolean(&self) -> &'static str {
+    "v.boolean()"
+  }
+
+  fn integer(&self) -> &'static str {
+    "v.pipe(v.number(), v.integer())"
+  }
+
+  fn number(&self) -> &'static str {
+    "v.number()"
+  }
+
+  fn string(&self) -> &'static str {
+    "v.string()"
+  }
+
+  fn unknown(&self) -> &'static str {
+    "v.unknown()"
+  }
+
+  fn null(&self) -> &'static str {
+    "v.null()"
+  }
+
+  fn array(&self, item: &str) -> String {
+    format!("v.array({})", item)
+  }
+
+  fn record(&self, value: &str) -> String {
+    format!("v.record(v.string(), {})", value)
+  }
+
+  fn object(&self, fields: &[ObjectField]) -> String {
+    let fields = fields.iter().map(|f| match f.optional {
+      true => (f.name.as_str(), format!("v.optional({})", f.schema)),
+      false => (f.name.as_str(), f.schema.clone()),
+    });
+    format!("v.object({})", object_literal(fields))
+  }
+
+  fn enumeration(&self, name: &str, _values: &[String]) -> String {
+    format!("v.enum({})", name)
+  }
+
+  fn union(&self, variants: &[String]) -> String {
+    format!("v.union([{}])", variants.join(", "))
+  }
+
+  fn reference(&self, schema: &str) -> String {
+    format!("v.lazy(() => {})", schema)
+  }
+}
@@ -0,0 +1,66 @@
+use crate::validator::{object_literal, ObjectField, ValidatorSyntax};
+
+/// The schemas of zod, e.g. `z.object({ id: z.string() })`.
+pub struct Zod;
+
+impl ValidatorSyntax for Zod {
+  fn import(&self) -> &'static str {
+    "import { z } from \"zod\";"
+  }
+
+  fn schema_type(&self, name: &str) -> String {
+    format!("z.ZodType<{}>", name)
+  }
+
+  fn boolean(&self) -> &'static str {
+    "z.boolean()"
+  }
+
+  fn integer(&self) -> &'static str {
+    "z.number().int()"
+  }
+
+  fn number(&self) -> &'static str {
+    "z.number()"
+  }
+
+  fn string(&self) -> &'static str {
+    "z.string()"
+  }
+
+  fn unknown(&self) -> &'static str {
+    "z.unknown()"
+  }
+
+  fn null(&self) -> &'static str {
+    "z.null()"
+  }
+
+  fn array(&self, item: &str) -> String {
+    format!("z.array({})", item)
+  }
+
+  fn record(&self, value: &str) -> String {
+    format!("z.record(z.string(), {})", value)
+  }
+
+  fn object(&self, fields: &[ObjectField]) -> String {
+    let fields = fields.iter().map(|f| match f.optional {
+      true => (f.name.as_str(), format!("{}.optional()", f.schema)),
+      false => (f.name.as_str(), f.schema.clone()),
+    });
+    format!("z.object({})", object_literal(fields))
+  }
+
+  fn enumeration(&self, name: &str, _values: &[String]) -> String {
+    format!("z.nativeEnum({})", name)
+  }
+
+  fn union(&self, variants: &[String]) -> String {
+    format!("z.union([{}])", variants.join(", "))
+  }
+
+  fn reference(&self, schema: &str) -> String {
+    format!("z.lazy(() => {})", schema)
+  }
+}
@@ -0,0 +1,255 @@
+use std::{error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::Schema;
+
+/// The version of the dump format, it is bumped whenever the format changes incompatibly.
+pub const DUMP_VERSION: u32 = 1;
+
+/// The json schema describing the dump format.
+pub const DUMP_JSON_SCHEMA: &str = include_str!("../dump.schema.json");
+
+/// The json document of a dump, e.g. `{"version": 1, "modules": {...}}`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Dump {
+  version: u32,
+  #[serde(flatten)]
+  schema: Schema,
+}
+
+/// Which part of the schema to dump, everything is kept by default.
+#[derive(Debug, Clone, Default)]
+pub struct DumpFilter {
+  pub namespace: Option<String>,
+  /// The name of a definition.
+  pub r#type: Option<String>,
+}
+
+impl DumpFilter {
+  /// Keep the matched definitions, the modules left without definitions are dropped when filtered
+  /// by type.
+  fn apply(&self, schema: &Schema) -> Schema {
+    let mut res = Schema::default();
+    for (namespace, module) in &schema.modules {
+      if self.namespace.as_ref().is_some_and(|n| n != namespace) {
+        continue;
+      }
+      let mut module = module.clone();
+      if let Some(name) = &self.r#type {
+        module.definitions.retain(|d| d.name() == name);
+        if module.definitions.is_empty() {
+          continue;
+        }
+      }
+      res.modules.insert(namespace.clone(), module);
+    }
+    res
+  }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+  Json(serde_json::Error),
+  UnsupportedVersion(u32),
+}
+
+impl fmt::Display for LoadError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      LoadError::Json(err) => write!(f, "invalid dump: {}", err),
+      LoadError::UnsupportedVersion(version) => write!(
+        f,
+        "unsupported dump version {}, expected {}",
+        version, DUMP_VERSION
+      ),
+    }
+  }
+}
+
+impl Error for LoadError {}
+
+/// Serialize the filtered schema into the pretty printed json of the dump format.
+pub fn dump(schema: &Schema, filter: &DumpFilter) -> String {
+  let dump = Dump {
+    version: DUMP_VERSION,
+    schema: filter.apply(schema),
+  };
+  serde_json::to_string_pretty(&dump).unwrap()
+}
+
+/// Deserialize a dump, the dumps of other versions are rejected.
+pub fn load(json: &str) -> Result<Schema, LoadError> {
+  let dump: Dump = serde_json::from_str(json).map_err(LoadError::Json)?;
+  if dump.version != DUMP_VERSION {
+    return Err(LoadError::UnsupportedVersion(dump.version));
+  }
+  Ok(dump.schema)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{dump, load, DumpFilter, LoadError, DUMP_JSON_SCHEMA, DUMP_VERSION};
+  use crate::schema::{
+    Constant, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Schema, Union, Value,
+  };
+  use crate::types::Type;
+
+  fn schema() -> Schema {
+    let mut schema = Schema::default();
+    let mut id = Field::new("id", Type::Int64, false);
+    id.annotations
+      .insert("go.tag".to_string(), "json:\"id\"".to_string());
+    schema.modules.insert(
+      "course".to_string(),
+      Module {
+        namespace: "course".to_string(),
+        definitions: vec![
+          Definition::Record(Record {
+            name: "Course".to_string(),
+            fields: vec![
+              id,
+              Field::new("tags", Type::list(Type::String), true),
+              Field::new("base", Type::refer("Base", vec!["base".to_string()]), false),
+            ],
+            doc: Some("A course.".to_string()),
+            ..Record::default()
+          }),
+          Definition::Enum(Enum {
+            name: "Status".to_string(),
+            variants: vec![
+              EnumVariant {
+                name: "OPEN".to_string(),
+                value: EnumValue::Int(1),
+              },
+              EnumVariant {
+                name: "CLOSED".to_string(),
+                value: EnumValue::String("closed".to_string()),
+              },
+            ],
+            ..Enum::default()
+          }),
+          Definition::Union(Union {
+            name: "Item".to_string(),
+            variants: vec![Type::local("Course"), Type::String],
+            ..Union::default()
+          }),
+          Definition::Constant(Constant {
+            name: "LIMITS".to_string(),
+            r#type: Type::map(Type::String, Type::Float64),
+            value: Value::Map(vec![(Value::String("max".to_string()), Value::Float(1.5))]),
+            doc: None,
+          }),
+        ],
+      },
+    );
+    schema.modules.insert(
+      "base".to_string(),
+      Module {
+        namespace: "base".to_string(),
+        definitions: vec![Definition::Record(Record {
+          name: "Base".to_string(),
+          ..Record::default()
+        })],
+      },
+    );
+    schema
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let schema = schema();
+    let json = dump(&schema, &DumpFilter::default());
+    assert!(json.starts_with("{\n  \"version\": 1,\n  \"modules\": {\n    \"base\": {"));
+    assert_eq!(load(&json).unwrap(), schema);
+  }
+
+  #[test]
+  fn test_format() {
+    let filter = DumpFilter {
+      namespace: Some("course".to_string()),
+      r#type: Some("Course".to_string()),
+    };
+    let json: serde_json::Value = serde_json::from_str(&dump(&schema(), &filter)).unwrap();
+    assert_eq!(
+      json,
+      serde_json::json!({
+        "version": 1,
+        "modules": {
+          "course": {
+            "namespace": "course",
+            "definitions": [{
+              "kind": "record",
+              "name": "Course",
+              "fields": [
+                {
+                  "name": "id",
+                  "type": {"kind": "i64"},
+                  "optional": false,
+                  "annotations": {"go.tag": "json:\"id\""}
+                },
+                {
+                  "name": "tags",
+                  "type": {"kind": "list", "item": {"kind": "string"}},
+                  "optional": true
+                },
+                {
+                  "name": "base",
+                  "type": {"kind": "ref", "name": "Base", "namespace": ["base"]},
+                  "optional": false
+                }
+              ],
+              "doc": "A course."
+            }]
+          }
+        }
+      })
+    );
+
+    let filter = DumpFilter {
+      r#type: Some("Base".to_string()),
+      ..DumpFilter::default()
+    };
+    let filtered = load(&dump(&schema(), &filter)).unwrap();
+    assert_eq!(filtered.modules.keys().collect::<Vec<_>>(), ["base"]);
+  }
+
+  #[test]
+  fn test_load_other_version() {
+    let json = r#"{"version": 2, "modules": {}}"#;
+    assert!(matches!(load(json), Err(LoadError::UnsupportedVersion(2))));
+    assert!(matches!(load("{}"), Err(LoadError::Json(_))));
+  }
+
+  #[test]
+  fn test_json_schema() {
+    let json_schema: serde_json::Value = serde_json::from_str(DUMP_JSON_SCHEMA).unwrap();
+    assert_eq!(
+      json_schema["properties"]["version"]["const"],
+      serde_json::json!(DUMP_VERSION)
+    );
+    // every kind of the definitions and types is described
+    let kinds = |def: &str| -> Vec<String> {
+      json_schema["$defs"][def]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|s| {
+          let kind = &s["properties"]["kind"];
+          match kind["enum"].as_array() {
+            Some(kinds) => kinds
+              .iter()
+              .map(|k| k.as_str().unwrap().to_string())
+              .collect(),
+            None => vec![kind["const"].as_str().unwrap().to_string()],
+          }
+        })
+        .collect()
+    };
+    assert_eq!(
+      kinds("definition"),
+      ["record", "enum", "union", "alias", "service", "constant"]
+    );
+    assert_eq!(kinds("type").len(), 25);
+  }
+}
@@ -0,0 +1,3 @@
+pub mod dump;
+pub mod schema;
+pub mod types;
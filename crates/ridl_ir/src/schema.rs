@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Type;
+
+/// The annotations kept from the idl, e.g. `go.tag` of thrift or `json_name` of protobuf.
+pub type Annotations = BTreeMap<String, String>;
+
+/// The protocol-agnostic representation of the idl, every protocol is lowered into it and every
+/// target is generated from it. The modules are keyed and sorted by the namespace.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Schema {
+  pub modules: BTreeMap<String, Module>,
+}
+
+/// The definitions of a namespace, in the order they are declared, the nested ones follow their
+/// parents.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Module {
+  pub namespace: String,
+  pub definitions: Vec<Definition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Definition {
+  Record(Record),
+  Enum(Enum),
+  Union(Union),
+  Alias(Alias),
+  Service(Service),
+  Constant(Constant),
+}
+
+impl Definition {
+  pub fn name(&self) -> &str {
+    match self {
+      Definition::Record(d) => &d.name,
+      Definition::Enum(d) => &d.name,
+      Definition::Union(d) => &d.name,
+      Definition::Alias(d) => &d.name,
+      Definition::Service(d) => &d.name,
+      Definition::Constant(d) => &d.name,
+    }
+  }
+}
+
+/// A struct, message, table or object type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+  pub name: String,
+  pub fields: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+  #[serde(default, skip_serializing_if = "Annotations::is_empty")]
+  pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+  /// The field id or number of the idl, e.g. `1` of `1: i64 id`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<i32>,
+  pub name: String,
+  pub r#type: Type,
+  /// The field may be absent or null.
+  pub optional: bool,
+  /// The field must be set, e.g. `required` of thrift or proto2. The fields neither optional nor
+  /// required have the default requiredness of the idl.
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub required: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+  #[serde(default, skip_serializing_if = "Annotations::is_empty")]
+  pub annotations: Annotations,
+}
+
+impl Field {
+  pub fn new(name: &str, r#type: Type, optional: bool) -> Field {
+    Field {
+      id: None,
+      name: name.to_string(),
+      r#type,
+      optional,
+      required: false,
+      doc: None,
+      annotations: Annotations::new(),
+    }
+  }
+}
+
+fn is_false(b: &bool) -> bool {
+  !b
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Enum {
+  pub name: String,
+  pub variants: Vec<EnumVariant>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumVariant {
+  pub name: String,
+  pub value: EnumValue,
+}
+
+/// The values of enums are integers in most idl, and strings in the ones serializing enums by
+/// names, e.g. avro and graphql.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnumValue {
+  Int(i64),
+  String(String),
+}
+
+/// A type that is one of the variants, e.g. an union of graphql or `oneOf` of openapi.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Union {
+  pub name: String,
+  pub variants: Vec<Type>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+}
+
+/// Another name of a type, e.g. a typedef of thrift or a custom scalar of graphql.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alias {
+  pub name: String,
+  pub r#type: Type,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Service {
+  pub name: String,
+  pub methods: Vec<Method>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+  #[serde(default, skip_serializing_if = "Annotations::is_empty")]
+  pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Method {
+  pub name: String,
+  pub params: Vec<Field>,
+  /// `Void` if the method returns nothing.
+  pub result: Type,
+  /// The exceptions or errors the method may throw.
+  pub throws: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+  #[serde(default, skip_serializing_if = "Annotations::is_empty")]
+  pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Constant {
+  pub name: String,
+  pub r#type: Type,
+  pub value: Value,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub doc: Option<String>,
+}
+
+/// The literal values of constants and defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum Value {
+  Bool(bool),
+  Int(i64),
+  Float(f64),
+  String(String),
+  List(Vec<Value>),
+  Map(Vec<(Value, Value)>),
+  /// A reference to another constant or an enum variant, e.g. `Color.Red`.
+  Ref(String),
+}
@@ -0,0 +1,246 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A type referred by its name, the namespace is empty for the types of the same module.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TypeRef {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub namespace: Vec<String>,
+}
+
+/// The types of fields, aliases and constants. The types keep what the idl means rather than how
+/// a target presents it, e.g. 64-bit integers are `Int64` and it is up to the generators to present
+/// them as numbers or strings.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "TypeRepr", from = "TypeRepr")]
+pub enum Type {
+  Bool,
+  Int8,
+  Int16,
+  Int32,
+  Int64,
+  UInt8,
+  UInt16,
+  UInt32,
+  UInt64,
+  Float32,
+  Float64,
+  String,
+  Bytes,
+  /// A point in time, e.g. `google.protobuf.Timestamp` or `timestamp-millis` of avro.
+  Timestamp,
+  Date,
+  /// A time of day.
+  Time,
+  Duration,
+  Uuid,
+  Decimal,
+  /// Any value, e.g. `google.protobuf.Any` or a custom scalar of graphql.
+  Any,
+  /// No value, e.g. the result of a method returning nothing or `Void` of Cap'n Proto.
+  Void,
+  List(Box<Type>),
+  Set(Box<Type>),
+  Map(Box<Type>, Box<Type>),
+  Ref(TypeRef),
+}
+
+impl Type {
+  pub fn list(item: Type) -> Type {
+    Type::List(Box::new(item))
+  }
+
+  pub fn set(item: Type) -> Type {
+    Type::Set(Box::new(item))
+  }
+
+  pub fn map(key: Type, value: Type) -> Type {
+    Type::Map(Box::new(key), Box::new(value))
+  }
+
+  /// Refer to the type `name` of the module `namespace`.
+  pub fn refer(name: &str, namespace: Vec<String>) -> Type {
+    Type::Ref(TypeRef {
+      name: name.to_string(),
+      namespace,
+    })
+  }
+
+  /// Refer to the type `name` of the same module.
+  pub fn local(name: &str) -> Type {
+    Type::refer(name, Vec::new())
+  }
+
+  /// The refs in the type, e.g. the ones of the keys and values of a map.
+  pub fn refs(&self) -> Vec<&TypeRef> {
+    match self {
+      Type::List(item) | Type::Set(item) => item.refs(),
+      Type::Map(key, value) => [key.refs(), value.refs()].concat(),
+      Type::Ref(r) => vec![r],
+      _ => Vec::new(),
+    }
+  }
+}
+
+/// The json form of the types, which is an object tagged by `kind` named after the notation of
+/// `Display`, e.g. `{"kind": "list", "item": {"kind": "i64"}}`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum TypeRepr {
+  Bool,
+  I8,
+  I16,
+  I32,
+  I64,
+  U8,
+  U16,
+  U32,
+  U64,
+  F32,
+  F64,
+  String,
+  Bytes,
+  Timestamp,
+  Date,
+  Time,
+  Duration,
+  Uuid,
+  Decimal,
+  Any,
+  Void,
+  List { item: Box<Type> },
+  Set { item: Box<Type> },
+  Map { key: Box<Type>, value: Box<Type> },
+  Ref(TypeRef),
+}
+
+impl From<Type> for TypeRepr {
+  fn from(r#type: Type) -> Self {
+    match r#type {
+      Type::Bool => TypeRepr::Bool,
+      Type::Int8 => TypeRepr::I8,
+      Type::Int16 => TypeRepr::I16,
+      Type::Int32 => TypeRepr::I32,
+      Type::Int64 => TypeRepr::I64,
+      Type::UInt8 => TypeRepr::U8,
+      Type::UInt16 => TypeRepr::U16,
+      Type::UInt32 => TypeRepr::U32,
+      Type::UInt64 => TypeRepr::U64,
+      Type::Float32 => TypeRepr::F32,
+      Type::Float64 => TypeRepr::F64,
+      Type::String => TypeRepr::String,
+      Type::Bytes => TypeRepr::Bytes,
+      Type::Timestamp => TypeRepr::Timestamp,
+      Type::Date => TypeRepr::Date,
+      Type::Time => TypeRepr::Time,
+      Type::Duration => TypeRepr::Duration,
+      Type::Uuid => TypeRepr::Uuid,
+      Type::Decimal => TypeRepr::Decimal,
+      Type::Any => TypeRepr::Any,
+      Type::Void => TypeRepr::Void,
+      Type::List(item) => TypeRepr::List { item },
+      Type::Set(item) => TypeRepr::Set { item },
+      Type::Map(key, value) => TypeRepr::Map { key, value },
+      Type::Ref(r) => TypeRepr::Ref(r),
+    }
+  }
+}
+
+impl From<TypeRepr> for Type {
+  fn from(repr: TypeRepr) -> Self {
+    match repr {
+      TypeRepr::Bool => Type::Bool,
+      TypeRepr::I8 => Type::Int8,
+      TypeRepr::I16 => Type::Int16,
+      TypeRepr::I32 => Type::Int32,
+      TypeRepr::I64 => Type::Int64,
+      TypeRepr::U8 => Type::UInt8,
+      TypeRepr::U16 => Type::UInt16,
+      TypeRepr::U32 => Type::UInt32,
+      TypeRepr::U64 => Type::UInt64,
+      TypeRepr::F32 => Type::Float32,
+      TypeRepr::F64 => Type::Float64,
+      TypeRepr::String => Type::String,
+      TypeRepr::Bytes => Type::Bytes,
+      TypeRepr::Timestamp => Type::Timestamp,
+      TypeRepr::Date => Type::Date,
+      TypeRepr::Time => Type::Time,
+      TypeRepr::Duration => Type::Duration,
+      TypeRepr::Uuid => Type::Uuid,
+      TypeRepr::Decimal => Type::Decimal,
+      TypeRepr::Any => Type::Any,
+      TypeRepr::Void => Type::Void,
+      TypeRepr::List { item } => Type::List(item),
+      TypeRepr::Set { item } => Type::Set(item),
+      TypeRepr::Map { key, value } => Type::Map(key, value),
+      TypeRepr::Ref(r) => Type::Ref(r),
+    }
+  }
+}
+
+/// The types are displayed in a protocol neutral notation, e.g. `list<base.Base>` or
+/// `map<string, i64>`, which is handy for tests and logs.
+impl fmt::Display for Type {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      Type::Bool => "bool",
+      Type::Int8 => "i8",
+      Type::Int16 => "i16",
+      Type::Int32 => "i32",
+      Type::Int64 => "i64",
+      Type::UInt8 => "u8",
+      Type::UInt16 => "u16",
+      Type::UInt32 => "u32",
+      Type::UInt64 => "u64",
+      Type::Float32 => "f32",
+      Type::Float64 => "f64",
+      Type::String => "string",
+      Type::Bytes => "bytes",
+      Type::Timestamp => "timestamp",
+      Type::Date => "date",
+      Type::Time => "time",
+      Type::Duration => "duration",
+      Type::Uuid => "uuid",
+      Type::Decimal => "decimal",
+      Type::Any => "any",
+      Type::Void => "void",
+      Type::List(item) => return write!(f, "list<{}>", item),
+      Type::Set(item) => return write!(f, "set<{}>", item),
+      Type::Map(key, value) => return write!(f, "map<{}, {}>", key, value),
+      Type::Ref(r) => {
+        for ns in &r.namespace {
+          write!(f, "{}.", ns)?;
+        }
+        return write!(f, "{}", r.name);
+      }
+    };
+    write!(f, "{}", name)
+  }
+}
+
+#[test]
+fn test_display() {
+  let base = Type::refer("Base", vec!["foo".to_string(), "bar".to_string()]);
+  assert_eq!(base.to_string(), "foo.bar.Base");
+  assert_eq!(
+    Type::map(Type::String, Type::list(Type::local("Lesson"))).to_string(),
+    "map<string, list<Lesson>>"
+  );
+  assert_eq!(Type::set(Type::UInt64).to_string(), "set<u64>");
+}
+
+#[test]
+fn test_json() {
+  let r#type = Type::map(
+    Type::Int64,
+    Type::list(Type::refer("Base", vec!["base".to_string()])),
+  );
+  let json = serde_json::to_string(&r#type).unwrap();
+  assert_eq!(
+    json,
+    r#"{"kind":"map","key":{"kind":"i64"},"value":{"kind":"list","item":{"kind":"ref","name":"Base","namespace":["base"]}}}"#
+  );
+  assert_eq!(serde_json::from_str::<Type>(&json).unwrap(), r#type);
+}
@@ -0,0 +1,268 @@
+use serde_json::Value;
+
+use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union};
+use ridl_ir::types::Type;
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
+
+/// Lower an avro schema into the type, returns it with whether the field is optional, which is
+/// true for the unions with `null`. The anonymous unions of many types are declared as the unions
+/// named after their paths, e.g. `Lesson_either` of the field `either` of `Lesson`, whose values are
+/// presented in the plain json, e.g. `"x"`, rather than the json encoding of avro, `{"string": "x"}`.
+fn lower_type(
+  schema: &Value,
+  namespace: &str,
+  path: &str,
+  unions: &mut Vec<Definition>,
+) -> (Type, bool) {
+  match schema {
+    Value::String(name) => (lower_named_type(name, namespace), false),
+    Value::Array(union) => {
+      let non_null: Vec<&Value> = union.iter().filter(|s| *s != "null").collect();
+      let optional = non_null.len() < union.len();
+      match non_null[..] {
+        [single] => (lower_type(single, namespace, path, unions).0, optional),
+        [] => (Type::Any, optional),
+        _ => {
+          let variants = non_null
+            .iter()
+            .enumerate()
+            .map(|(i, s)| lower_type(s, namespace, &format!("{}_{}", path, i), unions).0)
+            .collect();
+          unions.push(Definition::Union(Union {
+            name: path.to_string(),
+            variants,
+            doc: None,
+          }));
+          (Type::local(path), optional)
+        }
+      }
+    }
+    Value::Object(o) => {
+      let r#type = o.get("type").and_then(|t| t.as_str()).unwrap_or("");
+      let logical_type = o.get("logicalType").and_then(|t| t.as_str());
+      let field_type = match (r#type, logical_type) {
+        (_, Some("decimal")) => Type::Decimal,
+        (_, Some("uuid")) => Type::Uuid,
+        (_, Some("duration")) => Type::Duration,
+        (_, Some("date")) => Type::Date,
+        (_, Some(l)) if l.starts_with("time-") => Type::Time,
+        (_, Some(l)) if l.contains("timestamp") => Type::Timestamp,
+        ("fixed", _) => Type::Bytes,
+        ("array", _) => {
+          let items = o.get("items").unwrap_or(&Value::Null);
+          Type::list(lower_type(items, namespace, &format!("{}_item", path), unions).0)
+        }
+        ("map", _) => {
+          let values = o.get("values").unwrap_or(&Value::Null);
+          Type::map(
+            Type::String,
+            lower_type(values, namespace, &format!("{}_value", path), unions).0,
+          )
+        }
+        (r#type, _) => lower_named_type(r#type, namespace),
+      };
+      (field_type, false)
+    }
+    _ => (Type::Any, false),
+  }
+}
+
+fn lower_named_type(name: &str, namespace: &str) -> Type {
+  match name {
+    "boolean" => Type::Bool,
+    "int" => Type::Int32,
+    "long" => Type::Int64,
+    "float" => Type::Float32,
+    "double" => Type::Float64,
+    "string" => Type::String,
+    "bytes" => Type::Bytes,
+    "null" => Type::Void,
+    "" => Type::Any,
+    _ => match name.rsplit_once('.') {
+      Some((ns, refer)) if ns != namespace => {
+        Type::refer(refer, ns.split('.').map(|p| p.to_string()).collect())
+      }
+      Some((_, refer)) => Type::local(refer),
+      None => Type::local(name),
+    },
+  }
+}
+
+/// The fields of the record, the unions declared by them are pushed into `unions`.
+fn parse_record(
+  schema: &Value,
+  name: &str,
+  namespace: &str,
+  unions: &mut Vec<Definition>,
+) -> Vec<Field> {
+  let fields = schema["fields"].as_array().cloned().unwrap_or_default();
+  fields
+    .iter()
+    .map(|f| {
+      let field_name = f["name"].as_str().unwrap_or("");
+      let path = format!("{}_{}", name, field_name);
+      let (r#type, optional) = lower_type(&f["type"], namespace, &path, unions);
+      let mut field = Field::new(field_name, r#type, optional);
+      field.doc = f["doc"].as_str().map(|d| d.to_string());
+      field
+    })
+    .collect()
+}
+
+/// The symbols of avro enums are encoded as strings, so the values are the symbols.
+fn parse_enum(schema: &Value) -> Vec<EnumVariant> {
+  let symbols = schema["symbols"].as_array().cloned().unwrap_or_default();
+  symbols
+    .iter()
+    .filter_map(|s| s.as_str())
+    .map(|s| EnumVariant {
+      name: s.to_string(),
+      value: EnumValue::String(s.to_string()),
+    })
+    .collect()
+}
+
+/// Parse the blocks of json schemas searched from avro files.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let mut definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    let schema: Value = serde_json::from_str(b.lines.join("\n").as_str()).unwrap_or_default();
+    let name = b.name.clone();
+    let doc = schema["doc"].as_str().map(|d| d.to_string());
+    let mut unions = Vec::new();
+    let definition = match k {
+      IdlType::Interface => Definition::Record(Record {
+        fields: parse_record(&schema, &name, namespace, &mut unions),
+        name,
+        doc,
+        ..Record::default()
+      }),
+      IdlType::Enum => Definition::Enum(Enum {
+        name,
+        variants: parse_enum(&schema),
+        doc,
+      }),
+      _ => continue,
+    };
+    // the unions follow the records declaring them
+    definitions.push(definition);
+    definitions.append(&mut unions);
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      [CodeBlock {
+        name: "Lesson".to_string(),
+        lines: [r#"{"type": "record", "name": "Lesson", "fields": [
+  {"name": "id", "type": "long"},
+  {"name": "name", "type": ["null", "string"]},
+  {"name": "status", "type": "Status"},
+  {"name": "times", "type": {"type": "array", "items": "foo.bar.LessonTime"}},
+  {"name": "tags", "type": {"type": "map", "values": "boolean"}},
+  {"name": "start", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+  {"name": "price", "type": {"type": "bytes", "logicalType": "decimal"}},
+  {"name": "hash", "type": {"type": "fixed", "name": "MD5", "size": 16}},
+  {"name": "base", "type": ["null", "other.Base"]},
+  {"name": "either", "type": ["int", "string"]},
+  {"name": "eithers", "type": {"type": "array", "items": ["null", "long", "Status"]}}
+]}"#
+          .to_string()]
+        .to_vec(),
+        order: 0,
+      }]
+      .to_vec(),
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      [CodeBlock {
+        name: "Status".to_string(),
+        lines: [r#"{"type": "enum", "name": "Status", "symbols": ["A", "B"]}"#.to_string()]
+          .to_vec(),
+        order: 0,
+      }]
+      .to_vec(),
+    );
+    let module = parser("foo.bar", &blocks_map);
+
+    let Definition::Record(record) = &module.definitions[0] else {
+      panic!("should be a record");
+    };
+    let fields: Vec<(String, bool, String)> = record
+      .fields
+      .iter()
+      .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+      .collect();
+    let answer = [
+      ("id", false, "i64"),
+      ("name", true, "string"),
+      ("status", false, "Status"),
+      ("times", false, "list<LessonTime>"),
+      ("tags", false, "map<string, bool>"),
+      ("start", false, "timestamp"),
+      ("price", false, "decimal"),
+      ("hash", false, "bytes"),
+      ("base", true, "other.Base"),
+      ("either", false, "Lesson_either"),
+      ("eithers", false, "list<Lesson_eithers_item>"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields, answer);
+
+    let unions: Vec<(&str, Vec<String>)> = module.definitions[1..3]
+      .iter()
+      .map(|d| {
+        let Definition::Union(u) = d else {
+          panic!("should be an union");
+        };
+        let variants = u.variants.iter().map(|t| t.to_string()).collect();
+        (u.name.as_str(), variants)
+      })
+      .collect();
+    assert_eq!(
+      unions,
+      [
+        (
+          "Lesson_either",
+          vec!["i32".to_string(), "string".to_string()]
+        ),
+        (
+          "Lesson_eithers_item",
+          vec!["i64".to_string(), "Status".to_string()]
+        ),
+      ]
+    );
+
+    let Definition::Enum(e) = &module.definitions[3] else {
+      panic!("should be an enum");
+    };
+    let variants: Vec<(&str, &EnumValue)> = e
+      .variants
+      .iter()
+      .map(|v| (v.name.as_str(), &v.value))
+      .collect();
+    assert_eq!(
+      variants,
+      [
+        ("A", &EnumValue::String("A".to_string())),
+        ("B", &EnumValue::String("B".to_string()))
+      ]
+    );
+  }
+}
@@ -0,0 +1,491 @@
+use std::collections::{HashMap, HashSet};
+
+use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record};
+use ridl_ir::types::Type;
+use ridl_utils::lexer::{tokenize, Token, Tokens};
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
+
+/// The type of a struct field in Cap'n Proto, e.g. `List(Person.Phone)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CapnpType {
+  Named(String),
+  List(Box<CapnpType>),
+}
+
+#[derive(Debug, Clone)]
+struct CapnpField {
+  name: String,
+  r#type: CapnpType,
+  /// The fields of unions are optional, as only one of them is set.
+  in_union: bool,
+}
+
+/// A struct or enum whose nested path is flattened, e.g. `Person_Phone`. The fields of enums are
+/// kept as (key, value).
+#[derive(Debug, Clone)]
+enum CapnpDefinition {
+  Struct {
+    path: Vec<String>,
+    fields: Vec<CapnpField>,
+  },
+  Enum {
+    path: Vec<String>,
+    values: Vec<(String, String)>,
+  },
+}
+
+fn parse_type(tokens: &mut Tokens) -> Option<CapnpType> {
+  let name = tokens.dotted_ident()?;
+  if !tokens.eat_symbol('(') {
+    return Some(CapnpType::Named(name));
+  }
+  if name == "List" {
+    let item = parse_type(tokens)?;
+    tokens.skip_until(')');
+    return Some(CapnpType::List(Box::new(item)));
+  }
+  // the parameters of generic types are not presented
+  tokens.skip_until(')');
+  Some(CapnpType::Named(name))
+}
+
+fn parse_enum_body(tokens: &mut Tokens) -> Vec<(String, String)> {
+  let mut values = Vec::new();
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    match (
+      tokens.ident(),
+      tokens.eat_symbol('@'),
+      tokens.peek().cloned(),
+    ) {
+      (Some(key), true, Some(Token::Number(n))) => {
+        values.push((key, n));
+        tokens.skip_statement();
+      }
+      _ => tokens.skip_statement(),
+    }
+  }
+  values
+}
+
+/// Skip the id, generic parameters and annotations of a definition up to its body.
+fn skip_to_body(tokens: &mut Tokens) {
+  while !tokens.is_end() && !tokens.eat_symbol('{') {
+    if tokens.eat_symbol('(') {
+      tokens.skip_until(')');
+    } else {
+      tokens.next_token();
+    }
+  }
+}
+
+/// Parse the body of a struct, group or union into the fields of the struct at `path`, the nested
+/// definitions are pushed after the struct in the order they are declared.
+fn parse_struct_body(
+  tokens: &mut Tokens,
+  path: &[String],
+  in_union: bool,
+  fields: &mut Vec<CapnpField>,
+  definitions: &mut Vec<CapnpDefinition>,
+) {
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    if tokens.eat_symbol(';') {
+      continue;
+    }
+    let is_definition = matches!(tokens.peek_nth(1), Some(Token::Ident(_)));
+    if is_definition && tokens.eat_ident("struct") {
+      let mut nested_path = path.to_vec();
+      nested_path.push(tokens.ident().unwrap());
+      skip_to_body(tokens);
+      parse_struct(tokens, nested_path, false, definitions);
+    } else if is_definition && tokens.eat_ident("enum") {
+      let mut nested_path = path.to_vec();
+      nested_path.push(tokens.ident().unwrap());
+      skip_to_body(tokens);
+      definitions.push(CapnpDefinition::Enum {
+        path: nested_path,
+        values: parse_enum_body(tokens),
+      });
+    } else if tokens.peek().is_some_and(|t| t.is_ident("union"))
+      && tokens.peek_nth(1).is_some_and(|t| t.is_symbol('{'))
+    {
+      // the unnamed union shares the scope of the struct
+      tokens.next_token();
+      tokens.next_token();
+      parse_struct_body(tokens, path, true, fields, definitions);
+    } else if ["using", "const", "annotation", "interface"]
+      .iter()
+      .any(|k| tokens.peek().is_some_and(|t| t.is_ident(k)))
+      || tokens.peek().is_some_and(|t| t.is_symbol('$'))
+    {
+      tokens.skip_statement();
+    } else if let Some(name) = tokens.ident() {
+      if tokens.eat_symbol('@') {
+        tokens.next_token();
+      }
+      if !tokens.eat_symbol(':') {
+        tokens.skip_statement();
+        continue;
+      }
+      let is_group = ["group", "union"]
+        .iter()
+        .any(|k| tokens.peek().is_some_and(|t| t.is_ident(k)));
+      let r#type = if is_group {
+        // the groups and named unions are structs named after the fields, e.g. `Person_address`
+        let is_union = tokens.eat_ident("union");
+        tokens.eat_ident("group");
+        skip_to_body(tokens);
+        let mut group_path = path.to_vec();
+        group_path.push(name.clone());
+        parse_struct(tokens, group_path.clone(), is_union, definitions);
+        CapnpType::Named(group_path.join("."))
+      } else {
+        let Some(r#type) = parse_type(tokens) else {
+          tokens.skip_statement();
+          continue;
+        };
+        // the default values and annotations
+        tokens.skip_statement();
+        r#type
+      };
+      fields.push(CapnpField {
+        name,
+        r#type,
+        in_union,
+      });
+    } else {
+      tokens.skip_statement();
+    }
+  }
+}
+
+fn parse_struct(
+  tokens: &mut Tokens,
+  path: Vec<String>,
+  in_union: bool,
+  definitions: &mut Vec<CapnpDefinition>,
+) {
+  let index = definitions.len();
+  definitions.push(CapnpDefinition::Struct {
+    path: path.clone(),
+    fields: Vec::new(),
+  });
+  let mut fields = Vec::new();
+  parse_struct_body(tokens, &path, in_union, &mut fields, definitions);
+  definitions[index] = CapnpDefinition::Struct { path, fields };
+}
+
+fn lower_builtin_type(name: &str) -> Option<Type> {
+  let r#type = match name {
+    "Bool" => Type::Bool,
+    "Int8" => Type::Int8,
+    "Int16" => Type::Int16,
+    "Int32" => Type::Int32,
+    "Int64" => Type::Int64,
+    "UInt8" => Type::UInt8,
+    "UInt16" => Type::UInt16,
+    "UInt32" => Type::UInt32,
+    "UInt64" => Type::UInt64,
+    "Float32" => Type::Float32,
+    "Float64" => Type::Float64,
+    "Text" => Type::String,
+    "Data" => Type::Bytes,
+    "Void" => Type::Void,
+    "AnyPointer" | "AnyStruct" | "AnyList" | "Capability" => Type::Any,
+    _ => return None,
+  };
+  Some(r#type)
+}
+
+/// The namespaces of the imports by their names, e.g. `Base` of the block of
+/// `using Base = import "base.capnp";` followed by the `$Cxx.namespace("common");` of the file.
+/// The namespace of the current one is empty.
+fn parse_imports(namespace: &str, blocks_map: &IdlBlocksMap) -> HashMap<String, Vec<String>> {
+  blocks_map
+    .get(&IdlType::Alias)
+    .into_iter()
+    .flatten()
+    .filter_map(|b| {
+      let imported = b.lines.get(1)?.split('"').nth(1)?.replace("::", ".");
+      let parts = match imported == namespace {
+        true => Vec::new(),
+        false => imported
+          .split('.')
+          .filter(|p| !p.is_empty())
+          .map(|p| p.to_string())
+          .collect(),
+      };
+      Some((b.name.clone(), parts))
+    })
+    .collect()
+}
+
+/// The types the struct of `scope` refers to, by the declared paths of the namespace and the
+/// imports.
+struct Scope<'a> {
+  path: &'a [String],
+  declared: &'a HashSet<String>,
+  imports: &'a HashMap<String, Vec<String>>,
+}
+
+/// Resolve the type name used inside the struct of the scope, starting from the innermost scope.
+/// The names led by an import refer to the namespace of the imported file, e.g. `Base.Id` of
+/// `using Base = import "base.capnp";` is `common.Id` if `base.capnp` is of `common`. The other
+/// names not declared are taken as ones of unknown imports, e.g. `Other.Id` is `Id`.
+fn lower_type(r#type: &CapnpType, scope: &Scope) -> Type {
+  match r#type {
+    CapnpType::List(item) => Type::list(lower_type(item, scope)),
+    CapnpType::Named(name) => {
+      if let Some(builtin) = lower_builtin_type(name) {
+        return builtin;
+      }
+      let resolved = (0..=scope.path.len()).rev().find_map(|i| {
+        let mut path = scope.path[..i].to_vec();
+        path.push(name.clone());
+        let path = path.join(".");
+        scope
+          .declared
+          .contains(&path)
+          .then(|| path.replace('.', "_"))
+      });
+      if let Some(resolved) = resolved {
+        return Type::local(&resolved);
+      }
+      let parts: Vec<&str> = name.split('.').collect();
+      match scope.imports.get(parts[0]) {
+        Some(namespace) if parts.len() > 1 => Type::refer(&parts[1..].join("_"), namespace.clone()),
+        _ => Type::local(&parts[parts.len().min(2) - 1..].join("_")),
+      }
+    }
+  }
+}
+
+/// Parse the blocks of a namespace, the nested structs and enums, the groups and the named unions
+/// are flattened into records and enums named after their paths joined by `_`, e.g.
+/// `Person_Phone`.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let mut definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    if *k == IdlType::Alias {
+      continue;
+    }
+    let mut tokens = Tokens::new(tokenize(b.lines.join("\n").as_str(), &["#"]));
+    tokens.next_token();
+    let Some(name) = tokens.ident() else {
+      continue;
+    };
+    skip_to_body(&mut tokens);
+    match k {
+      IdlType::Interface => parse_struct(&mut tokens, vec![name], false, &mut definitions),
+      IdlType::Enum => definitions.push(CapnpDefinition::Enum {
+        path: vec![name],
+        values: parse_enum_body(&mut tokens),
+      }),
+      _ => {}
+    }
+  }
+
+  let declared: HashSet<String> = definitions
+    .iter()
+    .map(|d| match d {
+      CapnpDefinition::Struct { path, .. } | CapnpDefinition::Enum { path, .. } => path.join("."),
+    })
+    .collect();
+  let imports = parse_imports(namespace, blocks_map);
+
+  let mut lowered = Vec::new();
+  for d in definitions {
+    match d {
+      CapnpDefinition::Struct { path, fields } => {
+        let fields = fields
+          .iter()
+          .map(|f| {
+            let scope = Scope {
+              path: &path,
+              declared: &declared,
+              imports: &imports,
+            };
+            Field::new(&f.name, lower_type(&f.r#type, &scope), f.in_union)
+          })
+          .collect();
+        lowered.push(Definition::Record(Record {
+          name: path.join("_"),
+          fields,
+          ..Record::default()
+        }));
+      }
+      CapnpDefinition::Enum { path, values } => {
+        let variants = values
+          .into_iter()
+          .map(|(name, value)| EnumVariant {
+            name,
+            value: EnumValue::Int(value.parse().unwrap_or(0)),
+          })
+          .collect();
+        lowered.push(Definition::Enum(Enum {
+          name: path.join("_"),
+          variants,
+          ..Enum::default()
+        }));
+      }
+    }
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions: lowered,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn block(name: &str, source: &str) -> CodeBlock {
+    CodeBlock {
+      name: name.to_string(),
+      lines: source.lines().map(|l| l.to_string()).collect(),
+      order: 0,
+    }
+  }
+
+  fn fields(definition: &Definition) -> (String, Vec<(String, bool, String)>) {
+    let fields = match definition {
+      Definition::Record(r) => r
+        .fields
+        .iter()
+        .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+        .collect(),
+      Definition::Enum(e) => e
+        .variants
+        .iter()
+        .map(|v| match &v.value {
+          EnumValue::Int(i) => (v.name.clone(), false, i.to_string()),
+          EnumValue::String(s) => (v.name.clone(), false, s.clone()),
+        })
+        .collect(),
+      _ => Vec::new(),
+    };
+    (definition.name().to_string(), fields)
+  }
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![block(
+        "Person",
+        r#"struct Person @0x8e0594c8abeb307c {
+  id @0 :UInt64;
+  name @1 :Text $Json.name("fullName");
+  age @2 :UInt8 = 18;
+  phones @3 :List(Phone);
+  tags @4 :List(Text) = ["a", "b"];
+  status @5 :Status;
+  base @6 :Base.Id;
+  other @14 :Other.Id;
+  inner @15 :Base.Outer.Inner;
+
+  struct Phone {
+    number @0 :Text;
+    kind @1 :Kind;
+    enum Kind { mobile @0; home @1; }
+  }
+
+  union {
+    unemployed @7 :Void;
+    employer @8 :Text;
+  }
+
+  address :group {
+    city @9 :Text;
+  }
+
+  contact :union {
+    email @10 :Text;
+    phone @11 :Phone;
+  }
+
+  matrix @12 :List(List(Int32));
+  extra @13 :Map(Text, Text);
+}"#,
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      vec![block(
+        "Status",
+        "enum Status { open @0; closed @1 $deprecated; }",
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Alias,
+      vec![block(
+        "Base",
+        "using Base = import \"base.capnp\";\n$Cxx.namespace(\"ek::common\");",
+      )],
+    );
+    let module = parser("", &blocks_map);
+
+    // the nested definitions follow their parents
+    let definitions: Vec<_> = module.definitions.iter().map(fields).collect();
+    let to_fields = |fields: &[(&str, bool, &str)]| -> Vec<(String, bool, String)> {
+      fields
+        .iter()
+        .map(|(n, o, t)| (n.to_string(), *o, t.to_string()))
+        .collect()
+    };
+    assert_eq!(
+      definitions,
+      [
+        (
+          "Person".to_string(),
+          to_fields(&[
+            ("id", false, "u64"),
+            ("name", false, "string"),
+            ("age", false, "u8"),
+            ("phones", false, "list<Person_Phone>"),
+            ("tags", false, "list<string>"),
+            ("status", false, "Status"),
+            ("base", false, "ek.common.Id"),
+            ("other", false, "Id"),
+            ("inner", false, "ek.common.Outer_Inner"),
+            ("unemployed", true, "void"),
+            ("employer", true, "string"),
+            ("address", false, "Person_address"),
+            ("contact", false, "Person_contact"),
+            ("matrix", false, "list<list<i32>>"),
+            ("extra", false, "Map"),
+          ])
+        ),
+        (
+          "Person_Phone".to_string(),
+          to_fields(&[
+            ("number", false, "string"),
+            ("kind", false, "Person_Phone_Kind")
+          ])
+        ),
+        (
+          "Person_Phone_Kind".to_string(),
+          to_fields(&[("mobile", false, "0"), ("home", false, "1")])
+        ),
+        (
+          "Person_address".to_string(),
+          to_fields(&[("city", false, "string")])
+        ),
+        (
+          "Person_contact".to_string(),
+          to_fields(&[("email", true, "string"), ("phone", true, "Person_Phone")])
+        ),
+        (
+          "Status".to_string(),
+          to_fields(&[("open", false, "0"), ("closed", false, "1")])
+        ),
+      ]
+    );
+  }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+
+use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union};
+use ridl_ir::types::Type;
+use ridl_utils::lexer::{tokenize, Token, Tokens};
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
+
+const SCALAR_TYPES: [&str; 21] = [
+  "bool", "byte", "ubyte", "short", "ushort", "int", "uint", "float", "long", "ulong", "double",
+  "int8", "uint8", "int16", "uint16", "int32", "uint32", "int64", "uint64", "float32", "float64",
+];
+
+/// The attributes of a definition or a field, e.g. `(deprecated, id: 1)`.
+fn parse_attributes(tokens: &mut Tokens) -> Vec<String> {
+  let mut attributes = Vec::new();
+  if !tokens.eat_symbol('(') {
+    return attributes;
+  }
+  while !tokens.is_end() && !tokens.eat_symbol(')') {
+    match tokens.next_token() {
+      Some(Token::Ident(i)) => {
+        attributes.push(i);
+        if tokens.eat_symbol(':') {
+          tokens.next_token();
+        }
+      }
+      _ => continue,
+    }
+  }
+  attributes
+}
+
+/// Lower a type name, the qualified names of the same namespace are the local ones.
+fn lower_named_type(name: &str, namespace: &str) -> Type {
+  match name {
+    "bool" => Type::Bool,
+    "byte" | "int8" => Type::Int8,
+    "ubyte" | "uint8" => Type::UInt8,
+    "short" | "int16" => Type::Int16,
+    "ushort" | "uint16" => Type::UInt16,
+    "int" | "int32" => Type::Int32,
+    "uint" | "uint32" => Type::UInt32,
+    "long" | "int64" => Type::Int64,
+    "ulong" | "uint64" => Type::UInt64,
+    "float" | "float32" => Type::Float32,
+    "double" | "float64" => Type::Float64,
+    "string" => Type::String,
+    _ => match name.rsplit_once('.') {
+      Some((ns, refer)) if ns != namespace => {
+        Type::refer(refer, ns.split('.').map(|p| p.to_string()).collect())
+      }
+      Some((_, refer)) => Type::local(refer),
+      None => Type::local(name),
+    },
+  }
+}
+
+/// Parse the fields of a table or struct. The scalars (including enums) of tables have default
+/// values, so they are optional only if the default is `null`, while the other fields of tables
+/// are optional unless they are `required`. The fields of structs are always present and the
+/// deprecated fields are skipped.
+fn parse_fields(
+  tokens: &mut Tokens,
+  is_table: bool,
+  enums: &HashSet<String>,
+  namespace: &str,
+) -> Vec<Field> {
+  let mut fields = Vec::new();
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    let Some(name) = tokens.ident() else {
+      tokens.skip_statement();
+      continue;
+    };
+    if !tokens.eat_symbol(':') {
+      tokens.skip_statement();
+      continue;
+    }
+    let (type_name, is_vector) = if tokens.eat_symbol('[') {
+      let item = tokens.dotted_ident().unwrap_or_default();
+      // the fixed length arrays of structs, e.g. `[int:3]`
+      tokens.skip_until(']');
+      (item, true)
+    } else {
+      (tokens.dotted_ident().unwrap_or_default(), false)
+    };
+    let is_null_default = tokens.eat_symbol('=') && {
+      tokens.eat_symbol('-');
+      tokens.next_token().is_some_and(|t| t.is_ident("null"))
+    };
+    let attributes = parse_attributes(tokens);
+    tokens.skip_statement();
+    if attributes.iter().any(|a| a == "deprecated") {
+      continue;
+    }
+
+    let single = lower_named_type(&type_name, namespace);
+    let is_scalar = !is_vector
+      && (SCALAR_TYPES.contains(&type_name.as_str())
+        || enums.contains(type_name.rsplit('.').next().unwrap_or("")));
+    let r#type = match is_vector {
+      true => Type::list(single),
+      false => single,
+    };
+    let optional = match (is_table, is_scalar) {
+      (false, _) => false,
+      (true, true) => is_null_default,
+      (true, false) => !attributes.iter().any(|a| a == "required"),
+    };
+    fields.push(Field::new(&name, r#type, optional));
+  }
+  fields
+}
+
+/// The values without explicit values follow the previous ones, and the values of `bit_flags`
+/// enums are the bits, e.g. `1 << 2` for the value of `2`.
+fn parse_enum_values(tokens: &mut Tokens, bit_flags: bool) -> Vec<EnumVariant> {
+  let mut variants = Vec::new();
+  let mut next_value: i64 = 0;
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    let Some(name) = tokens.ident() else {
+      tokens.next_token();
+      continue;
+    };
+    let mut value = next_value;
+    if tokens.eat_symbol('=') {
+      let negative = tokens.eat_symbol('-');
+      if let Some(Token::Number(n)) = tokens.next_token() {
+        value = n.parse().unwrap_or(next_value);
+        if negative {
+          value = -value;
+        }
+      }
+    }
+    next_value = value + 1;
+    tokens.eat_symbol(',');
+    variants.push(EnumVariant {
+      name,
+      value: EnumValue::Int(match bit_flags {
+        true => 1_i64 << value,
+        false => value,
+      }),
+    });
+  }
+  variants
+}
+
+/// The members of an union, e.g. `Weapon` or `alias: Foo`.
+fn parse_union_members(tokens: &mut Tokens, namespace: &str) -> Vec<Type> {
+  let mut members = Vec::new();
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    let Some(mut member) = tokens.dotted_ident() else {
+      tokens.next_token();
+      continue;
+    };
+    if tokens.eat_symbol(':') {
+      member = tokens.dotted_ident().unwrap_or(member);
+    }
+    tokens.eat_symbol(',');
+    members.push(lower_named_type(&member, namespace));
+  }
+  members
+}
+
+/// Parse the blocks of a namespace, the enums declared in it are needed to tell the scalar fields.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let enums: HashSet<String> = blocks_map
+    .get(&IdlType::Enum)
+    .into_iter()
+    .flatten()
+    .map(|b| b.name.clone())
+    .collect();
+
+  let mut definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    let mut tokens = Tokens::new(tokenize(b.lines.join("\n").as_str(), &["//"]));
+    let keyword = tokens.ident().unwrap_or_default();
+    tokens.ident();
+    // the underlying type of enums, e.g. `enum Color : byte`
+    if tokens.eat_symbol(':') {
+      tokens.ident();
+    }
+    let attributes = parse_attributes(&mut tokens);
+    tokens.skip_until('{');
+    let name = b.name.clone();
+    definitions.push(match k {
+      IdlType::Interface => Definition::Record(Record {
+        name,
+        fields: parse_fields(&mut tokens, keyword == "table", &enums, namespace),
+        ..Record::default()
+      }),
+      IdlType::Enum => Definition::Enum(Enum {
+        name,
+        variants: parse_enum_values(&mut tokens, attributes.iter().any(|a| a == "bit_flags")),
+        ..Enum::default()
+      }),
+      IdlType::Alias => Definition::Union(Union {
+        name,
+        variants: parse_union_members(&mut tokens, namespace),
+        ..Union::default()
+      }),
+      _ => continue,
+    });
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn block(name: &str, source: &str) -> CodeBlock {
+    CodeBlock {
+      name: name.to_string(),
+      lines: source.lines().map(|l| l.to_string()).collect(),
+      order: 0,
+    }
+  }
+
+  fn fields(definition: &Definition) -> Vec<(String, bool, String)> {
+    match definition {
+      Definition::Record(r) => r
+        .fields
+        .iter()
+        .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+        .collect(),
+      Definition::Enum(e) => e
+        .variants
+        .iter()
+        .map(|v| match &v.value {
+          EnumValue::Int(i) => (v.name.clone(), false, i.to_string()),
+          EnumValue::String(s) => (v.name.clone(), false, s.clone()),
+        })
+        .collect(),
+      Definition::Union(u) => u
+        .variants
+        .iter()
+        .map(|t| (String::new(), false, t.to_string()))
+        .collect(),
+      _ => Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![
+        block(
+          "Monster",
+          r#"table Monster (priority: 1) {
+  pos:Vec3;
+  hp:short = 100;
+  mana:int = null;
+  name:string (required);
+  color:Color = Blue;
+  inventory:[ubyte];
+  weapons:[game.telemetry.Weapon];
+  shield:base.Shield;
+  equipped:Equipment;
+  friendly:bool = false (deprecated);
+}"#,
+        ),
+        block("Vec3", "struct Vec3 { x:float; y:float; ids:[int:3]; }"),
+      ],
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      vec![
+        block("Color", "enum Color : byte { Red = 1, Green, Blue = 5 }"),
+        block("Flags", "enum Flags : ubyte (bit_flags) { A, B, C = 4 }"),
+      ],
+    );
+    blocks_map.insert(
+      IdlType::Alias,
+      vec![block(
+        "Equipment",
+        "union Equipment { Weapon, alias: base.Shield }",
+      )],
+    );
+    let module = parser("game.telemetry", &blocks_map);
+
+    let interfaces = &module.definitions[0..2];
+    let answer = [
+      ("pos", true, "Vec3"),
+      ("hp", false, "i16"),
+      ("mana", true, "i32"),
+      ("name", false, "string"),
+      ("color", false, "Color"),
+      ("inventory", true, "list<u8>"),
+      ("weapons", true, "list<Weapon>"),
+      ("shield", true, "base.Shield"),
+      ("equipped", true, "Equipment"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&interfaces[0]), answer);
+    let answer = [
+      ("x", false, "f32"),
+      ("y", false, "f32"),
+      ("ids", false, "list<i32>"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&interfaces[1]), answer);
+
+    let enums = &module.definitions[2..4];
+    let answer = [
+      ("Red", false, "1"),
+      ("Green", false, "2"),
+      ("Blue", false, "5"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&enums[0]), answer);
+    let answer = [("A", false, "1"), ("B", false, "2"), ("C", false, "16")]
+      .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&enums[1]), answer);
+
+    let answer = [("", false, "Weapon"), ("", false, "base.Shield")]
+      .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&module.definitions[4]), answer);
+  }
+}
@@ -0,0 +1,337 @@
+use ridl_ir::schema::{
+  Alias, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union,
+};
+use ridl_ir::types::Type;
+use ridl_utils::lexer::{tokenize, Token, Tokens};
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
+
+/// The type of a field in graphql, e.g. `[Lesson!]!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GraphQLType {
+  Named(String),
+  List(Box<GraphQLType>),
+  NonNull(Box<GraphQLType>),
+}
+
+fn parse_type(tokens: &mut Tokens) -> Option<GraphQLType> {
+  let r#type = if tokens.eat_symbol('[') {
+    let item = parse_type(tokens)?;
+    tokens.eat_symbol(']');
+    GraphQLType::List(Box::new(item))
+  } else {
+    GraphQLType::Named(tokens.ident()?)
+  };
+  if tokens.eat_symbol('!') {
+    return Some(GraphQLType::NonNull(Box::new(r#type)));
+  }
+  Some(r#type)
+}
+
+/// Skip the directives, e.g. `@deprecated(reason: "use id")`.
+fn skip_directives(tokens: &mut Tokens) {
+  while tokens.eat_symbol('@') {
+    tokens.ident();
+    if tokens.eat_symbol('(') {
+      tokens.skip_until(')');
+    }
+  }
+}
+
+/// Skip a constant value, e.g. `1`, `"a"`, `OPEN`, `[1, 2]` or `{ a: 1 }`.
+fn skip_value(tokens: &mut Tokens) {
+  tokens.eat_symbol('-');
+  match tokens.next_token() {
+    Some(Token::Symbol('[')) => tokens.skip_until(']'),
+    Some(Token::Symbol('{')) => tokens.skip_until('}'),
+    _ => {}
+  }
+}
+
+fn lower_named_type(name: &str) -> Type {
+  match name {
+    "Int" => Type::Int32,
+    "Float" => Type::Float64,
+    "String" | "ID" => Type::String,
+    "Boolean" => Type::Bool,
+    _ => Type::local(name),
+  }
+}
+
+/// Lower the type, returns it with whether the field is optional, which is true for the nullable
+/// types.
+fn lower_type(r#type: &GraphQLType) -> (Type, bool) {
+  match r#type {
+    GraphQLType::NonNull(t) => (lower_type(t).0, false),
+    GraphQLType::Named(name) => (lower_named_type(name), true),
+    GraphQLType::List(item) => (Type::list(lower_type(item).0), true),
+  }
+}
+
+/// Parse the fields of `type`, `input` and `interface` until the closing `}`, the arguments of
+/// fields are skipped.
+fn parse_fields(tokens: &mut Tokens) -> Vec<Field> {
+  let mut fields = Vec::new();
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    // the descriptions
+    if let Some(Token::Str(_)) = tokens.peek() {
+      tokens.next_token();
+      continue;
+    }
+    let Some(name) = tokens.ident() else {
+      tokens.next_token();
+      continue;
+    };
+    if tokens.eat_symbol('(') {
+      tokens.skip_until(')');
+    }
+    if !tokens.eat_symbol(':') {
+      continue;
+    }
+    let Some(r#type) = parse_type(tokens) else {
+      continue;
+    };
+    if tokens.eat_symbol('=') {
+      skip_value(tokens);
+    }
+    skip_directives(tokens);
+    let (r#type, optional) = lower_type(&r#type);
+    fields.push(Field::new(&name, r#type, optional));
+  }
+  fields
+}
+
+/// The values of graphql enums are serialized as their names.
+fn parse_enum_values(tokens: &mut Tokens) -> Vec<EnumVariant> {
+  let mut variants = Vec::new();
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    match tokens.next_token() {
+      Some(Token::Ident(name)) => {
+        variants.push(EnumVariant {
+          value: EnumValue::String(name.clone()),
+          name,
+        });
+        skip_directives(tokens);
+      }
+      _ => continue,
+    }
+  }
+  variants
+}
+
+/// An union is lowered into the union of its members, and a custom scalar into an alias of any.
+fn parse_alias(tokens: &mut Tokens, keyword: &str, name: String) -> Definition {
+  if keyword == "scalar" {
+    return Definition::Alias(Alias {
+      name,
+      r#type: Type::Any,
+      doc: None,
+    });
+  }
+  skip_directives(tokens);
+  let mut variants = Vec::new();
+  if tokens.eat_symbol('=') {
+    loop {
+      tokens.eat_symbol('|');
+      match tokens.ident() {
+        Some(member) => variants.push(lower_named_type(&member)),
+        None => break,
+      }
+    }
+  }
+  Definition::Union(Union {
+    name,
+    variants,
+    doc: None,
+  })
+}
+
+/// Merge an extension into the type it extends, its fields, values or members are appended.
+fn merge_extension(definitions: &mut [Definition], extension: Definition) {
+  let extended = definitions
+    .iter_mut()
+    .find(|d| d.name() == extension.name());
+  match (extended, extension) {
+    (Some(Definition::Record(r)), Definition::Record(e)) => r.fields.extend(e.fields),
+    (Some(Definition::Enum(r)), Definition::Enum(e)) => r.variants.extend(e.variants),
+    (Some(Definition::Union(r)), Definition::Union(e)) => r.variants.extend(e.variants),
+    (_, extension) => eprintln!(
+      "the extension of `{}` is dropped, the type it extends is not declared",
+      extension.name()
+    ),
+  }
+}
+
+/// Parse the blocks of the schema, the nullable fields are optional. The extensions are merged into
+/// the types they extend.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let mut definitions = Vec::new();
+  let mut extensions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    let mut tokens = Tokens::new(tokenize(b.lines.join("\n").as_str(), &["#"]));
+    let mut keyword = tokens.ident().unwrap_or_default();
+    let is_extension = keyword == "extend";
+    if is_extension {
+      keyword = tokens.ident().unwrap_or_default();
+    }
+    tokens.ident();
+    let name = b.name.clone();
+    let definition = match k {
+      IdlType::Alias => parse_alias(&mut tokens, keyword.as_str(), name),
+      _ => {
+        // skip the implemented interfaces and directives
+        while !tokens.is_end() && !tokens.eat_symbol('{') {
+          if tokens.eat_symbol('(') {
+            tokens.skip_until(')');
+          } else {
+            tokens.next_token();
+          }
+        }
+        match k {
+          IdlType::Enum => Definition::Enum(Enum {
+            name,
+            variants: parse_enum_values(&mut tokens),
+            ..Enum::default()
+          }),
+          IdlType::Interface => Definition::Record(Record {
+            name,
+            fields: parse_fields(&mut tokens),
+            ..Record::default()
+          }),
+          _ => continue,
+        }
+      }
+    };
+    match is_extension {
+      true => extensions.push(definition),
+      false => definitions.push(definition),
+    }
+  }
+  for extension in extensions {
+    merge_extension(&mut definitions, extension);
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn block(name: &str, line: &str) -> CodeBlock {
+    CodeBlock {
+      name: name.to_string(),
+      lines: vec![line.to_string()],
+      order: 0,
+    }
+  }
+
+  fn fields(definition: &Definition) -> Vec<(String, bool, String)> {
+    match definition {
+      Definition::Record(r) => r
+        .fields
+        .iter()
+        .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+        .collect(),
+      Definition::Enum(e) => e
+        .variants
+        .iter()
+        .map(|v| match &v.value {
+          EnumValue::Int(i) => (v.name.clone(), false, i.to_string()),
+          EnumValue::String(s) => (v.name.clone(), false, format!("{:?}", s)),
+        })
+        .collect(),
+      Definition::Union(u) => u
+        .variants
+        .iter()
+        .map(|t| (String::new(), false, t.to_string()))
+        .collect(),
+      Definition::Alias(a) => vec![(String::new(), false, a.r#type.to_string())],
+      _ => Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![
+        block(
+          "Course",
+          r#"type Course implements Node & Entity @key(fields: "id") {
+  id: ID!
+  "the lessons"
+  lessons(first: Int = 10, after: [String!] = ["a"]): [Lesson!]!
+  tags: [String]
+  price: Float @deprecated(reason: "use cost")
+  status: Status!
+  matrix: [[Int!]!]
+  published: Boolean = false
+}"#,
+        ),
+        block("Course", "extend type Course @a { extra: Int! }"),
+        // the extension of an undeclared type is dropped
+        block("Missing", "extend type Missing { a: Int }"),
+      ],
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      vec![
+        block("Status", "enum Status @a { OPEN CLOSED @deprecated }"),
+        block("Status", "extend enum Status { ARCHIVED }"),
+      ],
+    );
+    blocks_map.insert(
+      IdlType::Alias,
+      vec![
+        block("SearchResult", "union SearchResult @a = | Course | Lesson"),
+        block("DateTime", "scalar DateTime @specifiedBy(url: \"x\")"),
+        block("SearchResult", "extend union SearchResult = Teacher"),
+      ],
+    );
+    let module = parser("", &blocks_map);
+    let definitions = &module.definitions;
+
+    let answer = [
+      ("id", false, "string"),
+      ("lessons", false, "list<Lesson>"),
+      ("tags", true, "list<string>"),
+      ("price", true, "f64"),
+      ("status", false, "Status"),
+      ("matrix", true, "list<list<i32>>"),
+      ("published", true, "bool"),
+      ("extra", false, "i32"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&definitions[0]), answer);
+
+    let answer = [
+      ("OPEN", false, "\"OPEN\""),
+      ("CLOSED", false, "\"CLOSED\""),
+      ("ARCHIVED", false, "\"ARCHIVED\""),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&definitions[1]), answer);
+
+    assert!(matches!(definitions[2], Definition::Union(_)));
+    let answer = [
+      ("", false, "Course"),
+      ("", false, "Lesson"),
+      ("", false, "Teacher"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&definitions[2]), answer);
+    assert_eq!(
+      fields(&definitions[3]),
+      [(String::new(), false, "any".to_string())]
+    );
+    assert_eq!(definitions.len(), 4);
+  }
+}
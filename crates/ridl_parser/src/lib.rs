@@ -1,24 +1,66 @@
+use ridl_ir::schema::{Module, Schema};
+use ridl_utils::pool::par_map;
 use ridl_utils::types::{IdlBlocksMap, IdlProtocolType, NamespaceIdlBlocksMap};
-mod thrift;
-use thrift::parser::{parser as parser_thrift, IdlMetaMap, NamespaceIdlMetaMap};
+pub mod avro;
+pub mod capnp;
+pub mod flatbuffers;
+pub mod graphql;
+pub mod openapi;
+pub mod protobuf;
+pub mod thrift;
+pub mod thrift_json;
+use avro::parser::parser as parser_avro;
+use capnp::parser::parser as parser_capnp;
+use flatbuffers::parser::parser as parser_flatbuffers;
+use graphql::parser::parser as parser_graphql;
+use openapi::parser::{
+  collect_objects as collect_objects_openapi, parser as parser_openapi,
+  parser_with_objects as parser_with_objects_openapi,
+};
+use protobuf::parser::parser as parser_protobuf;
+use protobuf::resolver::resolve_refs as resolve_refs_protobuf;
+use thrift::parser::parser as parser_thrift;
+use thrift_json::parser::parser as parser_thrift_json;
 
-type Parser = fn(blocks_map: &IdlBlocksMap) -> IdlMetaMap;
+/// Every protocol lowers the blocks of a namespace into a module of the ir.
+type Parser = fn(namespace: &str, blocks_map: &IdlBlocksMap) -> Module;
 
-pub fn parse_idl_metas_from_blocks(
+pub fn parse_schema_from_blocks(
   idl_protocol_type: &IdlProtocolType,
   namespace_idl_blocks_map: &NamespaceIdlBlocksMap,
-) -> NamespaceIdlMetaMap {
-  let mut idl_metas_map = NamespaceIdlMetaMap::new();
+) -> Schema {
+  let mut schema = Schema::default();
 
   let parser: Parser = match idl_protocol_type {
     IdlProtocolType::Thrift => parser_thrift,
-    // TODO: other idl types.
+    IdlProtocolType::Protobuf => parser_protobuf,
+    IdlProtocolType::Avro => parser_avro,
+    IdlProtocolType::GraphQL => parser_graphql,
+    IdlProtocolType::OpenApi => parser_openapi,
+    IdlProtocolType::ThriftJson => parser_thrift_json,
+    IdlProtocolType::FlatBuffers => parser_flatbuffers,
+    IdlProtocolType::CapnProto => parser_capnp,
   };
 
-  for (k, v) in namespace_idl_blocks_map {
-    let idl_metas = parser(v);
-    idl_metas_map.insert(k.clone(), idl_metas);
+  // parse the blocks of every namespace in parallel
+  let namespaces: Vec<(&String, &IdlBlocksMap)> = namespace_idl_blocks_map.iter().collect();
+  let modules = match idl_protocol_type {
+    // the `allOf` of an openapi document may merge the objects of other documents
+    IdlProtocolType::OpenApi => {
+      let objects = collect_objects_openapi(namespace_idl_blocks_map);
+      par_map(namespaces, |(k, v)| {
+        (k.clone(), parser_with_objects_openapi(k, v, &objects))
+      })
+    }
+    _ => par_map(namespaces, |(k, v)| (k.clone(), parser(k, v))),
+  };
+  for (k, v) in modules {
+    schema.modules.insert(k, v);
+  }
+  // the refs of protobuf may be told only with the declarations of all packages
+  if matches!(idl_protocol_type, IdlProtocolType::Protobuf) {
+    resolve_refs_protobuf(&mut schema);
   }
 
-  idl_metas_map
+  schema
 }
@@ -0,0 +1,439 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde_json::{json, Value};
+
+use ridl_ir::schema::{
+  Alias, Definition, Enum, EnumValue, EnumVariant, Field, Module, Record, Union,
+};
+use ridl_ir::types::Type;
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType, NamespaceIdlBlocksMap};
+
+/// Whether the schema allows `null`, by `nullable` of openapi 3.0, the `null` type of openapi 3.1
+/// and json schema, or a `null` member of the unions.
+fn is_nullable(schema: &Value) -> bool {
+  let null = json!("null");
+  schema.get("nullable") == Some(&json!(true))
+    || schema["type"]
+      .as_array()
+      .is_some_and(|types| types.contains(&null))
+    || ["oneOf", "anyOf"].iter().any(|k| {
+      schema[k]
+        .as_array()
+        .is_some_and(|members| members.iter().any(|m| m["type"] == null))
+    })
+}
+
+/// The members of `oneOf` or `anyOf` except `null`, the searcher names the inline unions of many.
+pub fn union_members(schema: &Value) -> Vec<&Value> {
+  ["oneOf", "anyOf"]
+    .iter()
+    .filter_map(|k| schema[k].as_array())
+    .flatten()
+    .filter(|m| m["type"] != "null")
+    .collect()
+}
+
+/// The object referred by a `$ref` of the namespace, keyed by the qualified name, e.g.
+/// `common.Error`. The searcher qualifies the refs to other documents only.
+fn object_key(r#ref: &str, namespace: &str) -> String {
+  match r#ref.contains('.') {
+    true => r#ref.to_string(),
+    false => format!("{}.{}", namespace, r#ref),
+  }
+}
+
+/// Lower a schema into the type, the inline objects, enums and unions are already replaced by
+/// the references to their names when the blocks are searched.
+fn lower_type(schema: &Value) -> Type {
+  if let Some(r#ref) = schema["$ref"].as_str() {
+    return match r#ref.split_once('.') {
+      Some((namespace, name)) => Type::refer(name, vec![namespace.to_string()]),
+      None => Type::local(r#ref),
+    };
+  }
+  // `allOf` of a single schema is used to add siblings to a `$ref` in openapi 3.0
+  if let Some([member]) = schema["allOf"].as_array().map(|a| a.as_slice()) {
+    return lower_type(member);
+  }
+  if let [member] = union_members(schema)[..] {
+    return lower_type(member);
+  }
+  let r#type = match &schema["type"] {
+    Value::String(t) => t.as_str(),
+    Value::Array(types) => types
+      .iter()
+      .filter_map(|t| t.as_str())
+      .find(|t| *t != "null")
+      .unwrap_or(""),
+    _ => "",
+  };
+  let format = schema["format"].as_str().unwrap_or("");
+  match (r#type, format) {
+    ("string", "date-time") => Type::Timestamp,
+    ("string", "date") => Type::Date,
+    ("string", "time") => Type::Time,
+    ("string", "duration") => Type::Duration,
+    ("string", "uuid") => Type::Uuid,
+    ("string", "byte") | ("string", "binary") => Type::Bytes,
+    ("string", _) => Type::String,
+    ("integer", "int64") => Type::Int64,
+    ("integer", _) => Type::Int32,
+    ("number", "float") => Type::Float32,
+    ("number", _) => Type::Float64,
+    ("boolean", _) => Type::Bool,
+    ("array", _) => Type::list(lower_type(&schema["items"])),
+    ("object", _) => Type::map(
+      Type::String,
+      match &schema["additionalProperties"] {
+        Value::Object(_) => lower_type(&schema["additionalProperties"]),
+        _ => Type::Any,
+      },
+    ),
+    _ => Type::Any,
+  }
+}
+
+/// Collect the properties and the required ones of an object of the namespace, the members of
+/// `allOf` are merged into it, including the referred objects of any document.
+fn collect_properties(
+  schema: &Value,
+  namespace: &str,
+  objects: &BTreeMap<String, Value>,
+  visited: &mut HashSet<String>,
+  properties: &mut Vec<(String, Value)>,
+  required: &mut HashSet<String>,
+) {
+  if let Some(r#ref) = schema["$ref"].as_str() {
+    let key = object_key(r#ref, namespace);
+    if let Some(object) = objects.get(&key) {
+      if visited.insert(key.clone()) {
+        let (namespace, _) = key.rsplit_once('.').unwrap();
+        collect_properties(object, namespace, objects, visited, properties, required);
+      }
+    }
+    return;
+  }
+  for member in schema["allOf"].as_array().into_iter().flatten() {
+    collect_properties(member, namespace, objects, visited, properties, required);
+  }
+  for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+    match properties.iter_mut().find(|(n, _)| n == name) {
+      Some(p) => p.1 = property.clone(),
+      None => properties.push((name.clone(), property.clone())),
+    }
+  }
+  for name in schema["required"].as_array().into_iter().flatten() {
+    if let Some(name) = name.as_str() {
+      required.insert(name.to_string());
+    }
+  }
+}
+
+/// The properties are optional if they are not required or they are nullable.
+fn parse_object(schema: &Value, namespace: &str, objects: &BTreeMap<String, Value>) -> Vec<Field> {
+  let mut properties = Vec::new();
+  let mut required = HashSet::new();
+  collect_properties(
+    schema,
+    namespace,
+    objects,
+    &mut HashSet::new(),
+    &mut properties,
+    &mut required,
+  );
+  properties
+    .into_iter()
+    .map(|(name, property)| {
+      let optional = !required.contains(&name) || is_nullable(&property);
+      let mut field = Field::new(&name, lower_type(&property), optional);
+      field.doc = description_of(&property);
+      field
+    })
+    .collect()
+}
+
+/// The names of the variants are the values turned into identifiers, e.g. `in-progress` is named
+/// `in_progress` and `1` is `_1`.
+fn parse_enum(schema: &Value) -> Vec<EnumVariant> {
+  let values = schema["enum"].as_array().cloned().unwrap_or_default();
+  values
+    .iter()
+    .filter_map(|v| {
+      let value = match v {
+        Value::String(s) => EnumValue::String(s.clone()),
+        Value::Number(n) => EnumValue::Int(n.as_i64()?),
+        _ => return None,
+      };
+      let raw = v.as_str().map(|s| s.to_string()).unwrap_or(v.to_string());
+      let mut name: String = raw
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+      if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+      }
+      Some(EnumVariant { name, value })
+    })
+    .collect()
+}
+
+/// The schemas of `oneOf` or `anyOf` are lowered into unions, and the others into aliases.
+fn parse_alias(schema: &Value, name: String) -> Definition {
+  let members = union_members(schema);
+  match members.len() {
+    0 | 1 => Definition::Alias(Alias {
+      name,
+      r#type: lower_type(schema),
+      doc: description_of(schema),
+    }),
+    _ => Definition::Union(Union {
+      name,
+      variants: members.into_iter().map(lower_type).collect(),
+      doc: description_of(schema),
+    }),
+  }
+}
+
+fn description_of(schema: &Value) -> Option<String> {
+  schema["description"].as_str().map(|d| d.to_string())
+}
+
+fn schema_of(lines: &[String]) -> Value {
+  serde_json::from_str(lines.join("\n").as_str()).unwrap_or_default()
+}
+
+/// The objects of the namespace keyed by their qualified names, e.g. `common.Base`.
+fn objects_of<'a>(
+  namespace: &'a str,
+  blocks_map: &'a IdlBlocksMap,
+) -> impl Iterator<Item = (String, Value)> + 'a {
+  blocks_map
+    .get(&IdlType::Interface)
+    .into_iter()
+    .flatten()
+    .map(move |b| (object_key(&b.name, namespace), schema_of(&b.lines)))
+}
+
+/// The objects of all documents, which the `allOf` of a document may merge.
+pub fn collect_objects(namespace_blocks_map: &NamespaceIdlBlocksMap) -> BTreeMap<String, Value> {
+  namespace_blocks_map
+    .iter()
+    .flat_map(|(namespace, blocks_map)| objects_of(namespace, blocks_map))
+    .collect()
+}
+
+/// Parse the blocks of the schemas, the objects referred by `allOf` are looked up among the
+/// interfaces of the document.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let objects = objects_of(namespace, blocks_map).collect();
+  parser_with_objects(namespace, blocks_map, &objects)
+}
+
+/// Parse the blocks of the schemas, the objects referred by `allOf` are looked up among `objects`
+/// of [`collect_objects`].
+pub fn parser_with_objects(
+  namespace: &str,
+  blocks_map: &IdlBlocksMap,
+  objects: &BTreeMap<String, Value>,
+) -> Module {
+  let mut definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    let schema = schema_of(&b.lines);
+    let name = b.name.clone();
+    definitions.push(match k {
+      IdlType::Interface => Definition::Record(Record {
+        name,
+        fields: parse_object(&schema, namespace, objects),
+        doc: description_of(&schema),
+        ..Record::default()
+      }),
+      IdlType::Enum => Definition::Enum(Enum {
+        name,
+        variants: parse_enum(&schema),
+        doc: description_of(&schema),
+      }),
+      IdlType::Alias => parse_alias(&schema, name),
+      _ => continue,
+    });
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{collect_objects, parser, parser_with_objects};
+  use ridl_ir::schema::{Definition, EnumValue};
+  use ridl_ir::types::Type;
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn block(name: &str, line: &str) -> CodeBlock {
+    CodeBlock {
+      name: name.to_string(),
+      lines: vec![line.to_string()],
+      order: 0,
+    }
+  }
+
+  fn fields(definition: &Definition) -> Vec<(String, bool, String)> {
+    match definition {
+      Definition::Record(r) => r
+        .fields
+        .iter()
+        .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+        .collect(),
+      Definition::Enum(e) => e
+        .variants
+        .iter()
+        .map(|v| match &v.value {
+          EnumValue::Int(i) => (v.name.clone(), false, i.to_string()),
+          EnumValue::String(s) => (v.name.clone(), false, format!("{:?}", s)),
+        })
+        .collect(),
+      Definition::Union(u) => u
+        .variants
+        .iter()
+        .map(|t| (String::new(), false, t.to_string()))
+        .collect(),
+      Definition::Alias(a) => vec![(String::new(), false, a.r#type.to_string())],
+      _ => Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![
+        block(
+          "Base",
+          r#"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}"#,
+        ),
+        block(
+          "Course",
+          r#"{"allOf": [{"$ref": "Base"}, {"type": "object", "required": ["status", "plan"], "properties": {
+  "name": {"type": "string", "nullable": true},
+  "status": {"$ref": "Course_status"},
+  "plan": {"type": ["object", "null"], "additionalProperties": {"type": "number"}},
+  "tags": {"type": "array", "items": {"type": "string"}},
+  "lessons": {"type": "array", "items": {"$ref": "Lesson"}},
+  "teacher": {"allOf": [{"$ref": "Teacher"}], "description": "the teacher"},
+  "extra": {},
+  "created_at": {"type": "string", "format": "date-time"}
+}}]}"#,
+        ),
+      ],
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      vec![block(
+        "Course_status",
+        r#"{"type": "string", "enum": ["open", "in-progress", null]}"#,
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Alias,
+      vec![
+        block(
+          "Id",
+          r#"{"oneOf": [{"type": "string"}, {"type": "integer"}, {"type": "null"}]}"#,
+        ),
+        block("Ids", r#"{"type": "array", "items": {"$ref": "Id"}}"#),
+      ],
+    );
+    let module = parser("", &blocks_map);
+    let definitions = &module.definitions;
+
+    let answer = [
+      ("id", false, "i32"),
+      ("name", true, "string"),
+      ("status", false, "Course_status"),
+      ("plan", true, "map<string, f64>"),
+      ("tags", true, "list<string>"),
+      ("lessons", true, "list<Lesson>"),
+      ("teacher", true, "Teacher"),
+      ("extra", true, "any"),
+      ("created_at", true, "timestamp"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&definitions[1]), answer);
+
+    let answer = [
+      ("open", false, "\"open\""),
+      ("in_progress", false, "\"in-progress\""),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&definitions[2]), answer);
+
+    assert!(matches!(definitions[3], Definition::Union(_)));
+    let answer = [("", false, "string"), ("", false, "i32")]
+      .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&definitions[3]), answer);
+    assert!(matches!(definitions[4], Definition::Alias(_)));
+    assert_eq!(
+      fields(&definitions[4]),
+      [(String::new(), false, "list<Id>".to_string())]
+    );
+  }
+
+  #[test]
+  fn test_parser_documents() {
+    let mut a = IdlBlocksMap::new();
+    a.insert(
+      IdlType::Interface,
+      vec![
+        block(
+          "Error",
+          r#"{"type": "object", "properties": {"cause": {"$ref": "b.Error"}}}"#,
+        ),
+        block(
+          "Course",
+          r#"{"allOf": [{"$ref": "b.Base"}, {"type": "object", "properties": {"name": {"type": "string"}}}]}"#,
+        ),
+      ],
+    );
+    let mut b = IdlBlocksMap::new();
+    b.insert(
+      IdlType::Interface,
+      vec![
+        block(
+          "Error",
+          r#"{"type": "object", "properties": {"code": {"type": "integer"}}}"#,
+        ),
+        block(
+          "Base",
+          r#"{"allOf": [{"$ref": "Root"}, {"type": "object", "properties": {"id": {"type": "string"}}}]}"#,
+        ),
+        block(
+          "Root",
+          r#"{"type": "object", "properties": {"log_id": {"type": "string"}}}"#,
+        ),
+      ],
+    );
+    let namespace_blocks_map = [("a".to_string(), a), ("b".to_string(), b)].into();
+    let objects = collect_objects(&namespace_blocks_map);
+    let module = parser_with_objects("a", &namespace_blocks_map["a"], &objects);
+
+    let Definition::Record(error) = &module.definitions[0] else {
+      panic!("Error should be a record");
+    };
+    assert_eq!(
+      error.fields[0].r#type,
+      Type::refer("Error", vec!["b".to_string()])
+    );
+    // the objects merged by `allOf` are looked up in their own documents
+    let answer = [
+      ("log_id", true, "string"),
+      ("id", true, "string"),
+      ("name", true, "string"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields(&module.definitions[1]), answer);
+  }
+}
@@ -0,0 +1,82 @@
+use ridl_ir::types::Type;
+
+/// The type of a message field in protobuf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtoFieldType {
+  /// A scalar, well-known, message or enum type, e.g. `int64`, `google.protobuf.Timestamp` or
+  /// `foo.Bar`.
+  Named(String),
+  /// `map<key, value>`
+  Map(String, String),
+}
+
+/// Lower the scalar and well-known types, returns the type and whether it is nullable, which is
+/// true for the wrapper types.
+pub fn from_builtin_type(s: &str) -> Option<(Type, bool)> {
+  let well_known = s.trim_start_matches('.').strip_prefix("google.protobuf.");
+  let res = match well_known {
+    None => match s {
+      "int32" | "sint32" | "sfixed32" => (Type::Int32, false),
+      "uint32" | "fixed32" => (Type::UInt32, false),
+      "int64" | "sint64" | "sfixed64" => (Type::Int64, false),
+      "uint64" | "fixed64" => (Type::UInt64, false),
+      "float" => (Type::Float32, false),
+      "double" => (Type::Float64, false),
+      "string" => (Type::String, false),
+      "bytes" => (Type::Bytes, false),
+      "bool" => (Type::Bool, false),
+      _ => return None,
+    },
+    Some(name) => match name {
+      "Timestamp" => (Type::Timestamp, false),
+      "Duration" => (Type::Duration, false),
+      // the paths joined by commas
+      "FieldMask" => (Type::String, false),
+      "DoubleValue" => (Type::Float64, true),
+      "FloatValue" => (Type::Float32, true),
+      "Int32Value" => (Type::Int32, true),
+      "UInt32Value" => (Type::UInt32, true),
+      "Int64Value" => (Type::Int64, true),
+      "UInt64Value" => (Type::UInt64, true),
+      "StringValue" => (Type::String, true),
+      "BytesValue" => (Type::Bytes, true),
+      "BoolValue" => (Type::Bool, true),
+      "Struct" => (Type::map(Type::String, Type::Any), false),
+      // `Any`, `Value`, `ListValue`, `Empty` and others
+      _ => (Type::Any, false),
+    },
+  };
+  Some(res)
+}
+
+#[cfg(test)]
+mod test {
+  use super::from_builtin_type;
+  use ridl_ir::types::Type;
+
+  #[test]
+  fn test_builtin_type() {
+    let to_str = |s: &str| from_builtin_type(s).map(|(t, nullable)| (t.to_string(), nullable));
+    assert_eq!(to_str("int32"), Some(("i32".to_string(), false)));
+    assert_eq!(to_str("sint64"), Some(("i64".to_string(), false)));
+    assert_eq!(to_str("fixed32"), Some(("u32".to_string(), false)));
+    assert_eq!(to_str("bytes"), Some(("bytes".to_string(), false)));
+    assert_eq!(
+      to_str(".google.protobuf.Timestamp"),
+      Some(("timestamp".to_string(), false))
+    );
+    assert_eq!(
+      to_str("google.protobuf.Int64Value"),
+      Some(("i64".to_string(), true))
+    );
+    assert_eq!(
+      to_str("google.protobuf.Struct"),
+      Some(("map<string, any>".to_string(), false))
+    );
+    assert_eq!(
+      from_builtin_type("google.protobuf.Any"),
+      Some((Type::Any, false))
+    );
+    assert_eq!(to_str("foo.Bar"), None);
+  }
+}
@@ -0,0 +1,4 @@
+pub mod field_type;
+
+pub mod parser;
+pub mod resolver;
@@ -0,0 +1,593 @@
+use std::collections::HashSet;
+
+use super::field_type::{from_builtin_type, ProtoFieldType};
+use ridl_ir::schema::{Definition, Enum, EnumValue, EnumVariant, Field, Module, Record};
+use ridl_ir::types::Type;
+use ridl_utils::lexer::{tokenize, Token, Tokens};
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
+
+#[derive(Debug, Clone)]
+struct ProtoField {
+  /// `optional`, `required` or `repeated`
+  label: Option<String>,
+  r#type: ProtoFieldType,
+  name: String,
+  number: Option<i32>,
+  /// The name of the field in json, e.g. `teacherId` of `teacher_id`.
+  json_name: String,
+  in_oneof: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ProtoEnum {
+  name: String,
+  values: Vec<(String, i64)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ProtoMessage {
+  name: String,
+  fields: Vec<ProtoField>,
+  messages: Vec<ProtoMessage>,
+  enums: Vec<ProtoEnum>,
+}
+
+/// A top level message or enum of the package.
+enum ProtoDefinition {
+  Message(ProtoMessage),
+  Enum(ProtoEnum),
+}
+
+fn parse_enum_body(tokens: &mut Tokens, name: String) -> ProtoEnum {
+  let mut values = Vec::new();
+  while !tokens.is_end() && !tokens.eat_symbol('}') {
+    if tokens.eat_ident("option") || tokens.eat_ident("reserved") {
+      tokens.skip_statement();
+      continue;
+    }
+    match (tokens.ident(), tokens.eat_symbol('=')) {
+      (Some(key), true) => {
+        let negative = tokens.eat_symbol('-');
+        let value = match tokens.next_token() {
+          Some(Token::Number(n)) => parse_int(&n).map(|v| if negative { -v } else { v }),
+          _ => None,
+        };
+        if let Some(value) = value {
+          values.push((key, value));
+        }
+        tokens.skip_statement();
+      }
+      _ => tokens.skip_statement(),
+    }
+  }
+  ProtoEnum { name, values }
+}
+
+fn parse_int(n: &str) -> Option<i64> {
+  match n.strip_prefix("0x").or(n.strip_prefix("0X")) {
+    Some(hex) => i64::from_str_radix(hex, 16).ok(),
+    None if n.len() > 1 && n.starts_with('0') => i64::from_str_radix(&n[1..], 8).ok(),
+    None => n.parse().ok(),
+  }
+}
+
+/// The default json name of a field by proto3, which is the name in lower camel case, e.g.
+/// `teacherId` of `teacher_id`.
+fn default_json_name(name: &str) -> String {
+  let mut json_name = String::new();
+  let mut upper = false;
+  for c in name.chars() {
+    match c {
+      '_' => upper = true,
+      c if upper => {
+        json_name.push(c.to_ascii_uppercase());
+        upper = false;
+      }
+      c => json_name.push(c),
+    }
+  }
+  json_name
+}
+
+/// Parse the options of a field for its `json_name`, e.g. `[json_name = "id", deprecated = true]`,
+/// the other options are skipped.
+fn parse_json_name(tokens: &mut Tokens) -> Option<String> {
+  let mut json_name = None;
+  while !tokens.is_end() && !tokens.eat_symbol(']') {
+    if tokens.eat_ident("json_name") && tokens.eat_symbol('=') {
+      if let Some(Token::Str(s)) = tokens.next_token() {
+        json_name = Some(s);
+      }
+      continue;
+    }
+    match tokens.next_token() {
+      Some(Token::Symbol('{')) => tokens.skip_until('}'),
+      Some(Token::Symbol('[')) => tokens.skip_until(']'),
+      Some(Token::Symbol('(')) => tokens.skip_until(')'),
+      _ => {}
+    }
+  }
+  json_name
+}
+
+/// Parse a field statement, e.g. `repeated foo.Bar bars = 1 [deprecated = true];`.
+fn parse_field(tokens: &mut Tokens, in_oneof: bool) -> Option<ProtoField> {
+  let label = ["optional", "required", "repeated"]
+    .into_iter()
+    .find(|l| tokens.eat_ident(l))
+    .map(|l| l.to_string());
+  let r#type = if tokens.peek().is_some_and(|t| t.is_ident("map"))
+    && tokens.peek_nth(1).is_some_and(|t| t.is_symbol('<'))
+  {
+    tokens.next_token();
+    tokens.next_token();
+    let key = tokens.dotted_ident()?;
+    tokens.eat_symbol(',');
+    let value = tokens.dotted_ident()?;
+    tokens.eat_symbol('>');
+    ProtoFieldType::Map(key, value)
+  } else {
+    ProtoFieldType::Named(tokens.dotted_ident()?)
+  };
+  let name = tokens.ident()?;
+  if !tokens.eat_symbol('=') {
+    return None;
+  }
+  let number = match tokens.next_token() {
+    Some(Token::Number(n)) => parse_int(&n).and_then(|n| i32::try_from(n).ok()),
+    _ => None,
+  };
+  let json_name = match tokens.eat_symbol('[') {
+    true => parse_json_name(tokens),
+    false => None,
+  };
+  tokens.skip_statement();
+  Some(ProtoField {
+    label,
+    r#type,
+    json_name: json_name.unwrap_or_else(|| default_json_name(&name)),
+    name,
+    number,
+    in_oneof,
+  })
+}
+
+fn parse_message_body(tokens: &mut Tokens, name: String) -> ProtoMessage {
+  let mut message = ProtoMessage {
+    name,
+    ..ProtoMessage::default()
+  };
+  let mut in_oneof = false;
+  while !tokens.is_end() {
+    if tokens.eat_symbol('}') {
+      if in_oneof {
+        in_oneof = false;
+        continue;
+      }
+      break;
+    }
+    if tokens.eat_symbol(';') {
+      continue;
+    }
+    let is_definition = matches!(tokens.peek_nth(1), Some(Token::Ident(_)))
+      && tokens.peek_nth(2).is_some_and(|t| t.is_symbol('{'));
+    if is_definition && tokens.eat_ident("message") {
+      let name = tokens.ident().unwrap();
+      tokens.eat_symbol('{');
+      message.messages.push(parse_message_body(tokens, name));
+    } else if is_definition && tokens.eat_ident("enum") {
+      let name = tokens.ident().unwrap();
+      tokens.eat_symbol('{');
+      message.enums.push(parse_enum_body(tokens, name));
+    } else if is_definition && tokens.eat_ident("oneof") {
+      tokens.next_token();
+      tokens.eat_symbol('{');
+      in_oneof = true;
+    } else if ["option", "reserved", "extensions", "extend"]
+      .iter()
+      .any(|k| tokens.peek().is_some_and(|t| t.is_ident(k)))
+    {
+      tokens.skip_statement();
+    } else if let Some(field) = parse_field(tokens, in_oneof) {
+      message.fields.push(field);
+    } else {
+      tokens.skip_statement();
+    }
+  }
+  message
+}
+
+/// Parse a top level block, e.g. `message Foo { ... }` or `enum Bar { ... }`.
+fn parse_block(lines: &[String]) -> Option<(String, Tokens)> {
+  let mut tokens = Tokens::new(tokenize(lines.join("\n").as_str(), &["//"]));
+  tokens.next_token();
+  let name = tokens.ident()?;
+  tokens.eat_symbol('{');
+  Some((name, tokens))
+}
+
+/// The messages and enums declared in a namespace, which are keyed by their dot separated path
+/// relative to the package, e.g. `Outer.Inner`.
+struct Declared {
+  names: HashSet<String>,
+  /// The paths of the messages among the names, the fields of messages have presence.
+  messages: HashSet<String>,
+}
+
+impl Declared {
+  fn collect(&mut self, message: &ProtoMessage, prefix: &str) {
+    let path = format!("{}{}", prefix, message.name);
+    for e in &message.enums {
+      self.names.insert(format!("{}.{}", path, e.name));
+    }
+    for m in &message.messages {
+      self.collect(m, format!("{}.", path).as_str());
+    }
+    self.messages.insert(path.clone());
+    self.names.insert(path);
+  }
+
+  /// Resolve the type name used inside the message of `scope` by the protobuf scoping rules,
+  /// starting from the innermost scope. Returns the flattened name, e.g. `Outer_Inner`, with
+  /// whether it is a message.
+  fn resolve(&self, name: &str, scope: &[String]) -> Option<(String, bool)> {
+    (0..=scope.len()).rev().find_map(|i| {
+      let mut path = scope[..i].to_vec();
+      path.push(name.to_string());
+      let path = path.join(".");
+      self
+        .names
+        .contains(&path)
+        .then(|| (path.replace('.', "_"), self.messages.contains(&path)))
+    })
+  }
+}
+
+/// Lower a field type, returns it with whether it is nullable, which the singular messages and
+/// wrappers are.
+fn lower_field_type(
+  r#type: &ProtoFieldType,
+  repeated: bool,
+  scope: &[String],
+  declared: &Declared,
+  namespace: &[String],
+) -> (Type, bool) {
+  match r#type {
+    ProtoFieldType::Map(key, value) => {
+      let (key, _) = lower_named_type(key, scope, declared, namespace);
+      let (value, _) = lower_named_type(value, scope, declared, namespace);
+      (Type::map(key, value), false)
+    }
+    ProtoFieldType::Named(name) => {
+      let (single, nullable) = lower_named_type(name, scope, declared, namespace);
+      match repeated {
+        true => (Type::list(single), false),
+        false => (single, nullable),
+      }
+    }
+  }
+}
+
+fn lower_named_type(
+  name: &str,
+  scope: &[String],
+  declared: &Declared,
+  namespace: &[String],
+) -> (Type, bool) {
+  if let Some(builtin) = from_builtin_type(name) {
+    return builtin;
+  }
+  // the qualified names of the same package are resolved as the relative ones
+  let package = format!("{}.", namespace.join("."));
+  let relative_name = name.trim_start_matches('.');
+  let relative_name = relative_name
+    .strip_prefix(package.as_str())
+    .unwrap_or(relative_name);
+  match declared.resolve(relative_name, scope) {
+    Some((refer, is_message)) => (Type::local(&refer), is_message),
+    // the types of other packages are resolved by `resolve_refs` once all packages are parsed
+    None => {
+      let mut parts: Vec<String> = name
+        .trim_start_matches('.')
+        .split('.')
+        .map(|p| p.to_string())
+        .collect();
+      let refer = parts.pop().unwrap_or_default();
+      (Type::refer(&refer, parts), false)
+    }
+  }
+}
+
+/// Flatten the message and its nested messages and enums into records and enums following it, the
+/// nested ones are named after the path joined by `_`, e.g. `Outer_Inner`.
+fn lower_message(
+  message: &ProtoMessage,
+  scope: &[String],
+  declared: &Declared,
+  namespace: &[String],
+  definitions: &mut Vec<Definition>,
+) {
+  let mut scope = scope.to_vec();
+  scope.push(message.name.clone());
+  let name = scope.join("_");
+
+  let fields = message
+    .fields
+    .iter()
+    .map(|f| {
+      let repeated = f.label.as_deref() == Some("repeated");
+      let (r#type, nullable) = lower_field_type(&f.r#type, repeated, &scope, declared, namespace);
+      let required = f.label.as_deref() == Some("required");
+      let optional =
+        !required && (nullable || f.in_oneof || f.label.as_deref() == Some("optional"));
+      let mut field = Field::new(&f.name, r#type, optional);
+      field.id = f.number;
+      field.required = required;
+      field
+        .annotations
+        .insert("json_name".to_string(), f.json_name.clone());
+      field
+    })
+    .collect();
+  definitions.push(Definition::Record(Record {
+    name,
+    fields,
+    ..Record::default()
+  }));
+
+  for e in &message.enums {
+    definitions.push(lower_enum(e, &scope));
+  }
+  for m in &message.messages {
+    lower_message(m, &scope, declared, namespace, definitions);
+  }
+}
+
+fn lower_enum(e: &ProtoEnum, scope: &[String]) -> Definition {
+  let mut path = scope.to_vec();
+  path.push(e.name.clone());
+  let variants = e
+    .values
+    .iter()
+    .map(|(key, value)| EnumVariant {
+      name: key.clone(),
+      value: EnumValue::Int(*value),
+    })
+    .collect();
+  Definition::Enum(Enum {
+    name: path.join("_"),
+    variants,
+    ..Enum::default()
+  })
+}
+
+/// Parse the blocks of a package. The namespace of the blocks is needed to tell the types of the
+/// package from the ones of other packages.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let package: Vec<String> = namespace
+    .split('.')
+    .filter(|p| !p.is_empty())
+    .map(|p| p.to_string())
+    .collect();
+  let mut proto_definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    let Some((name, mut tokens)) = parse_block(&b.lines) else {
+      continue;
+    };
+    match k {
+      IdlType::Interface => proto_definitions.push(ProtoDefinition::Message(parse_message_body(
+        &mut tokens,
+        name,
+      ))),
+      IdlType::Enum => {
+        proto_definitions.push(ProtoDefinition::Enum(parse_enum_body(&mut tokens, name)))
+      }
+      _ => {}
+    }
+  }
+
+  let mut declared = Declared {
+    names: HashSet::new(),
+    messages: HashSet::new(),
+  };
+  for d in &proto_definitions {
+    match d {
+      ProtoDefinition::Message(m) => declared.collect(m, ""),
+      ProtoDefinition::Enum(e) => {
+        declared.names.insert(e.name.clone());
+      }
+    }
+  }
+
+  let mut definitions = Vec::new();
+  for d in &proto_definitions {
+    match d {
+      ProtoDefinition::Message(m) => lower_message(m, &[], &declared, &package, &mut definitions),
+      ProtoDefinition::Enum(e) => definitions.push(lower_enum(e, &[])),
+    }
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue};
+  use ridl_ir::types::Type;
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn block(source: &str) -> CodeBlock {
+    CodeBlock {
+      name: String::new(),
+      lines: source.lines().map(|l| l.to_string()).collect(),
+      order: 0,
+    }
+  }
+
+  fn fields(definition: &Definition) -> Vec<(String, bool, String)> {
+    match definition {
+      Definition::Record(r) => r
+        .fields
+        .iter()
+        .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+        .collect(),
+      Definition::Enum(e) => e
+        .variants
+        .iter()
+        .map(|v| match &v.value {
+          EnumValue::Int(i) => (v.name.clone(), false, i.to_string()),
+          EnumValue::String(s) => (v.name.clone(), false, s.clone()),
+        })
+        .collect(),
+      _ => Vec::new(),
+    }
+  }
+
+  fn to_owned(fields: &[(&str, bool, &str)]) -> Vec<(String, bool, String)> {
+    fields
+      .iter()
+      .map(|(n, o, t)| (n.to_string(), *o, t.to_string()))
+      .collect()
+  }
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      [
+        block(
+          r#"message Lesson {
+  // comment
+  message Time { int64 start = 1; int64 end = 2; }
+  enum Status {
+    option allow_alias = true;
+    NOT_STARTED = 0;
+    IN_PROGRESS = 1 [deprecated = true];
+    FINISHED = 0x2;
+  }
+  int64 id = 1;
+  optional string name = 2;
+  repeated Time times = 3;
+  Status status = 4;
+  map<string, Time> time_map = 5;
+  oneof teacher {
+    int32 teacher_id = 6;
+    string teacher_name = 7;
+  }
+  google.protobuf.Timestamp created_at = 8;
+  google.protobuf.Int32Value capacity = 9;
+  .foo.bar.Grade grade = 10;
+  base.Base base = 11;
+  repeated double scores = 12;
+  Time time = 15;
+  required Time required_time = 0x10;
+  reserved 13, 14;
+}"#,
+        ),
+        block("message Empty {}"),
+      ]
+      .to_vec(),
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      [block(
+        "enum Grade {\n  GRADE_UNSPECIFIED = 0;\n  GRADE_ONE = -1;\n}",
+      )]
+      .to_vec(),
+    );
+    let module = parser("foo.bar", &blocks_map);
+
+    let names: Vec<&str> = module.definitions.iter().map(|d| d.name()).collect();
+    assert_eq!(
+      names,
+      ["Lesson", "Lesson_Status", "Lesson_Time", "Empty", "Grade"]
+    );
+    let definitions = &module.definitions;
+    assert_eq!(
+      fields(&definitions[0]),
+      to_owned(&[
+        ("id", false, "i64"),
+        ("name", true, "string"),
+        ("times", false, "list<Lesson_Time>"),
+        ("status", false, "Lesson_Status"),
+        ("time_map", false, "map<string, Lesson_Time>"),
+        ("teacher_id", true, "i32"),
+        ("teacher_name", true, "string"),
+        ("created_at", false, "timestamp"),
+        ("capacity", true, "i32"),
+        ("grade", false, "Grade"),
+        ("base", false, "base.Base"),
+        ("scores", false, "list<f64>"),
+        ("time", true, "Lesson_Time"),
+        ("required_time", false, "Lesson_Time"),
+      ])
+    );
+    assert_eq!(
+      fields(&definitions[2]),
+      to_owned(&[("start", false, "i64"), ("end", false, "i64")])
+    );
+    assert_eq!(fields(&definitions[3]).len(), 0);
+    let Definition::Record(lesson) = &definitions[0] else {
+      panic!("Lesson should be a record");
+    };
+    let ids: Vec<i32> = lesson.fields.iter().filter_map(|f| f.id).collect();
+    assert_eq!(ids, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 15, 16]);
+    assert!(lesson
+      .fields
+      .iter()
+      .all(|f| f.required == (f.name == "required_time")));
+    assert_eq!(
+      lesson.fields[10].r#type,
+      Type::refer("Base", vec!["base".to_string()])
+    );
+
+    assert_eq!(
+      fields(&definitions[1]),
+      to_owned(&[
+        ("NOT_STARTED", false, "0"),
+        ("IN_PROGRESS", false, "1"),
+        ("FINISHED", false, "2"),
+      ])
+    );
+    assert_eq!(
+      fields(&definitions[4]),
+      to_owned(&[
+        ("GRADE_UNSPECIFIED", false, "0"),
+        ("GRADE_ONE", false, "-1")
+      ])
+    );
+  }
+
+  #[test]
+  fn test_parse_json_names() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![block(
+        r#"message Course {
+  int64 course_id = 1;
+  string name = 2 [deprecated = true, json_name = "title"];
+  repeated string tag_ids_v2 = 3 [(custom.option) = { a: 1 }];
+}"#,
+      )],
+    );
+    let module = parser("foo", &blocks_map);
+    let Definition::Record(course) = &module.definitions[0] else {
+      panic!("Course should be a record");
+    };
+    let json_names: Vec<&str> = course
+      .fields
+      .iter()
+      .map(|f| f.annotations["json_name"].as_str())
+      .collect();
+    // the json names are in lower camel case unless they are given
+    assert_eq!(json_names, ["courseId", "title", "tagIdsV2"]);
+  }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use ridl_ir::schema::{Definition, Schema};
+use ridl_ir::types::{Type, TypeRef};
+
+/// The types declared in all packages, keyed by the package and the flattened name, e.g.
+/// `("pkg", "Outer_Inner")`, with whether they are messages.
+struct Packages {
+  declared: HashMap<(String, String), bool>,
+}
+
+impl Packages {
+  /// Resolve a name of another package by the protobuf scoping rules, it is tried relatively to
+  /// the package referring it and then to its parents, e.g. `baz.Msg` of `foo.bar` is
+  /// `foo.bar.baz.Msg`, `foo.baz.Msg` or `baz.Msg`. The longest package comes first when the name
+  /// is split into a package and a nested path. Returns the package, the flattened name and whether
+  /// it is a message.
+  fn resolve(&self, r: &TypeRef, package: &[String]) -> Option<(Vec<String>, String, bool)> {
+    let mut name = r.namespace.clone();
+    name.push(r.name.clone());
+    (0..=package.len()).rev().find_map(|i| {
+      let path = [&package[..i], name.as_slice()].concat();
+      (1..path.len()).rev().find_map(|k| {
+        let key = (path[..k].join("."), path[k..].join("_"));
+        self
+          .declared
+          .get(&key)
+          .map(|is_message| (path[..k].to_vec(), key.1, *is_message))
+      })
+    })
+  }
+
+  /// Resolve the refs of the type, returns it with whether it is a singular message.
+  fn resolve_type(&self, r#type: &Type, package: &[String]) -> (Type, bool) {
+    match r#type {
+      Type::List(item) => (Type::list(self.resolve_type(item, package).0), false),
+      Type::Map(key, value) => (
+        Type::map(
+          self.resolve_type(key, package).0,
+          self.resolve_type(value, package).0,
+        ),
+        false,
+      ),
+      Type::Ref(r) if !r.namespace.is_empty() => match self.resolve(r, package) {
+        Some((namespace, name, is_message)) if namespace == package => {
+          (Type::local(&name), is_message)
+        }
+        Some((namespace, name, is_message)) => (Type::refer(&name, namespace), is_message),
+        None => (r#type.clone(), false),
+      },
+      _ => (r#type.clone(), false),
+    }
+  }
+}
+
+/// Resolve the refs to the types of other packages once all packages are parsed, since a package
+/// alone can't tell the nested types of another one from its subpackages, e.g. `pkg.Outer.Inner` is
+/// `Outer_Inner` of `pkg`. The singular fields of the messages found are optional, as the local
+/// ones are.
+pub fn resolve_refs(schema: &mut Schema) {
+  let declared = schema
+    .modules
+    .values()
+    .flat_map(|m| {
+      m.definitions.iter().map(|d| {
+        let key = (m.namespace.clone(), d.name().to_string());
+        (key, matches!(d, Definition::Record(_)))
+      })
+    })
+    .collect();
+  let packages = Packages { declared };
+
+  for module in schema.modules.values_mut() {
+    let package: Vec<String> = module
+      .namespace
+      .split('.')
+      .filter(|p| !p.is_empty())
+      .map(|p| p.to_string())
+      .collect();
+    for d in &mut module.definitions {
+      let Definition::Record(record) = d else {
+        continue;
+      };
+      for f in &mut record.fields {
+        let (r#type, is_message) = packages.resolve_type(&f.r#type, &package);
+        f.r#type = r#type;
+        f.optional = f.optional || (is_message && !f.required);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::resolve_refs;
+  use crate::protobuf::parser::parser;
+  use ridl_ir::schema::{Definition, Schema};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn blocks_map(messages: &[&str], enums: &[&str]) -> IdlBlocksMap {
+    let to_blocks = |sources: &[&str]| -> Vec<CodeBlock> {
+      sources
+        .iter()
+        .map(|source| CodeBlock {
+          name: String::new(),
+          lines: source.lines().map(|l| l.to_string()).collect(),
+          order: 0,
+        })
+        .collect()
+    };
+    let mut map = IdlBlocksMap::new();
+    map.insert(IdlType::Interface, to_blocks(messages));
+    map.insert(IdlType::Enum, to_blocks(enums));
+    map
+  }
+
+  #[test]
+  fn test_resolve_refs() {
+    let mut schema = Schema::default();
+    for (namespace, blocks_map) in [
+      (
+        "pkg",
+        blocks_map(&["message Outer { message Inner { int64 id = 1; } }"], &[]),
+      ),
+      (
+        "foo.baz",
+        blocks_map(
+          &["message Msg { int64 id = 1; }"],
+          &["enum Kind { A = 0; }"],
+        ),
+      ),
+      (
+        "foo.bar",
+        blocks_map(
+          &[r#"message Course {
+  pkg.Outer.Inner inner = 1;
+  baz.Msg msg = 2;
+  baz.Kind kind = 3;
+  bar.Course next = 4;
+  repeated pkg.Outer outers = 5;
+  map<string, baz.Msg> msgs = 6;
+  required foo.baz.Msg legacy = 7;
+  other.Missing missing = 8;
+}"#],
+          &[],
+        ),
+      ),
+    ] {
+      schema
+        .modules
+        .insert(namespace.to_string(), parser(namespace, &blocks_map));
+    }
+    resolve_refs(&mut schema);
+
+    let Definition::Record(course) = &schema.modules["foo.bar"].definitions[0] else {
+      panic!("Course should be a record");
+    };
+    let fields: Vec<(&str, bool, String)> = course
+      .fields
+      .iter()
+      .map(|f| (f.name.as_str(), f.optional, f.r#type.to_string()))
+      .collect();
+    assert_eq!(
+      fields,
+      [
+        ("inner", true, "pkg.Outer_Inner".to_string()),
+        ("msg", true, "foo.baz.Msg".to_string()),
+        ("kind", false, "foo.baz.Kind".to_string()),
+        ("next", true, "Course".to_string()),
+        ("outers", false, "list<pkg.Outer>".to_string()),
+        ("msgs", false, "map<string, foo.baz.Msg>".to_string()),
+        ("legacy", false, "foo.baz.Msg".to_string()),
+        ("missing", false, "other.Missing".to_string()),
+      ]
+    );
+  }
+}
@@ -1,6 +0,0 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Comment {
-  Line,
-  Block,
-  Tail,
-}
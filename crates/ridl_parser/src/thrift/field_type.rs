@@ -0,0 +1,124 @@
+use ridl_ir::types::Type;
+
+/// Split the parameters of a container type at the top level commas, e.g. `string, list<i32>` of
+/// `map<string, list<i32>>`.
+fn split_type_params(s: &str) -> Vec<&str> {
+  let mut params = Vec::new();
+  let mut depth = 0;
+  let mut beg = 0;
+  for (i, c) in s.char_indices() {
+    match c {
+      '<' => depth += 1,
+      '>' => depth -= 1,
+      ',' if depth == 0 => {
+        params.push(s[beg..i].trim());
+        beg = i + 1;
+      }
+      _ => {}
+    }
+  }
+  params.push(s[beg..].trim());
+  params
+}
+
+/// Parse the type of a thrift field, e.g. `i64`, `list<foo.bar.ReferObj>` or
+/// `map<string, list<i32>>`. The names with dots refer to the types of the included files.
+pub fn parse_field_type(s: &str) -> Type {
+  let s = s.trim();
+  if let Some((container, params)) = s.strip_suffix('>').and_then(|s| s.split_once('<')) {
+    let params = split_type_params(params);
+    return match (container.trim(), params.as_slice()) {
+      ("list", [item]) => Type::list(parse_field_type(item)),
+      ("set", [item]) => Type::set(parse_field_type(item)),
+      ("map", [key, value]) => Type::map(parse_field_type(key), parse_field_type(value)),
+      _ => Type::Any,
+    };
+  }
+  match s {
+    "bool" => Type::Bool,
+    "byte" | "i8" => Type::Int8,
+    "i16" => Type::Int16,
+    "i32" => Type::Int32,
+    "i64" => Type::Int64,
+    "double" => Type::Float64,
+    "string" => Type::String,
+    "binary" => Type::Bytes,
+    "void" => Type::Void,
+    _ if !s.is_empty()
+      && s
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '.') =>
+    {
+      let mut parts: Vec<String> = s.split('.').map(|p| p.to_string()).collect();
+      let name = parts.pop().unwrap();
+      Type::refer(&name, parts)
+    }
+    _ => Type::Any,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parse_field_type;
+  use ridl_ir::types::{Type, TypeRef};
+
+  #[test]
+  fn test_field_type_basic() {
+    let answer = [
+      ("bool", "bool"),
+      ("list<bool>", "list<bool>"),
+      ("i32", "i32"),
+      ("list<i32>", "list<i32>"),
+      ("i64", "i64"),
+      ("string", "string"),
+      ("list<i64>", "list<i64>"),
+      ("list<string>", "list<string>"),
+      ("double", "f64"),
+      ("binary", "bytes"),
+      ("set<list<i16>>", "set<list<i16>>"),
+    ];
+    for (text, r#type) in answer {
+      assert_eq!(parse_field_type(text).to_string(), r#type);
+    }
+  }
+
+  #[test]
+  fn test_field_type_refer() {
+    assert_eq!(parse_field_type("ReferObj"), Type::local("ReferObj"));
+    assert_eq!(
+      parse_field_type("list<ReferObj>"),
+      Type::list(Type::local("ReferObj"))
+    );
+    let refer = Type::Ref(TypeRef {
+      name: "ReferObj".to_string(),
+      namespace: vec!["foo".to_string(), "bar".to_string()],
+    });
+    assert_eq!(parse_field_type("foo.bar.ReferObj"), refer);
+    assert_eq!(
+      parse_field_type("list<foo.bar.ReferObj>"),
+      Type::list(refer)
+    );
+  }
+
+  #[test]
+  fn test_field_type_map() {
+    let answer = [
+      ("map<string, string>", "map<string, string>"),
+      (
+        "map<foo.bar.ReferObj, string>",
+        "map<foo.bar.ReferObj, string>",
+      ),
+      (
+        "map<string, foo.bar.ReferObj>",
+        "map<string, foo.bar.ReferObj>",
+      ),
+      (
+        "map<string,map<i32, list<i64>>>",
+        "map<string, map<i32, list<i64>>>",
+      ),
+    ];
+    for (text, r#type) in answer {
+      assert_eq!(parse_field_type(text).to_string(), r#type);
+    }
+  }
+}
@@ -1,4 +1,3 @@
-pub mod field_comment;
-pub mod field_type_interface;
+pub mod field_type;
 
 pub mod parser;
@@ -1,9 +1,11 @@
-use std::collections::HashMap;
-
-use super::field_comment::Comment;
-use super::field_type_interface::InterfaceFieldType;
+use super::field_type::parse_field_type;
 use regex::Regex;
-use ridl_utils::types::{IdlBlocksMap, IdlType};
+use ridl_ir::schema::{
+  Alias, Annotations, Definition, Enum, EnumValue, EnumVariant, Field, Method, Module, Record,
+  Service,
+};
+use ridl_ir::types::Type;
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
 
 /// The regex to find the field of idl
 struct IdlFieldRe {
@@ -14,101 +16,207 @@ impl From<IdlType> for IdlFieldRe {
   fn from(idl_type: IdlType) -> Self {
     IdlFieldRe {
       re: match idl_type {
-        IdlType::Enum => Regex::new(r"\s*([A-Z][A-Za-z0-9]*) = (\d+)").unwrap(),
+        IdlType::Enum => Regex::new(r"\s*([A-Z][A-Za-z0-9]*) = (-?\d+)").unwrap(),
         IdlType::Interface => Regex::new(
-          r"\s*\d+:\s+(?:(optional|required)\s+)?((?:\w|\.)+|(?:map|list)<.+>)\s+(\w+)(?:\s|\(|,|;|$)",
+          r"\s*(-?\d+):\s*(?:(optional|required)\s+)?((?:\w|\.)+|(?:map|list|set)<.+>)\s+(\w+)(?:\s|\(|,|;|$)",
+        )
+        .unwrap(),
+        IdlType::Alias => {
+          Regex::new(r"^\s*typedef\s+((?:\w|\.)+|(?:map|list|set)<.+>)\s+\w+").unwrap()
+        }
+        IdlType::Service => Regex::new(
+          r"(?:oneway\s+)?((?:\w|\.)+|(?:map|list|set)<[^()]*>)\s+(\w+)\s*\(([^)]*)\)(?:\s*throws\s*\(([^)]*)\))?(?:\s*\(([^)]*)\))?",
         )
         .unwrap(),
+        IdlType::Constant => {
+          unreachable!("the fields of {} are not searched", idl_type.as_str())
+        }
       },
     }
   }
 }
 
-#[derive(Debug, Clone)]
-pub struct InterfaceField {
-  pub name: String,
-  pub optional: bool,
-  pub r#type: InterfaceFieldType,
-  pub comment: Option<Comment>,
+/// Parse the annotations in the first parentheses, e.g. `(api.header = 'X-Id', go.tag = "")`.
+fn parse_annotations(s: &str) -> Annotations {
+  let re = Regex::new(r#"([\w\.]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+  let Some(start) = s.find('(') else {
+    return Annotations::new();
+  };
+  let end = s.rfind(')').filter(|end| *end > start).unwrap_or(s.len());
+  re.captures_iter(&s[start..end])
+    .map(|c| {
+      let value = c.get(2).or(c.get(3)).map_or("", |m| m.as_str());
+      (c[1].to_string(), value.to_string())
+    })
+    .collect()
+}
+
+fn parse_field(re: &Regex, line: &str) -> Option<Field> {
+  re.captures(line).map(|captures| {
+    let requiredness = captures.get(2).map_or("", |m| m.as_str());
+    let name = captures.get(4).unwrap();
+    let mut field = Field::new(
+      name.as_str(),
+      parse_field_type(captures.get(3).unwrap().as_str()),
+      requiredness == "optional",
+    );
+    field.id = captures.get(1).and_then(|m| m.as_str().parse().ok());
+    field.required = requiredness == "required";
+    field.annotations = parse_annotations(&line[name.end()..]);
+    field
+  })
 }
 
-#[derive(Debug, Clone)]
-pub struct EnumField {
-  pub key: String,
-  pub value: String,
+// Iterator lines and use regex to parse field meta from each line.
+fn parse_fields_from_lines(lines: &[String]) -> Vec<Field> {
+  let re = IdlFieldRe::from(IdlType::Interface).re;
+  lines
+    .iter()
+    .filter_map(|line| parse_field(&re, line))
+    .collect()
 }
 
-#[derive(Debug, Clone)]
-pub enum IdlMetaField {
-  Interface(InterfaceField),
-  Enum(EnumField),
+/// Parse the fields separated by commas, e.g. the params `1: i64 id, 2: map<i32, i32> m`.
+fn parse_fields_from_list(list: &str) -> Vec<Field> {
+  let re = IdlFieldRe::from(IdlType::Interface).re;
+  let mut fields = Vec::new();
+  let (mut depth, mut start) = (0, 0);
+  for (i, c) in list.char_indices().chain([(list.len(), ',')]) {
+    match c {
+      '<' => depth += 1,
+      '>' => depth -= 1,
+      ',' if depth == 0 => {
+        fields.extend(parse_field(&re, &list[start..i]));
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  fields
 }
 
-#[derive(Debug, Clone)]
-pub struct IdlMeta {
-  pub name: String,
-  pub fields: Vec<IdlMetaField>,
+/// Cut the `//` or `#` comment at the end of the line, the ones in quotes are kept.
+fn strip_line_comment(line: &str) -> &str {
+  let mut quote = None;
+  let bytes = line.as_bytes();
+  for (i, c) in line.char_indices() {
+    match (c, quote) {
+      ('"' | '\'', None) => quote = Some(c),
+      (c, Some(q)) if c == q => quote = None,
+      ('#', None) => return &line[..i],
+      ('/', None) if bytes.get(i + 1) == Some(&b'/') => return &line[..i],
+      _ => {}
+    }
+  }
+  line
 }
 
-pub type IdlMetaMap = HashMap<IdlType, Vec<IdlMeta>>;
-pub type NamespaceIdlMetaMap = HashMap<String, IdlMetaMap>;
+/// Parse the methods of a service, a method may span lines, e.g.
+/// `GetResp Get(1: GetReq req) throws (1: Error err) (api.get = "/get")`.
+fn parse_methods_from_lines(lines: &[String]) -> Vec<Method> {
+  let re = IdlFieldRe::from(IdlType::Service).re;
+  let body: Vec<&str> = lines.iter().map(|l| strip_line_comment(l)).collect();
+  let body = body.join("\n");
+  let start = body.find('{').map_or(0, |i| i + 1);
+  let end = body
+    .rfind('}')
+    .filter(|end| *end >= start)
+    .unwrap_or(body.len());
+  re.captures_iter(&body[start..end])
+    .map(|c| Method {
+      name: c[2].to_string(),
+      params: parse_fields_from_list(&c[3]),
+      result: parse_field_type(&c[1]),
+      throws: c
+        .get(4)
+        .map_or(Vec::new(), |m| parse_fields_from_list(m.as_str())),
+      doc: None,
+      annotations: c.get(5).map_or(Annotations::new(), |m| {
+        parse_annotations(&format!("({})", m.as_str()))
+      }),
+    })
+    .collect()
+}
 
-// Iterator lines and use regex to parse field meta from each line.
-fn parse_field_from_line(itype: IdlType, lines: &Vec<String>) -> Vec<IdlMetaField> {
-  let re = IdlFieldRe::from(itype.clone()).re;
+fn parse_variants_from_lines(lines: &[String]) -> Vec<EnumVariant> {
+  let re = IdlFieldRe::from(IdlType::Enum).re;
   lines
     .iter()
-    .map(|line| {
-      let field = match itype {
-        IdlType::Enum => match re.captures(line.as_str()) {
-          Some(captures) => Some(IdlMetaField::Enum(EnumField {
-            key: captures.get(1).map_or("", |m| m.as_str()).to_string(),
-            value: captures.get(2).map_or("", |m| m.as_str()).to_string(),
-          })),
-          None => None,
-        },
-        IdlType::Interface => match re.captures(line.as_str()) {
-          Some(captures) => Some(IdlMetaField::Interface(InterfaceField {
-            name: captures.get(3).map_or("", |m| m.as_str()).to_string(),
-            r#type: InterfaceFieldType::from_str(captures.get(2).unwrap().as_str()),
-            optional: captures.get(1).map_or("", |m| m.as_str()).eq("optional"),
-            comment: None,
-          })),
-          None => None,
-        },
-      };
-      field
+    .filter_map(|line| {
+      re.captures(line.as_str()).map(|captures| EnumVariant {
+        name: captures.get(1).map_or("", |m| m.as_str()).to_string(),
+        value: EnumValue::Int(captures.get(2).unwrap().as_str().parse().unwrap_or(0)),
+      })
     })
-    .filter(|f| f.is_some())
-    .map(|f| f.unwrap())
     .collect()
 }
 
-pub fn parser(blocks_map: &IdlBlocksMap) -> IdlMetaMap {
-  let mut res = IdlMetaMap::new();
-  for (k, v) in blocks_map {
-    let idl_metas = v
-      .iter()
-      .map(|b| {
-        let fields = parse_field_from_line(k.clone(), &b.lines);
-        IdlMeta {
-          name: b.name.clone(), // IdlMeta's name is equal to CodeBlock's name.
-          fields,
-        }
-      })
-      .collect();
-    res.insert(k.clone(), idl_metas);
+fn parse_alias_from_lines(lines: &[String]) -> Option<Type> {
+  let re = IdlFieldRe::from(IdlType::Alias).re;
+  lines.iter().find_map(|line| {
+    re.captures(line.as_str())
+      .map(|captures| parse_field_type(captures.get(1).unwrap().as_str()))
+  })
+}
+
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let mut definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    // the name of the definition is equal to CodeBlock's name.
+    let name = b.name.clone();
+    let definition = match k {
+      IdlType::Interface => Definition::Record(Record {
+        name,
+        fields: parse_fields_from_lines(&b.lines),
+        ..Record::default()
+      }),
+      IdlType::Enum => Definition::Enum(Enum {
+        name,
+        variants: parse_variants_from_lines(&b.lines),
+        ..Enum::default()
+      }),
+      IdlType::Service => Definition::Service(Service {
+        // the name may be followed by `extends`
+        name: name.split_whitespace().next().unwrap_or("").to_string(),
+        methods: parse_methods_from_lines(&b.lines),
+        ..Service::default()
+      }),
+      // the fields of unions are optional as only one of them is set
+      IdlType::Alias if b.lines[0].trim_start().starts_with("union") => {
+        Definition::Record(Record {
+          name,
+          fields: parse_fields_from_lines(&b.lines)
+            .into_iter()
+            .map(|f| Field {
+              optional: true,
+              ..f
+            })
+            .collect(),
+          ..Record::default()
+        })
+      }
+      IdlType::Alias => match parse_alias_from_lines(&b.lines) {
+        Some(r#type) => Definition::Alias(Alias {
+          name,
+          r#type,
+          doc: None,
+        }),
+        None => continue,
+      },
+      _ => continue,
+    };
+    definitions.push(definition);
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
   }
-  res
 }
 
+#[cfg(test)]
 mod test {
-  use crate::thrift::{
-    field_type_interface::{InterfaceFieldRefer, InterfaceFieldType},
-    parser::{EnumField, IdlMetaField},
-  };
-
   use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue};
   use ridl_utils::{
     code_block::CodeBlock,
     types::{IdlBlocksMap, IdlType},
@@ -128,12 +236,13 @@ mod test {
           "",
           "30: i64 org_id //orgid",
           "31: i64 job_key",
-          "32: i64 user_id",
+          "32: required i64 user_id",
           "255: optional base.Base Base",
           "}",
         ]
         .map(|s| s.to_string())
         .to_vec(),
+        order: 2,
       }]
       .to_vec(),
     );
@@ -149,91 +258,216 @@ mod test {
         ]
         .map(|s| s.to_string())
         .to_vec(),
+        order: 0,
       }]
       .to_vec(),
     );
-    let idl_meta_map = parser(&blocks_map);
-    // enum
-    let idl_metas_enum = idl_meta_map.get(&IdlType::Enum).unwrap();
-    assert_eq!(idl_metas_enum.len(), 1);
-    assert_eq!(idl_metas_enum[0].name, "enum1");
-    let enum_fields = &idl_metas_enum[0].fields;
-    assert_eq!(enum_fields.len(), 2);
-    fn test_enum_field(field: &IdlMetaField, key: &str, value: &str) {
-      match field {
-        IdlMetaField::Enum(f) => {
-          assert_eq!(f.key, key);
-          assert_eq!(f.value, value);
-        }
-        IdlMetaField::Interface(_) => {}
-      }
-    }
-    test_enum_field(&enum_fields[0], "Soft", "0");
-    test_enum_field(&enum_fields[1], "Hard", "1");
+    blocks_map.insert(
+      IdlType::Alias,
+      [CodeBlock {
+        name: "Bases".to_string(),
+        lines: ["typedef map<string, list<base.Base>> Bases".to_string()].to_vec(),
+        order: 1,
+      }]
+      .to_vec(),
+    );
+    let module = parser("foo", &blocks_map);
+    assert_eq!(module.namespace, "foo");
+    // the definitions are in the order they are declared, whatever their kinds
+    let names: Vec<&str> = module.definitions.iter().map(|d| d.name()).collect();
+    assert_eq!(names, ["enum1", "Bases", "interface1"]);
 
     // interface
-    let idl_metas_interface = idl_meta_map.get(&IdlType::Interface).unwrap();
-    assert_eq!(idl_metas_interface.len(), 1);
-    assert_eq!(idl_metas_interface[0].name, "interface1");
-    let interface_fields = &idl_metas_interface[0].fields;
-    assert_eq!(interface_fields.len(), 6);
-    fn test_interface_field(
-      field: &IdlMetaField,
-      name: &str,
-      optional: bool,
-      r#type: InterfaceFieldType,
-    ) {
-      match field {
-        IdlMetaField::Enum(_) => {}
-        IdlMetaField::Interface(f) => {
-          assert_eq!(f.name, name);
-          assert_eq!(f.optional, optional);
-          assert_eq!(f.r#type, r#type);
-          assert_eq!(f.comment, None);
-        }
-      }
-    }
-    test_interface_field(
-      &interface_fields[0],
-      "term_id",
-      false,
-      InterfaceFieldType::String,
+    let Definition::Record(record) = &module.definitions[2] else {
+      panic!("should be a record");
+    };
+    assert_eq!(record.name, "interface1");
+    let fields: Vec<(String, bool, String)> = record
+      .fields
+      .iter()
+      .map(|f| (f.name.clone(), f.optional, f.r#type.to_string()))
+      .collect();
+    let ids: Vec<Option<i32>> = record.fields.iter().map(|f| f.id).collect();
+    assert_eq!(ids, [1, 2, 30, 31, 32, 255].map(Some));
+    let required: Vec<&str> = record
+      .fields
+      .iter()
+      .filter(|f| f.required)
+      .map(|f| f.name.as_str())
+      .collect();
+    assert_eq!(required, ["user_id"]);
+    let answer = [
+      ("term_id", false, "i64"),
+      ("rules", false, "list<ScheduleRule>"),
+      ("org_id", false, "i64"),
+      ("job_key", false, "i64"),
+      ("user_id", false, "i64"),
+      ("Base", true, "base.Base"),
+    ]
+    .map(|(n, o, t)| (n.to_string(), o, t.to_string()));
+    assert_eq!(fields, answer);
+
+    // enum
+    let Definition::Enum(e) = &module.definitions[0] else {
+      panic!("should be an enum");
+    };
+    assert_eq!(e.name, "enum1");
+    let variants: Vec<(&str, &EnumValue)> = e
+      .variants
+      .iter()
+      .map(|v| (v.name.as_str(), &v.value))
+      .collect();
+    assert_eq!(
+      variants,
+      [("Soft", &EnumValue::Int(0)), ("Hard", &EnumValue::Int(1))]
     );
-    test_interface_field(
-      &interface_fields[1],
-      "rules",
-      false,
-      InterfaceFieldType::Refers(InterfaceFieldRefer {
-        refer: "ScheduleRule".to_string(),
-        namespace: [].to_vec(),
-      }),
+
+    // typedef
+    let Definition::Alias(alias) = &module.definitions[1] else {
+      panic!("should be an alias");
+    };
+    assert_eq!(alias.name, "Bases");
+    assert_eq!(alias.r#type.to_string(), "map<string, list<base.Base>>");
+  }
+
+  #[test]
+  fn test_parse_service() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      [CodeBlock {
+        name: "GetCourseReq".to_string(),
+        lines: [
+          "struct GetCourseReq {",
+          "  1: i64 id (api.path = 'id')",
+          "  2: optional i64 org_id(api.header = \"X-Org-Id\", go.tag = 'json:\"org_id\"') // org",
+          "}",
+        ]
+        .map(|s| s.to_string())
+        .to_vec(),
+        order: 0,
+      }]
+      .to_vec(),
     );
-    test_interface_field(
-      &interface_fields[2],
-      "org_id",
-      false,
-      InterfaceFieldType::String,
+    blocks_map.insert(
+      IdlType::Service,
+      [CodeBlock {
+        name: "CourseService extends base.BaseService".to_string(),
+        lines: [
+          "service CourseService extends base.BaseService {",
+          "  GetCourseResp GetCourse(1: GetCourseReq req) (api.get = '/course/:id') // get",
+          "  map<i64, list<string>> Tags(1:i64 id, 2: map<string, i32> filter)",
+          "    throws (1: base.Error err)",
+          "  oneway void Ping()",
+          "}",
+        ]
+        .map(|s| s.to_string())
+        .to_vec(),
+        order: 4,
+      }]
+      .to_vec(),
     );
-    test_interface_field(
-      &interface_fields[3],
-      "job_key",
-      false,
-      InterfaceFieldType::String,
+    let module = parser("course", &blocks_map);
+
+    let Definition::Record(req) = &module.definitions[0] else {
+      panic!("should be a record");
+    };
+    assert_eq!(req.fields[0].annotations["api.path"], "id");
+    assert_eq!(req.fields[1].annotations["api.header"], "X-Org-Id");
+    assert_eq!(req.fields[1].annotations["go.tag"], "json:\"org_id\"");
+
+    let Definition::Service(service) = &module.definitions[1] else {
+      panic!("should be a service");
+    };
+    assert_eq!(service.name, "CourseService");
+    let methods: Vec<(&str, String, Vec<String>, usize)> = service
+      .methods
+      .iter()
+      .map(|m| {
+        let params = m
+          .params
+          .iter()
+          .map(|p| format!("{}: {}", p.name, p.r#type))
+          .collect();
+        (
+          m.name.as_str(),
+          m.result.to_string(),
+          params,
+          m.throws.len(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      methods,
+      [
+        (
+          "GetCourse",
+          "GetCourseResp".to_string(),
+          vec!["req: GetCourseReq".to_string()],
+          0
+        ),
+        (
+          "Tags",
+          "map<i64, list<string>>".to_string(),
+          vec![
+            "id: i64".to_string(),
+            "filter: map<string, i32>".to_string()
+          ],
+          1
+        ),
+        ("Ping", "void".to_string(), Vec::new(), 0),
+      ]
+    );
+    assert_eq!(service.methods[0].annotations["api.get"], "/course/:id");
+  }
+
+  #[test]
+  fn test_parse_exception_and_union() {
+    let block = |name: &str, lines: &[&str], order: usize| CodeBlock {
+      name: name.to_string(),
+      lines: lines.iter().map(|s| s.to_string()).collect(),
+      order,
+    };
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![block(
+        "NotFound",
+        &["exception NotFound {", "  1: string message", "}"],
+        0,
+      )],
     );
-    test_interface_field(
-      &interface_fields[4],
-      "user_id",
-      false,
-      InterfaceFieldType::String,
+    blocks_map.insert(
+      IdlType::Alias,
+      vec![
+        block(
+          "Id",
+          &["union Id {", "  1: i64 num", "  2: base.Id base", "}"],
+          1,
+        ),
+        block("Key", &["typedef Id Key"], 2),
+      ],
     );
-    test_interface_field(
-      &interface_fields[5],
-      "Base",
-      true,
-      InterfaceFieldType::Refer(InterfaceFieldRefer {
-        refer: "Base".to_string(),
-        namespace: ["base".to_string()].to_vec(),
-      }),
+    let module = parser("foo", &blocks_map);
+    let Definition::Record(exception) = &module.definitions[0] else {
+      panic!("should be a record");
+    };
+    assert_eq!(exception.fields[0].name, "message");
+    let Definition::Record(union) = &module.definitions[1] else {
+      panic!("should be a record");
+    };
+    let fields: Vec<(&str, String, bool)> = union
+      .fields
+      .iter()
+      .map(|f| (f.name.as_str(), f.r#type.to_string(), f.optional))
+      .collect();
+    assert_eq!(
+      fields,
+      [
+        ("num", "i64".to_string(), true),
+        ("base", "base.Id".to_string(), true)
+      ]
     );
+    assert!(matches!(module.definitions[2], Definition::Alias(_)));
   }
 }
@@ -0,0 +1,328 @@
+use serde_json::Value as Json;
+
+use ridl_ir::schema::{
+  Alias, Annotations, Constant, Definition, Enum, EnumValue, EnumVariant, Field, Method, Module,
+  Record, Service, Value,
+};
+use ridl_ir::types::Type;
+use ridl_utils::types::{blocks_in_order, IdlBlocksMap, IdlType};
+
+/// Lower the type of a type id, e.g. `i64` or `list`, with its type spec, which describes the
+/// containers and the classes, e.g. `{"typeId": "list", "elemTypeId": "struct", "elemType":
+/// {"typeId": "struct", "class": "ek.common.Base"}}`. The classes of other files are qualified by
+/// their namespaces, which are resolved from their programs when the blocks are collected, and the
+/// bare classes are the local types.
+fn lower_type(type_id: &Json, spec: &Json) -> Type {
+  if let Some(class) = spec["class"].as_str() {
+    let mut namespace: Vec<String> = class.split('.').map(str::to_string).collect();
+    let name = namespace.pop().unwrap_or_default();
+    return Type::refer(&name, namespace);
+  }
+  match type_id.as_str().unwrap_or("") {
+    "bool" => Type::Bool,
+    "i8" | "byte" => Type::Int8,
+    "i16" => Type::Int16,
+    "i32" => Type::Int32,
+    "i64" => Type::Int64,
+    "double" => Type::Float64,
+    "string" => Type::String,
+    "binary" => Type::Bytes,
+    "void" => Type::Void,
+    "list" => Type::list(lower_type(&spec["elemTypeId"], &spec["elemType"])),
+    "set" => Type::set(lower_type(&spec["elemTypeId"], &spec["elemType"])),
+    "map" => Type::map(
+      lower_type(&spec["keyTypeId"], &spec["keyType"]),
+      lower_type(&spec["valueTypeId"], &spec["valueType"]),
+    ),
+    _ => Type::Any,
+  }
+}
+
+/// Lower a constant value by its type, the maps are json objects whose keys are strings.
+fn lower_value(value: &Json, r#type: &Type) -> Value {
+  match (value, r#type) {
+    (Json::Bool(b), _) => Value::Bool(*b),
+    (Json::Number(n), Type::Bool) => Value::Bool(n.as_i64() != Some(0)),
+    (Json::Number(n), Type::Float32 | Type::Float64) => Value::Float(n.as_f64().unwrap_or(0.0)),
+    (Json::Number(n), _) => match n.as_i64() {
+      Some(i) => Value::Int(i),
+      None => Value::Float(n.as_f64().unwrap_or(0.0)),
+    },
+    (Json::String(s), _) => Value::String(s.clone()),
+    (Json::Array(items), Type::List(item) | Type::Set(item)) => {
+      Value::List(items.iter().map(|v| lower_value(v, item)).collect())
+    }
+    (Json::Array(items), _) => {
+      Value::List(items.iter().map(|v| lower_value(v, &Type::Any)).collect())
+    }
+    (Json::Object(entries), _) => {
+      let (key_type, value_type) = match r#type {
+        Type::Map(key, value) => (key.as_ref(), value.as_ref()),
+        _ => (&Type::String, &Type::Any),
+      };
+      let entries = entries
+        .iter()
+        .map(|(k, v)| {
+          let key = match (key_type, k.parse::<i64>()) {
+            (Type::Int8 | Type::Int16 | Type::Int32 | Type::Int64, Ok(i)) => Value::Int(i),
+            _ => Value::String(k.clone()),
+          };
+          (key, lower_value(v, value_type))
+        })
+        .collect();
+      Value::Map(entries)
+    }
+    (Json::Null, _) => Value::List(Vec::new()),
+  }
+}
+
+fn doc_of(definition: &Json) -> Option<String> {
+  definition["doc"].as_str().map(|d| d.trim().to_string())
+}
+
+fn annotations_of(definition: &Json) -> Annotations {
+  definition["annotations"]
+    .as_object()
+    .into_iter()
+    .flatten()
+    .map(|(k, v)| {
+      let value = match v {
+        Json::String(s) => s.clone(),
+        // the repeated annotations are kept as arrays by some versions of the compiler
+        Json::Array(values) => {
+          let values: Vec<&str> = values.iter().filter_map(|v| v.as_str()).collect();
+          values.join(",")
+        }
+        v => v.to_string(),
+      };
+      (k.clone(), value)
+    })
+    .collect()
+}
+
+/// The fields are optional only if they are declared `optional`, the default requiredness of
+/// thrift (`req_out`) is always written.
+fn lower_fields(fields: &Json, all_optional: bool) -> Vec<Field> {
+  fields
+    .as_array()
+    .into_iter()
+    .flatten()
+    .map(|f| {
+      let r#type = lower_type(&f["typeId"], &f["type"]);
+      let optional = all_optional || f["required"] == "optional";
+      let mut field = Field::new(f["name"].as_str().unwrap_or(""), r#type, optional);
+      field.id = f["key"].as_i64().map(|k| k as i32);
+      field.required = f["required"] == "required";
+      field.doc = doc_of(f);
+      field.annotations = annotations_of(f);
+      field
+    })
+    .collect()
+}
+
+fn lower_definition(idl_type: &IdlType, d: &Json) -> Definition {
+  let name = d["name"].as_str().unwrap_or("").to_string();
+  let doc = doc_of(d);
+  match idl_type {
+    // the fields of unions are optional as only one of them is set
+    IdlType::Interface => Definition::Record(Record {
+      name,
+      fields: lower_fields(&d["fields"], d["isUnion"] == true),
+      doc,
+      annotations: annotations_of(d),
+    }),
+    IdlType::Enum => Definition::Enum(Enum {
+      name,
+      variants: d["members"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|m| EnumVariant {
+          name: m["name"].as_str().unwrap_or("").to_string(),
+          value: EnumValue::Int(m["value"].as_i64().unwrap_or(0)),
+        })
+        .collect(),
+      doc,
+    }),
+    IdlType::Alias => Definition::Alias(Alias {
+      name,
+      r#type: lower_type(&d["typeId"], &d["type"]),
+      doc,
+    }),
+    IdlType::Service => Definition::Service(Service {
+      name,
+      methods: d["functions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|f| Method {
+          name: f["name"].as_str().unwrap_or("").to_string(),
+          params: lower_fields(&f["arguments"], false),
+          result: lower_type(&f["returnTypeId"], &f["returnType"]),
+          throws: lower_fields(&f["exceptions"], false),
+          doc: doc_of(f),
+          annotations: annotations_of(f),
+        })
+        .collect(),
+      doc,
+      annotations: annotations_of(d),
+    }),
+    IdlType::Constant => {
+      let r#type = lower_type(&d["typeId"], &d["type"]);
+      Definition::Constant(Constant {
+        name,
+        value: lower_value(&d["value"], &r#type),
+        r#type,
+        doc,
+      })
+    }
+  }
+}
+
+/// Parse the blocks of the json generated by `thrift --gen json`, the types are already resolved
+/// by the compiler, e.g. the typedefs used by fields are replaced by their types.
+pub fn parser(namespace: &str, blocks_map: &IdlBlocksMap) -> Module {
+  let mut definitions = Vec::new();
+  for (k, b) in blocks_in_order(blocks_map) {
+    let d: Json = serde_json::from_str(b.lines.join("\n").as_str()).unwrap_or_default();
+    definitions.push(lower_definition(k, &d));
+  }
+  Module {
+    namespace: namespace.to_string(),
+    definitions,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::parser;
+  use ridl_ir::schema::{Definition, EnumValue, Value};
+  use ridl_utils::{
+    code_block::CodeBlock,
+    types::{IdlBlocksMap, IdlType},
+  };
+
+  fn block(name: &str, line: &str) -> CodeBlock {
+    CodeBlock {
+      name: name.to_string(),
+      lines: vec![line.to_string()],
+      order: 0,
+    }
+  }
+
+  #[test]
+  fn test_parser() {
+    let mut blocks_map = IdlBlocksMap::new();
+    blocks_map.insert(
+      IdlType::Interface,
+      vec![block(
+        "Course",
+        r#"{"name": "Course", "doc": " A course.\n", "isException": false, "isUnion": false, "fields": [
+  {"key": 1, "name": "id", "typeId": "i64", "required": "required", "annotations": {"go.tag": "json:\"id,string\""}},
+  {"key": 2, "name": "grades", "typeId": "list", "type": {"typeId": "list", "elemTypeId": "i32", "elemType": null}, "required": "optional"},
+  {"key": 3, "name": "lessons", "typeId": "map", "type": {"typeId": "map", "keyTypeId": "string", "valueTypeId": "struct", "valueType": {"typeId": "struct", "class": "Lesson"}}, "required": "req_out"},
+  {"key": 4, "name": "grade", "typeId": "enum", "type": {"typeId": "enum", "class": "Grade"}, "required": "req_out"},
+  {"key": 255, "name": "Base", "typeId": "struct", "type": {"typeId": "struct", "class": "ek.common.Base"}, "required": "optional"}
+]}"#,
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Enum,
+      vec![block(
+        "Grade",
+        r#"{"name": "Grade", "members": [{"name": "One", "value": 1}, {"name": "Two", "value": 2}]}"#,
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Alias,
+      vec![block(
+        "Ids",
+        r#"{"name": "Ids", "typeId": "set", "type": {"typeId": "set", "elemTypeId": "i64"}}"#,
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Service,
+      vec![block(
+        "CourseService",
+        r#"{"name": "CourseService", "functions": [{"name": "GetCourse", "returnTypeId": "struct", "returnType": {"typeId": "struct", "class": "Course"}, "oneway": false,
+  "arguments": [{"key": 1, "name": "id", "typeId": "i64", "required": "req_out"}],
+  "exceptions": [{"key": 1, "name": "err", "typeId": "exception", "type": {"typeId": "exception", "class": "ek.common.Error"}, "required": "req_out"}],
+  "annotations": {"api.get": "/course/:id"}}]}"#,
+      )],
+    );
+    blocks_map.insert(
+      IdlType::Constant,
+      vec![
+        block(
+          "LIMITS",
+          r#"{"name": "LIMITS", "typeId": "map", "type": {"typeId": "map", "keyTypeId": "i32", "valueTypeId": "double"}, "value": {"1": 1.5}}"#,
+        ),
+        block(
+          "TAGS",
+          r#"{"name": "TAGS", "typeId": "list", "type": {"typeId": "list", "elemTypeId": "string"}, "value": ["a", "b"]}"#,
+        ),
+      ],
+    );
+    let module = parser("course", &blocks_map);
+    let definitions = &module.definitions;
+
+    let Definition::Record(course) = &definitions[0] else {
+      panic!("should be a record");
+    };
+    assert_eq!(course.doc.as_deref(), Some("A course."));
+    let fields: Vec<(Option<i32>, &str, bool, String)> = course
+      .fields
+      .iter()
+      .map(|f| (f.id, f.name.as_str(), f.optional, f.r#type.to_string()))
+      .collect();
+    assert_eq!(
+      fields,
+      [
+        (Some(1), "id", false, "i64".to_string()),
+        (Some(2), "grades", true, "list<i32>".to_string()),
+        (Some(3), "lessons", false, "map<string, Lesson>".to_string()),
+        (Some(4), "grade", false, "Grade".to_string()),
+        (Some(255), "Base", true, "ek.common.Base".to_string()),
+      ]
+    );
+    assert_eq!(course.fields[0].annotations["go.tag"], "json:\"id,string\"");
+
+    let Definition::Enum(grade) = &definitions[1] else {
+      panic!("should be an enum");
+    };
+    assert_eq!(grade.variants[1].value, EnumValue::Int(2));
+
+    let Definition::Alias(ids) = &definitions[2] else {
+      panic!("should be an alias");
+    };
+    assert_eq!(ids.r#type.to_string(), "set<i64>");
+
+    let Definition::Service(service) = &definitions[3] else {
+      panic!("should be a service");
+    };
+    let method = &service.methods[0];
+    assert_eq!(method.name, "GetCourse");
+    assert_eq!(method.result.to_string(), "Course");
+    assert_eq!(method.params[0].r#type.to_string(), "i64");
+    assert_eq!(method.throws[0].r#type.to_string(), "ek.common.Error");
+    assert_eq!(method.annotations["api.get"], "/course/:id");
+
+    let Definition::Constant(limits) = &definitions[4] else {
+      panic!("should be a constant");
+    };
+    assert_eq!(
+      limits.value,
+      Value::Map(vec![(Value::Int(1), Value::Float(1.5))])
+    );
+    let Definition::Constant(tags) = &definitions[5] else {
+      panic!("should be a constant");
+    };
+    assert_eq!(
+      tags.value,
+      Value::List(vec![
+        Value::String("a".to_string()),
+        Value::String("b".to_string())
+      ])
+    );
+  }
+}
@@ -4,8 +4,14 @@ use regex::Regex;
 pub struct CodeBlock {
   pub name: String,
   pub lines: Vec<String>,
+  /// The order of the block among the blocks of all kinds of its namespace, e.g. the line of its
+  /// head in the file. The blocks of a later file of the namespace follow the ones of the earlier.
+  pub order: usize,
 }
 
+/// Search the blocks whose head line matches `re_head`. The head is matched with its indents
+/// trimmed, so `^struct` matches an indented `  struct S {` as well, while the lines inside a block
+/// are never taken for heads.
 pub fn search_blocks_from_source(
   source_str: &str,
   re_head: Regex,
@@ -29,15 +35,17 @@ pub fn search_blocks_from_source(
 
   let mut blocks: Vec<CodeBlock> = Vec::new();
   let lines = source_str.lines();
-  for line in lines {
+  for (i, line) in lines.enumerate() {
     // has not found the block head
     if !in_block {
-      // is match the block head
-      in_block = re_head.is_match(line);
+      // is match the block head, the indents before the head are ignored
+      let head = line.trim_start();
+      in_block = re_head.is_match(head);
       if in_block {
         blocks.push(CodeBlock {
-          name: get_name_from_block_head(line, &re_head, &re_edge_left),
+          name: get_name_from_block_head(head, &re_head, &re_edge_left),
           lines: Vec::new(),
+          order: i,
         })
       }
     }
@@ -234,3 +242,18 @@ fn test_invalid_nested_block() {
     search_blocks_from_source(source, Regex::new(r"^interface").unwrap(), None, None, None);
   assert_eq!(blocks.len(), 0);
 }
+
+#[test]
+fn test_indented_block_heads() {
+  let source = "  struct S1 {\n    1: i64 foo\n  }\n\tenum E1 {\n    Soft = 0\n  }\nstruct S2 {\n  struct_like: i64\n}\n";
+  let struct_blocks =
+    search_blocks_from_source(source, Regex::new(r"^struct").unwrap(), None, None, None);
+  let names: Vec<&str> = struct_blocks.iter().map(|b| b.name.as_str()).collect();
+  assert_eq!(names, ["S1", "S2"]);
+  // the head keeps its indents in the lines of the block
+  assert_eq!(struct_blocks[0].lines[0], "  struct S1 {");
+  let enum_blocks =
+    search_blocks_from_source(source, Regex::new(r"^enum").unwrap(), None, None, None);
+  assert_eq!(enum_blocks.len(), 1);
+  assert_eq!(enum_blocks[0].name, "E1");
+}
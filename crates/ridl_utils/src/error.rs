@@ -0,0 +1,46 @@
+use std::{error::Error, fmt, io};
+
+/// A type declared more than once in the same namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateType {
+  pub namespace: String,
+  pub name: String,
+  /// The files declaring the type, in the order they are collected.
+  pub files_path: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RidlError {
+  DuplicateTypes(Vec<DuplicateType>),
+  /// A file or folder failed to be read or written, e.g. `reading file a.thrift with error: ...`.
+  Io(String),
+}
+
+impl fmt::Display for RidlError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      RidlError::DuplicateTypes(duplicates) => {
+        write!(f, "found {} duplicate type(s):", duplicates.len())?;
+        for d in duplicates {
+          write!(
+            f,
+            "\n  `{}` in namespace `{}` is declared in: {}",
+            d.name,
+            d.namespace,
+            d.files_path.join(", ")
+          )?;
+        }
+        Ok(())
+      }
+      RidlError::Io(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl From<io::Error> for RidlError {
+  fn from(err: io::Error) -> Self {
+    RidlError::Io(err.to_string())
+  }
+}
+
+impl Error for RidlError {}
@@ -1,18 +1,73 @@
 use std::{
-  env,
-  fs::{metadata, read_dir, read_to_string},
-  sync::{Arc, Mutex},
+  collections::HashSet,
+  env, fs, io,
+  path::{Path, PathBuf},
+  sync::Mutex,
 };
 
-pub fn read_file_str_content(file_path: &str) -> String {
-  let content_str = match read_to_string(file_path) {
-    Ok(data) => data,
-    Err(err) => {
-      println!("reading file with error: {}", err);
-      "".to_string()
+use crate::glob::{globs_of, Glob};
+use crate::ignore::{IgnoreRules, IGNORE_FILES_NAME};
+use crate::pool::par_map;
+
+/// The file system all the inputs are read from and all the outputs are written into, so the
+/// whole pipeline can run on the disk as well as on the sources kept in memory.
+pub trait FileSystem: Send + Sync {
+  fn read_to_string(&self, path: &Path) -> io::Result<String>;
+  /// Write the content into the file, the missing parent folders are created.
+  fn write(&self, path: &Path, content: &str) -> io::Result<()>;
+  /// The paths of the direct children of the folder.
+  fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+  /// Whether the path is an existing folder, symbolic links are followed.
+  fn is_dir(&self, path: &Path) -> bool;
+  /// Whether the path is an existing file, symbolic links are followed.
+  fn is_file(&self, path: &Path) -> bool;
+  /// The unique absolute path of the file or folder, symbolic links are resolved.
+  fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// The real file system on the disk.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskFs;
+
+impl FileSystem for DiskFs {
+  fn read_to_string(&self, path: &Path) -> io::Result<String> {
+    fs::read_to_string(path)
+  }
+
+  fn write(&self, path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
     }
-  };
-  content_str
+    fs::write(path, content)
+  }
+
+  fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::read_dir(path)?
+      .map(|entry| entry.map(|e| e.path()))
+      .collect()
+  }
+
+  fn is_dir(&self, path: &Path) -> bool {
+    path.is_dir()
+  }
+
+  fn is_file(&self, path: &Path) -> bool {
+    path.is_file()
+  }
+
+  fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path)
+  }
+}
+
+/// Read the file, the error tells the path of the file, e.g. `reading file a.thrift with error: ...`.
+pub fn read_file_str_content(fs: &dyn FileSystem, file_path: &str) -> io::Result<String> {
+  fs.read_to_string(Path::new(file_path)).map_err(|err| {
+    io::Error::new(
+      err.kind(),
+      format!("reading file {} with error: {}", file_path, err),
+    )
+  })
 }
 
 pub fn get_absolute_path(rpath: &str) -> String {
@@ -22,27 +77,247 @@ pub fn get_absolute_path(rpath: &str) -> String {
   path
 }
 
-pub fn get_all_files_path_of_folder(folder_path: &str, postfix: &str) -> Arc<Mutex<Vec<String>>> {
-  // see https://stackoverflow.com/questions/30559073/cannot-borrow-captured-outer-variable-in-an-fn-closure-as-mutable about why using `Arc` and `Mutex`
-  let mut res = Arc::new(Mutex::new(Vec::<String>::new()));
-  struct Iter<'s> {
-    f: &'s dyn Fn(&Iter, &str) -> (),
-  }
-  let iter = Iter {
-    f: &|iter, path| {
-      let paths = read_dir(path).unwrap();
+/// The options to select the files of a folder.
+#[derive(Debug, Clone, Default)]
+pub struct FilesFilter {
+  /// Glob patterns of the relative paths to collect, all files are collected if it is empty.
+  pub include: Vec<String>,
+  /// Glob patterns of the relative paths to skip, a matched folder is not walked into.
+  pub exclude: Vec<String>,
+  /// Whether to skip the files and folders ignored by `.gitignore` and `.ridlignore`.
+  pub use_ignore_files: bool,
+  /// The maximum depth of folders to walk into, `Some(0)` only collects the files right inside the
+  /// root folder.
+  pub max_depth: Option<usize>,
+}
+
+struct WalkingFolder {
+  path: PathBuf,
+  /// The `/` separated path relative to the root folder.
+  rpath: String,
+  depth: usize,
+  ignore_rules: IgnoreRules,
+}
+
+/// Get the path of all files with any of the `postfixes` inside the folder, filtered by `files_filter`. The
+/// symbolic links are followed, while every folder is walked at most once so links pointing to
+/// their parent folders do not loop forever. Fails if any folder or ignore file can not be read.
+pub fn get_all_files_path_of_folder(
+  fs: &dyn FileSystem,
+  folder_path: &str,
+  postfixes: &[&str],
+  files_filter: &FilesFilter,
+) -> io::Result<Vec<PathBuf>> {
+  let include: Vec<Glob> = globs_of(&files_filter.include);
+  let exclude: Vec<Glob> = globs_of(&files_filter.exclude);
+  let walked_folders = Mutex::new(HashSet::<PathBuf>::new());
+  let is_first_walk = |path: &Path| match fs.canonicalize(path) {
+    Ok(path) => walked_folders.lock().unwrap().insert(path),
+    Err(_) => false,
+  };
+
+  let mut res = Vec::new();
+  // walk the folder level by level, the folders of the same level are read in parallel.
+  let root = PathBuf::from(folder_path);
+  is_first_walk(&root);
+  let mut folders = vec![WalkingFolder {
+    path: root,
+    rpath: String::new(),
+    depth: 0,
+    ignore_rules: IgnoreRules::default(),
+  }];
+  while !folders.is_empty() {
+    let walked = par_map(folders, |mut folder| -> io::Result<_> {
+      let mut sub_folders = Vec::new();
+      let mut files_path = Vec::new();
+      if files_filter.use_ignore_files {
+        for name in IGNORE_FILES_NAME {
+          let ignore_file_path = folder.path.join(name);
+          if fs.is_file(&ignore_file_path) {
+            let content = read_file_str_content(fs, ignore_file_path.to_str().unwrap())?;
+            folder.ignore_rules.add_rules(&folder.rpath, &content);
+          }
+        }
+      }
+
+      let paths = fs.read_dir(&folder.path).map_err(|err| {
+        io::Error::new(
+          err.kind(),
+          format!(
+            "reading folder {} with error: {}",
+            folder.path.display(),
+            err
+          ),
+        )
+      })?;
       for path in paths {
-        let path = path.unwrap().path();
-        let meta = metadata(path.clone()).unwrap();
-        let path = path.to_str().unwrap();
-        if meta.is_dir() {
-          (iter.f)(iter, path);
+        let name = match path.file_name() {
+          Some(name) => name.to_string_lossy().to_string(),
+          None => continue,
+        };
+        let is_folder = fs.is_dir(&path);
+        let rpath = if folder.rpath.is_empty() {
+          name.clone()
         } else {
-          res.lock().unwrap().push(String::from(path));
+          format!("{}/{}", folder.rpath, name)
+        };
+        if exclude.iter().any(|g| g.is_match(&rpath))
+          || folder.ignore_rules.is_ignored(&rpath, is_folder)
+        {
+          continue;
+        }
+
+        if is_folder {
+          let is_too_deep = files_filter
+            .max_depth
+            .is_some_and(|max_depth| folder.depth >= max_depth);
+          if !is_too_deep && is_first_walk(&path) {
+            sub_folders.push(WalkingFolder {
+              path,
+              rpath,
+              depth: folder.depth + 1,
+              ignore_rules: folder.ignore_rules.clone(),
+            });
+          }
+        } else if postfixes.iter().any(|p| name.ends_with(p))
+          && (include.is_empty() || include.iter().any(|g| g.is_match(&rpath)))
+        {
+          files_path.push(path);
         }
       }
-    },
-  };
-  (iter.f)(&iter, folder_path);
-  res
+      Ok((sub_folders, files_path))
+    });
+
+    folders = Vec::new();
+    for walked_folder in walked {
+      let (sub_folders, files_path) = walked_folder?;
+      folders.extend(sub_folders);
+      res.extend(files_path);
+    }
+  }
+  // the order of `read_dir` and threads are both uncertain.
+  res.sort();
+  Ok(res)
+}
+
+/// Write the file, the error tells the path of the file, e.g. `writing file a.ts with error: ...`.
+pub fn write_file_str_content(
+  fs: &dyn FileSystem,
+  file_path: &str,
+  content: &str,
+) -> io::Result<()> {
+  fs.write(Path::new(file_path), content).map_err(|err| {
+    io::Error::new(
+      err.kind(),
+      format!("writing file {} with error: {}", file_path, err),
+    )
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::{get_all_files_path_of_folder, write_file_str_content, DiskFs, FilesFilter};
+  use std::{env, fs::remove_dir_all, path::PathBuf};
+
+  fn create_folder(name: &str, files_path: &[&str]) -> PathBuf {
+    let root = env::temp_dir().join(format!("ridl_fs_{}_{}", name, std::process::id()));
+    let _ = remove_dir_all(&root);
+    for file_path in files_path {
+      write_file_str_content(&DiskFs, root.join(file_path).to_str().unwrap(), "").unwrap();
+    }
+    root
+  }
+
+  fn collect(root: &PathBuf, files_filter: &FilesFilter) -> Vec<String> {
+    get_all_files_path_of_folder(&DiskFs, root.to_str().unwrap(), &[".thrift"], files_filter)
+      .unwrap()
+      .iter()
+      .map(|p| {
+        let rpath = p.strip_prefix(root).unwrap();
+        rpath.to_str().unwrap().replace('\\', "/")
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_files_filter() {
+    let root = create_folder(
+      "filter",
+      &[
+        "base.thrift",
+        "README.md",
+        ".DS_Store",
+        "a/a.thrift",
+        "a/b/b.thrift",
+        "a/b/c/c.thrift",
+        "vendor/v.thrift",
+        "legacy/l.thrift",
+        "legacy/keep.thrift",
+      ],
+    );
+    write_file_str_content(
+      &DiskFs,
+      root.join(".gitignore").to_str().unwrap(),
+      "legacy/*\n!legacy/keep.thrift\n",
+    )
+    .unwrap();
+    write_file_str_content(
+      &DiskFs,
+      root.join("a/b/.ridlignore").to_str().unwrap(),
+      "c/\n",
+    )
+    .unwrap();
+
+    let all = collect(&root, &FilesFilter::default());
+    assert_eq!(
+      all,
+      [
+        "a/a.thrift",
+        "a/b/b.thrift",
+        "a/b/c/c.thrift",
+        "base.thrift",
+        "legacy/keep.thrift",
+        "legacy/l.thrift",
+        "vendor/v.thrift"
+      ]
+    );
+
+    let filtered = collect(
+      &root,
+      &FilesFilter {
+        exclude: vec!["vendor".to_string()],
+        use_ignore_files: true,
+        ..FilesFilter::default()
+      },
+    );
+    assert_eq!(
+      filtered,
+      [
+        "a/a.thrift",
+        "a/b/b.thrift",
+        "base.thrift",
+        "legacy/keep.thrift"
+      ]
+    );
+
+    let included = collect(
+      &root,
+      &FilesFilter {
+        include: vec!["a/**".to_string()],
+        max_depth: Some(1),
+        ..FilesFilter::default()
+      },
+    );
+    assert_eq!(included, ["a/a.thrift"]);
+    remove_dir_all(root).unwrap();
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_symlink_loop() {
+    let root = create_folder("symlink", &["a/a.thrift"]);
+    std::os::unix::fs::symlink(&root, root.join("a/loop")).unwrap();
+    assert_eq!(collect(&root, &FilesFilter::default()), ["a/a.thrift"]);
+    remove_dir_all(root).unwrap();
+  }
 }
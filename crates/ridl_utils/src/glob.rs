@@ -0,0 +1,158 @@
+use regex::Regex;
+
+/// A glob pattern matching the `/` separated relative path of a file or folder.
+///
+/// - `*` matches anything except `/`, `?` matches a single character except `/`
+/// - `**` matches any number of folders, e.g. `**/base.thrift` or `idl/**`
+/// - `[abc]`, `[a-z]` and `[!a-z]` match a single character of (or not of) the set
+/// - `{a,b}` matches any of the comma separated alternatives
+///
+/// A pattern without `/` only matches the name of the file or folder, which is the same as the
+/// pattern prefixed with `**/`.
+#[derive(Debug, Clone)]
+pub struct Glob {
+  pub pattern: String,
+  re: Regex,
+}
+
+impl Glob {
+  /// The patterns of invalid sets, e.g. `[]`, `[!]` or `[z-a]`, are errors.
+  pub fn new(pattern: &str) -> Result<Glob, regex::Error> {
+    let anchored = pattern.trim_start_matches('/');
+    let re_str = if pattern.contains('/') {
+      glob_to_re(anchored)
+    } else {
+      format!("(?:.*/)?{}", glob_to_re(anchored))
+    };
+    Ok(Glob {
+      pattern: pattern.to_string(),
+      re: Regex::new(format!("^{}$", re_str).as_str())?,
+    })
+  }
+
+  pub fn is_match(&self, rpath: &str) -> bool {
+    self.re.is_match(rpath)
+  }
+}
+
+fn glob_to_re(pattern: &str) -> String {
+  let chars: Vec<char> = pattern.chars().collect();
+  let mut re = String::new();
+  let mut in_alternatives = false;
+  let mut i = 0;
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      '*' if chars.get(i + 1) == Some(&'*') => {
+        let at_seg_beg = i == 0 || chars[i - 1] == '/';
+        if at_seg_beg && chars.get(i + 2) == Some(&'/') {
+          // `**/` matches zero or more folders
+          re.push_str("(?:.*/)?");
+          i += 3;
+          continue;
+        }
+        if at_seg_beg && i + 2 == chars.len() && i > 0 {
+          // trailing `/**` matches everything inside the folder, the `/` is already pushed
+          re.pop();
+          re.push_str("(?:/.*)?");
+          i += 2;
+          continue;
+        }
+        re.push_str(".*");
+        i += 2;
+        continue;
+      }
+      '*' => re.push_str("[^/]*"),
+      '?' => re.push_str("[^/]"),
+      '[' => match chars[i + 1..].iter().position(|c| *c == ']') {
+        Some(len) => {
+          let set: String = chars[i + 1..i + 1 + len].iter().collect();
+          let set = match set.strip_prefix('!') {
+            Some(s) => format!("^{}", s),
+            None => set,
+          };
+          re.push_str(format!("[{}]", set.replace('\\', "\\\\")).as_str());
+          i += len + 2;
+          continue;
+        }
+        None => re.push_str(regex::escape("[").as_str()),
+      },
+      '{' => {
+        in_alternatives = true;
+        re.push_str("(?:");
+      }
+      '}' if in_alternatives => {
+        in_alternatives = false;
+        re.push(')');
+      }
+      ',' if in_alternatives => re.push('|'),
+      _ => re.push_str(regex::escape(c.to_string().as_str()).as_str()),
+    }
+    i += 1;
+  }
+  re
+}
+
+/// The globs of the patterns, the invalid ones are skipped with a warning.
+pub fn globs_of(patterns: &[String]) -> Vec<Glob> {
+  patterns
+    .iter()
+    .filter_map(|p| match Glob::new(p) {
+      Ok(glob) => Some(glob),
+      Err(_) => {
+        eprintln!("invalid glob pattern {}, it is skipped", p);
+        None
+      }
+    })
+    .collect()
+}
+
+#[test]
+fn test_glob_name() {
+  let glob = Glob::new("*.thrift").unwrap();
+  assert!(glob.is_match("base.thrift"));
+  assert!(glob.is_match("a/b/base.thrift"));
+  assert!(!glob.is_match("base.thrift.bak"));
+  assert!(!glob.is_match("README.md"));
+
+  let glob = Glob::new("[!.]*.{thrift,proto}").unwrap();
+  assert!(glob.is_match("a/base.proto"));
+  assert!(glob.is_match("base.thrift"));
+  assert!(!glob.is_match("a/.base.thrift"));
+}
+
+#[test]
+fn test_glob_path() {
+  let glob = Glob::new("idl/*.thrift").unwrap();
+  assert!(glob.is_match("idl/base.thrift"));
+  assert!(!glob.is_match("idl/a/base.thrift"));
+  assert!(!glob.is_match("a/idl/base.thrift"));
+
+  let glob = Glob::new("/idl/**/base?.thrift").unwrap();
+  assert!(glob.is_match("idl/base1.thrift"));
+  assert!(glob.is_match("idl/a/b/base2.thrift"));
+  assert!(!glob.is_match("idl/a/b/base.thrift"));
+
+  let glob = Glob::new("vendor/**").unwrap();
+  assert!(glob.is_match("vendor"));
+  assert!(glob.is_match("vendor/a/b.thrift"));
+  assert!(!glob.is_match("vendors/b.thrift"));
+}
+
+#[test]
+fn test_glob_invalid() {
+  for pattern in ["[]", "[!]", "[z-a].thrift"] {
+    assert!(Glob::new(pattern).is_err(), "{}", pattern);
+  }
+  // an unclosed `[` is a literal
+  assert!(Glob::new("[a.thrift").unwrap().is_match("[a.thrift"));
+
+  let globs = globs_of(&["[z-a]", "*.thrift"].map(String::from));
+  assert_eq!(
+    globs
+      .iter()
+      .map(|g| g.pattern.as_str())
+      .collect::<Vec<&str>>(),
+    ["*.thrift"]
+  );
+}
@@ -0,0 +1,109 @@
+use crate::glob::Glob;
+
+/// The ignore files read from every walked folder.
+pub const IGNORE_FILES_NAME: [&str; 2] = [".gitignore", ".ridlignore"];
+
+/// A rule of an ignore file, which follows the syntax of `.gitignore`.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+  /// The `/` separated relative path of the folder containing the ignore file.
+  base: String,
+  glob: Glob,
+  /// Rules starting with `!` re-include the paths ignored by previous rules.
+  negated: bool,
+  /// Rules ending with `/` only match folders.
+  only_folder: bool,
+}
+
+/// All the ignore rules applied to a folder, including the ones inherited from its parent folders.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+  rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+  /// Add the rules of an ignore file located in the folder `base`.
+  pub fn add_rules(&mut self, base: &str, content: &str) {
+    for line in content.lines() {
+      let line = line.trim_end();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let (negated, pattern) = match line.strip_prefix('!') {
+        Some(p) => (true, p),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+      };
+      let (only_folder, pattern) = match pattern.strip_suffix('/') {
+        Some(p) => (true, p),
+        None => (false, pattern),
+      };
+      if pattern.is_empty() {
+        continue;
+      }
+      let Ok(glob) = Glob::new(pattern) else {
+        eprintln!("invalid ignore rule {} in {}, it is skipped", line, base);
+        continue;
+      };
+      self.rules.push(IgnoreRule {
+        base: base.to_string(),
+        glob,
+        negated,
+        only_folder,
+      });
+    }
+  }
+
+  /// Whether the file or folder of the relative path `rpath` is ignored, the last matched rule
+  /// wins.
+  pub fn is_ignored(&self, rpath: &str, is_folder: bool) -> bool {
+    let mut ignored = false;
+    for rule in &self.rules {
+      if rule.only_folder && !is_folder {
+        continue;
+      }
+      let rpath_to_base = if rule.base.is_empty() {
+        Some(rpath)
+      } else {
+        rpath
+          .strip_prefix(rule.base.as_str())
+          .and_then(|p| p.strip_prefix('/'))
+      };
+      if let Some(p) = rpath_to_base {
+        if rule.glob.is_match(p) {
+          ignored = !rule.negated;
+        }
+      }
+    }
+    ignored
+  }
+}
+
+#[test]
+fn test_ignore_rules() {
+  let mut rules = IgnoreRules::default();
+  rules.add_rules(
+    "",
+    r#"
+# comment
+*.bak
+/build
+[z-a]
+tmp/
+!keep.bak
+"#,
+  );
+  rules.add_rules("idl", "legacy/*.thrift\n");
+  // the invalid rule is skipped
+  assert_eq!(rules.rules.len(), 5);
+
+  assert!(rules.is_ignored("a.bak", false));
+  assert!(rules.is_ignored("a/b/a.bak", false));
+  assert!(!rules.is_ignored("a/keep.bak", false));
+  assert!(rules.is_ignored("build", true));
+  assert!(!rules.is_ignored("a/build", true));
+  assert!(rules.is_ignored("a/tmp", true));
+  assert!(!rules.is_ignored("a/tmp", false));
+  assert!(rules.is_ignored("idl/legacy/base.thrift", false));
+  assert!(!rules.is_ignored("legacy/base.thrift", false));
+  assert!(!rules.is_ignored("idl/base.thrift", false));
+}
@@ -0,0 +1,263 @@
+/// The tokens of a C-like schema language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+  /// Identifiers and keywords, e.g. `message`, `int32` or `foo_bar`.
+  Ident(String),
+  /// Integers and floats, a leading `-` is a separated `Symbol`.
+  Number(String),
+  /// The content of a single, double or triple double quoted string, without the quotes.
+  Str(String),
+  Symbol(char),
+}
+
+impl Token {
+  pub fn is_ident(&self, ident: &str) -> bool {
+    matches!(self, Token::Ident(i) if i == ident)
+  }
+
+  pub fn is_symbol(&self, symbol: char) -> bool {
+    matches!(self, Token::Symbol(s) if *s == symbol)
+  }
+}
+
+/// Split the source into tokens, the block comments `/* */` and the line comments started by any
+/// of `line_comments` are skipped.
+pub fn tokenize(source_str: &str, line_comments: &[&str]) -> Vec<Token> {
+  let chars: Vec<char> = source_str.chars().collect();
+  let starts_with = |i: usize, s: &str| {
+    s.chars()
+      .enumerate()
+      .all(|(j, c)| chars.get(i + j) == Some(&c))
+  };
+
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  while i < chars.len() {
+    let c = chars[i];
+    if c.is_whitespace() {
+      i += 1;
+    } else if line_comments.iter().any(|s| starts_with(i, s)) {
+      while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+      }
+    } else if starts_with(i, "/*") {
+      i += 2;
+      while i < chars.len() && !starts_with(i, "*/") {
+        i += 1;
+      }
+      i += 2;
+    } else if c.is_alphabetic() || c == '_' {
+      let beg = i;
+      while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+        i += 1;
+      }
+      tokens.push(Token::Ident(chars[beg..i].iter().collect()));
+    } else if c.is_ascii_digit() {
+      let beg = i;
+      while i < chars.len()
+        && (chars[i].is_ascii_alphanumeric()
+          || chars[i] == '.'
+          || ((chars[i] == '-' || chars[i] == '+') && matches!(chars[i - 1], 'e' | 'E')))
+      {
+        i += 1;
+      }
+      tokens.push(Token::Number(chars[beg..i].iter().collect()));
+    } else if starts_with(i, "\"\"\"") {
+      // the block strings are kept as they are, e.g. the descriptions of graphql
+      i += 3;
+      let beg = i;
+      while i < chars.len() && !starts_with(i, "\"\"\"") {
+        i += 1;
+      }
+      tokens.push(Token::Str(chars[beg..i].iter().collect()));
+      i += 3;
+    } else if c == '"' || c == '\'' {
+      i += 1;
+      let mut s = String::new();
+      while i < chars.len() && chars[i] != c {
+        if chars[i] == '\\' && i + 1 < chars.len() {
+          i += 1;
+        }
+        s.push(chars[i]);
+        i += 1;
+      }
+      i += 1;
+      tokens.push(Token::Str(s));
+    } else {
+      tokens.push(Token::Symbol(c));
+      i += 1;
+    }
+  }
+  tokens
+}
+
+/// A cursor to walk through the tokens.
+pub struct Tokens {
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl Tokens {
+  pub fn new(tokens: Vec<Token>) -> Tokens {
+    Tokens { tokens, pos: 0 }
+  }
+
+  pub fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+
+  pub fn peek_nth(&self, n: usize) -> Option<&Token> {
+    self.tokens.get(self.pos + n)
+  }
+
+  pub fn next_token(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.pos).cloned();
+    self.pos += 1;
+    token
+  }
+
+  pub fn is_end(&self) -> bool {
+    self.pos >= self.tokens.len()
+  }
+
+  /// Consume the next token if it is the symbol.
+  pub fn eat_symbol(&mut self, symbol: char) -> bool {
+    let is_matched = self.peek().is_some_and(|t| t.is_symbol(symbol));
+    if is_matched {
+      self.pos += 1;
+    }
+    is_matched
+  }
+
+  /// Consume the next token if it is the identifier.
+  pub fn eat_ident(&mut self, ident: &str) -> bool {
+    let is_matched = self.peek().is_some_and(|t| t.is_ident(ident));
+    if is_matched {
+      self.pos += 1;
+    }
+    is_matched
+  }
+
+  /// Consume the next token and return it as a string if it is an identifier.
+  pub fn ident(&mut self) -> Option<String> {
+    match self.peek() {
+      Some(Token::Ident(i)) => {
+        let i = i.clone();
+        self.pos += 1;
+        Some(i)
+      }
+      _ => None,
+    }
+  }
+
+  /// Consume a dot separated name, e.g. `google.protobuf.Timestamp` or `.foo.Bar`.
+  pub fn dotted_ident(&mut self) -> Option<String> {
+    let mut name = String::new();
+    if self.eat_symbol('.') {
+      name.push('.');
+    }
+    name.push_str(self.ident()?.as_str());
+    while self.peek().is_some_and(|t| t.is_symbol('.'))
+      && matches!(self.peek_nth(1), Some(Token::Ident(_)))
+    {
+      self.pos += 1;
+      name.push('.');
+      name.push_str(self.ident().unwrap().as_str());
+    }
+    Some(name)
+  }
+
+  /// Skip the tokens until the symbol is consumed, the nested brackets are skipped as a whole.
+  pub fn skip_until(&mut self, symbol: char) {
+    while let Some(token) = self.next_token() {
+      if token.is_symbol(symbol) {
+        return;
+      }
+      match token {
+        Token::Symbol('{') => self.skip_until('}'),
+        Token::Symbol('[') => self.skip_until(']'),
+        Token::Symbol('(') => self.skip_until(')'),
+        _ => {}
+      }
+    }
+  }
+
+  /// Skip a statement, which ends with `;` or a `{ }` block.
+  pub fn skip_statement(&mut self) {
+    while let Some(token) = self.next_token() {
+      match token {
+        Token::Symbol(';') => return,
+        Token::Symbol('{') => {
+          self.skip_until('}');
+          self.eat_symbol(';');
+          return;
+        }
+        Token::Symbol('[') => self.skip_until(']'),
+        Token::Symbol('(') => self.skip_until(')'),
+        _ => {}
+      }
+    }
+  }
+}
+
+#[test]
+fn test_tokenize() {
+  let tokens = tokenize(
+    r#"message Foo { // comment
+  /* block
+  comment */ repeated .foo.Bar bar = 1 [json_name = 'b\'ar'];
+  # not a comment here
+  double d = -1.5e-3;
+}"#,
+    &["//"],
+  );
+  assert_eq!(
+    tokens,
+    [
+      Token::Ident("message".to_string()),
+      Token::Ident("Foo".to_string()),
+      Token::Symbol('{'),
+      Token::Ident("repeated".to_string()),
+      Token::Symbol('.'),
+      Token::Ident("foo".to_string()),
+      Token::Symbol('.'),
+      Token::Ident("Bar".to_string()),
+      Token::Ident("bar".to_string()),
+      Token::Symbol('='),
+      Token::Number("1".to_string()),
+      Token::Symbol('['),
+      Token::Ident("json_name".to_string()),
+      Token::Symbol('='),
+      Token::Str("b'ar".to_string()),
+      Token::Symbol(']'),
+      Token::Symbol(';'),
+      Token::Symbol('#'),
+      Token::Ident("not".to_string()),
+      Token::Ident("a".to_string()),
+      Token::Ident("comment".to_string()),
+      Token::Ident("here".to_string()),
+      Token::Ident("double".to_string()),
+      Token::Ident("d".to_string()),
+      Token::Symbol('='),
+      Token::Symbol('-'),
+      Token::Number("1.5e-3".to_string()),
+      Token::Symbol(';'),
+      Token::Symbol('}'),
+    ]
+  );
+
+  assert_eq!(
+    tokenize("\"\"\"a \"quoted\" {\n\"\"\"\ntype", &["#"]),
+    [
+      Token::Str("a \"quoted\" {\n".to_string()),
+      Token::Ident("type".to_string())
+    ]
+  );
+
+  let mut tokens = Tokens::new(tokenize("a.b.C d; { x { y } } z", &["#"]));
+  assert_eq!(tokens.dotted_ident(), Some("a.b.C".to_string()));
+  tokens.skip_statement();
+  tokens.skip_statement();
+  assert_eq!(tokens.ident(), Some("z".to_string()));
+  assert!(tokens.is_end());
+}
@@ -1,3 +1,9 @@
 pub mod code_block;
+pub mod error;
 pub mod fs;
+pub mod glob;
+pub mod ignore;
+pub mod lexer;
+pub mod memory_fs;
+pub mod pool;
 pub mod types;
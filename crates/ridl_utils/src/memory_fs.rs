@@ -0,0 +1,153 @@
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  io,
+  path::{Component, Path, PathBuf},
+  sync::RwLock,
+};
+
+use crate::fs::FileSystem;
+
+/// A file system keeping all the files in memory, e.g. for tests, wasm or the unsaved buffers of an
+/// editor. Folders are not stored, a folder exists as long as there are files inside it.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+  files: RwLock<BTreeMap<PathBuf, String>>,
+}
+
+impl MemoryFs {
+  pub fn new() -> MemoryFs {
+    MemoryFs::default()
+  }
+
+  /// Create the file system with the files of (path, content).
+  pub fn from_files(files: &[(&str, &str)]) -> MemoryFs {
+    let fs = MemoryFs::new();
+    for (path, content) in files {
+      fs.write(Path::new(path), content).unwrap();
+    }
+    fs
+  }
+
+  /// The path of all the files, sorted.
+  pub fn files_path(&self) -> Vec<PathBuf> {
+    self.files.read().unwrap().keys().cloned().collect()
+  }
+}
+
+/// Normalize the path lexically, the `.` components are removed and the `..` components pop their
+/// parents, so every file has a unique key.
+fn normalize(path: &Path) -> PathBuf {
+  let mut res = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        res.pop();
+      }
+      c => res.push(c),
+    }
+  }
+  res
+}
+
+fn not_found(path: &Path) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::NotFound,
+    format!("{} is not found", path.display()),
+  )
+}
+
+impl FileSystem for MemoryFs {
+  fn read_to_string(&self, path: &Path) -> io::Result<String> {
+    let files = self.files.read().unwrap();
+    files.get(&normalize(path)).cloned().ok_or(not_found(path))
+  }
+
+  fn write(&self, path: &Path, content: &str) -> io::Result<()> {
+    if self.is_dir(path) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{} is a folder", path.display()),
+      ));
+    }
+    let mut files = self.files.write().unwrap();
+    files.insert(normalize(path), content.to_string());
+    Ok(())
+  }
+
+  fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+    let folder = normalize(path);
+    let files = self.files.read().unwrap();
+    let names: BTreeSet<_> = files
+      .keys()
+      .filter_map(|p| p.strip_prefix(&folder).ok())
+      .filter_map(|p| p.components().next())
+      .map(|c| c.as_os_str().to_os_string())
+      .collect();
+    if names.is_empty() {
+      return Err(not_found(path));
+    }
+    Ok(names.into_iter().map(|name| path.join(name)).collect())
+  }
+
+  fn is_dir(&self, path: &Path) -> bool {
+    let folder = normalize(path);
+    let files = self.files.read().unwrap();
+    files.keys().any(|p| p.starts_with(&folder) && *p != folder)
+  }
+
+  fn is_file(&self, path: &Path) -> bool {
+    self.files.read().unwrap().contains_key(&normalize(path))
+  }
+
+  fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+    if self.is_file(path) || self.is_dir(path) {
+      Ok(normalize(path))
+    } else {
+      Err(not_found(path))
+    }
+  }
+}
+
+#[test]
+fn test_memory_fs() {
+  let fs = MemoryFs::from_files(&[("idl/a.thrift", "a"), ("./idl/b/b.thrift", "b")]);
+  assert_eq!(
+    fs.read_to_string(Path::new("idl/b/../a.thrift")).unwrap(),
+    "a"
+  );
+  assert!(fs.read_to_string(Path::new("idl/c.thrift")).is_err());
+  assert!(fs.is_dir(Path::new("./idl")));
+  assert!(fs.is_dir(Path::new("idl/b")));
+  assert!(!fs.is_dir(Path::new("idl/a.thrift")));
+  assert!(fs.is_file(Path::new("idl/a.thrift")));
+  assert!(fs.write(Path::new("idl/b"), "").is_err());
+  assert_eq!(
+    fs.read_dir(Path::new("./idl")).unwrap(),
+    [PathBuf::from("./idl/a.thrift"), PathBuf::from("./idl/b")]
+  );
+}
+
+#[test]
+fn test_walk_memory_fs() {
+  use crate::fs::{get_all_files_path_of_folder, FilesFilter};
+
+  let fs = MemoryFs::from_files(&[
+    ("idl/a.thrift", ""),
+    ("idl/README.md", ""),
+    ("idl/.ridlignore", "legacy/\n"),
+    ("idl/legacy/l.thrift", ""),
+    ("idl/b/b.thrift", ""),
+  ]);
+  let files_filter = FilesFilter {
+    use_ignore_files: true,
+    ..FilesFilter::default()
+  };
+  assert_eq!(
+    get_all_files_path_of_folder(&fs, "idl", &[".thrift"], &files_filter).unwrap(),
+    [
+      PathBuf::from("idl/a.thrift"),
+      PathBuf::from("idl/b/b.thrift")
+    ]
+  );
+}
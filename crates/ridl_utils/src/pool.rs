@@ -0,0 +1,73 @@
+use std::{
+  num::NonZeroUsize,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+  },
+  thread,
+};
+
+/// The number of worker threads, which is equal to the available parallelism of the machine.
+pub fn threads_count() -> usize {
+  thread::available_parallelism()
+    .map(NonZeroUsize::get)
+    .unwrap_or(1)
+}
+
+/// Map all the items with `f` across a pool of scoped worker threads.
+///
+/// Workers keep taking the next unhandled item until all of them are consumed, and every result is
+/// put back into the slot of its item, so the returned vector always keeps the order of `items` no
+/// matter how the threads are scheduled.
+pub fn par_map<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+  T: Send,
+  R: Send,
+  F: Fn(T) -> R + Sync,
+{
+  let len = items.len();
+  let workers = threads_count().min(len);
+  if workers <= 1 {
+    return items.into_iter().map(f).collect();
+  }
+
+  let next_idx = AtomicUsize::new(0);
+  let items: Vec<Mutex<Option<T>>> = items.into_iter().map(|i| Mutex::new(Some(i))).collect();
+  let results: Vec<Mutex<Option<R>>> = (0..len).map(|_| Mutex::new(None)).collect();
+  thread::scope(|s| {
+    for _ in 0..workers {
+      s.spawn(|| loop {
+        let idx = next_idx.fetch_add(1, Ordering::Relaxed);
+        if idx >= len {
+          break;
+        }
+        let item = items[idx].lock().unwrap().take().unwrap();
+        let res = f(item);
+        *results[idx].lock().unwrap() = Some(res);
+      });
+    }
+  });
+  results
+    .into_iter()
+    .map(|r| r.into_inner().unwrap().unwrap())
+    .collect()
+}
+
+#[test]
+fn test_par_map_keeps_order() {
+  let items: Vec<usize> = (0..1000).collect();
+  let res = par_map(items.clone(), |i| {
+    // make the later items finish earlier
+    if i % 7 == 0 {
+      thread::yield_now();
+    }
+    i * 2
+  });
+  assert_eq!(res, items.iter().map(|i| i * 2).collect::<Vec<usize>>());
+}
+
+#[test]
+fn test_par_map_empty() {
+  let res: Vec<usize> = par_map(Vec::<usize>::new(), |i| i);
+  assert_eq!(res.len(), 0);
+}
@@ -1,24 +1,61 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::code_block::CodeBlock;
 
 /// Commons
 pub enum IdlProtocolType {
   Thrift,
+  Protobuf,
+  Avro,
+  GraphQL,
+  /// OpenAPI 3 documents (or swagger 2) and json schemas.
+  OpenApi,
+  /// The json descriptions generated by `thrift --gen json`.
+  ThriftJson,
+  FlatBuffers,
+  CapnProto,
 }
 
 impl IdlProtocolType {
-  pub fn as_str(&self) -> &'static str {
+  /// The protocol named on the command line, e.g. `thrift` or `proto`.
+  pub fn from_name(name: &str) -> Option<IdlProtocolType> {
+    let protocol_type = match name.to_lowercase().as_str() {
+      "thrift" => IdlProtocolType::Thrift,
+      "protobuf" | "proto" => IdlProtocolType::Protobuf,
+      "avro" => IdlProtocolType::Avro,
+      "graphql" => IdlProtocolType::GraphQL,
+      "openapi" | "jsonschema" => IdlProtocolType::OpenApi,
+      "thrift-json" => IdlProtocolType::ThriftJson,
+      "flatbuffers" | "fbs" => IdlProtocolType::FlatBuffers,
+      "capnp" | "capnproto" => IdlProtocolType::CapnProto,
+      _ => return None,
+    };
+    Some(protocol_type)
+  }
+
+  /// The postfixes of the idl files.
+  pub fn postfixes(&self) -> &'static [&'static str] {
     match self {
-      IdlProtocolType::Thrift => ".thrift",
+      IdlProtocolType::Thrift => &[".thrift"],
+      IdlProtocolType::Protobuf => &[".proto"],
+      IdlProtocolType::Avro => &[".avsc", ".avpr", ".avdl"],
+      IdlProtocolType::GraphQL => &[".graphql", ".graphqls", ".gql"],
+      IdlProtocolType::OpenApi => &[".yaml", ".yml", ".json"],
+      IdlProtocolType::ThriftJson => &[".json"],
+      IdlProtocolType::FlatBuffers => &[".fbs"],
+      IdlProtocolType::CapnProto => &[".capnp"],
     }
   }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum IdlType {
   Interface,
   Enum,
+  /// A named type standing for other types, e.g. an union of types or a custom scalar.
+  Alias,
+  Service,
+  Constant,
 }
 
 impl IdlType {
@@ -26,10 +63,25 @@ impl IdlType {
     match self {
       IdlType::Interface => "struct",
       IdlType::Enum => "enum",
+      IdlType::Alias => "typedef",
+      IdlType::Service => "service",
+      IdlType::Constant => "const",
     }
   }
 }
 
 /// Blocks
-pub type IdlBlocksMap = HashMap<IdlType, Vec<CodeBlock>>;
-pub type NamespaceIdlBlocksMap = HashMap<String, IdlBlocksMap>;
+/// Ordered maps are used so that iterating them is stable between runs: idl types follow the order
+/// of `IdlType`, namespaces are sorted, and the blocks of each idl type keep their source order.
+pub type IdlBlocksMap = BTreeMap<IdlType, Vec<CodeBlock>>;
+pub type NamespaceIdlBlocksMap = BTreeMap<String, IdlBlocksMap>;
+
+/// The blocks of all idl types in the order they are declared.
+pub fn blocks_in_order(blocks_map: &IdlBlocksMap) -> Vec<(&IdlType, &CodeBlock)> {
+  let mut blocks: Vec<(&IdlType, &CodeBlock)> = blocks_map
+    .iter()
+    .flat_map(|(k, v)| v.iter().map(move |b| (k, b)))
+    .collect();
+  blocks.sort_by_key(|(_, b)| b.order);
+  blocks
+}